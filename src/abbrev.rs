@@ -0,0 +1,263 @@
+//! Abbreviation of 64-bit hashes to their shortest unambiguous hex prefixes.
+
+const HEX_DIGITS: usize = 16;
+
+/// The number of leading hex digits shared by `a` and `b`.
+fn common_hex_digits(a: u64, b: u64) -> usize {
+    ((a ^ b).leading_zeros() / 4) as usize
+}
+
+/// Builds a table of the shortest unique hex prefixes of a set of hashes.
+///
+/// Each hash is abbreviated to the shortest prefix of its 16 digit,
+/// zero-padded, lowercase hex representation which is at least `min_len`
+/// digits long and is a prefix of no other hash in the set, as Git
+/// abbreviates object IDs. `min_len` is clamped to `1..=16`, so every
+/// abbreviation has at least one digit and at most sixteen.
+///
+/// Duplicate hashes are the same ID, so they are kept once and do not
+/// lengthen each other's abbreviation.
+///
+/// ```
+/// use lz_fnv::{abbreviate, Resolution};
+///
+/// let table = abbreviate(vec![0x8594_4171_f739_67e8, 0x8594_aaaa_0000_0000, 0x1234], 4);
+///
+/// assert_eq!(table.abbreviation(0x8594_4171_f739_67e8).unwrap(), "85944");
+/// assert_eq!(table.abbreviation(0x1234).unwrap(), "0000");
+/// assert_eq!(table.resolve("8594"), Resolution::Ambiguous(2));
+/// ```
+pub fn abbreviate<I: IntoIterator<Item = u64>>(hashes: I, min_len: usize) -> AbbrevTable {
+    let min_len = min_len.clamp(1, HEX_DIGITS);
+    let mut hashes: Vec<u64> = hashes.into_iter().collect();
+    hashes.sort_unstable();
+    hashes.dedup();
+
+    let entries = (0..hashes.len())
+        .map(|i| {
+            let mut shared = 0;
+            if i > 0 {
+                shared = shared.max(common_hex_digits(hashes[i], hashes[i - 1]));
+            }
+            if i + 1 < hashes.len() {
+                shared = shared.max(common_hex_digits(hashes[i], hashes[i + 1]));
+            }
+
+            (hashes[i], (shared + 1).max(min_len))
+        })
+        .collect();
+
+    AbbrevTable { entries }
+}
+
+/// The result of resolving an abbreviated hash with [`AbbrevTable::resolve`].
+///
+/// [`AbbrevTable::resolve`]: struct.AbbrevTable.html#method.resolve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Exactly one hash has the prefix.
+    Unique(u64),
+    /// The specified number of hashes, at least two, have the prefix.
+    Ambiguous(usize),
+    /// No hash has the prefix, or the prefix is not valid hex of at most 16
+    /// digits.
+    NotFound,
+}
+
+/// A set of hashes with their shortest unique hex prefixes, built by
+/// [`abbreviate`].
+///
+/// [`abbreviate`]: fn.abbreviate.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbbrevTable {
+    /// The distinct hashes in ascending order, each with the number of hex
+    /// digits in its abbreviation.
+    entries: Vec<(u64, usize)>,
+}
+
+impl AbbrevTable {
+    /// Returns the abbreviation of `hash`, or `None` if it is not in the
+    /// table.
+    pub fn abbreviation(&self, hash: u64) -> Option<String> {
+        self.abbreviation_len(hash)
+            .map(|len| format!("{:016x}", hash)[..len].to_owned())
+    }
+
+    /// Returns the number of hex digits in the abbreviation of `hash`, or
+    /// `None` if it is not in the table.
+    pub fn abbreviation_len(&self, hash: u64) -> Option<usize> {
+        self.entries
+            .binary_search_by_key(&hash, |&(hash, _)| hash)
+            .ok()
+            .map(|i| self.entries[i].1)
+    }
+
+    /// Returns an iterator over the distinct hashes and their abbreviations,
+    /// in ascending order of hash.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, String)> + '_ {
+        self.entries
+            .iter()
+            .map(|&(hash, len)| (hash, format!("{:016x}", hash)[..len].to_owned()))
+    }
+
+    /// Returns the number of distinct hashes in the table.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the table has no hashes.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Finds the hashes whose hex representation starts with `prefix`.
+    ///
+    /// The prefix may be in either case, and need not be as long as the
+    /// abbreviation the table gives. The empty prefix matches every hash.
+    ///
+    /// ```
+    /// use lz_fnv::{abbreviate, Resolution};
+    ///
+    /// let table = abbreviate(vec![0xabcd_0000_0000_0000, 0xabce_0000_0000_0000], 1);
+    ///
+    /// assert_eq!(table.resolve("ABCD"), Resolution::Unique(0xabcd_0000_0000_0000));
+    /// assert_eq!(table.resolve("abc"), Resolution::Ambiguous(2));
+    /// assert_eq!(table.resolve("f"), Resolution::NotFound);
+    /// ```
+    pub fn resolve(&self, prefix: &str) -> Resolution {
+        if prefix.len() > HEX_DIGITS || !prefix.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Resolution::NotFound;
+        }
+
+        let (low, high) = if prefix.is_empty() {
+            (0, u64::MAX)
+        } else {
+            let shift = 4 * (HEX_DIGITS - prefix.len()) as u32;
+            let value = u64::from_str_radix(prefix, 16).expect("the prefix is valid hex");
+            let low = value.checked_shl(shift).unwrap_or(0);
+            (low, low | (u64::MAX.checked_shr(64 - shift).unwrap_or(0)))
+        };
+
+        let start = self.entries.partition_point(|&(hash, _)| hash < low);
+        let end = self.entries.partition_point(|&(hash, _)| hash <= high);
+
+        match end - start {
+            0 => Resolution::NotFound,
+            1 => Resolution::Unique(self.entries[start].0),
+            count => Resolution::Ambiguous(count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {abbreviate, Resolution};
+
+    #[test]
+    fn shared_prefixes_lengthen_abbreviations() {
+        let hashes = [
+            0x1234_5678_0000_0000,
+            0x1234_5679_0000_0000,
+            0x1234_0000_0000_0000,
+            0xffff_ffff_ffff_fff0,
+            0xffff_ffff_ffff_fff1,
+        ];
+        let table = abbreviate(hashes.iter().cloned(), 4);
+
+        assert_eq!(
+            table.abbreviation(0x1234_5678_0000_0000).unwrap(),
+            "12345678"
+        );
+        assert_eq!(
+            table.abbreviation(0x1234_5679_0000_0000).unwrap(),
+            "12345679"
+        );
+        assert_eq!(table.abbreviation(0x1234_0000_0000_0000).unwrap(), "12340");
+        assert_eq!(
+            table.abbreviation(0xffff_ffff_ffff_fff0).unwrap(),
+            "fffffffffffffff0"
+        );
+        assert_eq!(table.abbreviation_len(0xffff_ffff_ffff_fff1), Some(16));
+        assert_eq!(table.abbreviation(0xdead), None);
+    }
+
+    #[test]
+    fn abbreviations_resolve_uniquely() {
+        let hashes: Vec<u64> = (0..2_000u64)
+            .map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+            .collect();
+        let table = abbreviate(hashes.clone(), 3);
+
+        for (hash, abbreviation) in table.iter() {
+            assert!(abbreviation.len() >= 3);
+            assert_eq!(table.resolve(&abbreviation), Resolution::Unique(hash));
+            assert_ne!(
+                table.resolve(&abbreviation[..abbreviation.len() - 1]),
+                Resolution::Unique(hash),
+                "{} is longer than necessary",
+                abbreviation
+            );
+        }
+        assert_eq!(table.len(), hashes.len());
+    }
+
+    #[test]
+    fn resolve_classifies_prefixes() {
+        let table = abbreviate(
+            vec![0xabcd_0000_0000_0000, 0xabce_0000_0000_0001, 0x0001],
+            1,
+        );
+
+        assert_eq!(
+            table.resolve("abcd"),
+            Resolution::Unique(0xabcd_0000_0000_0000)
+        );
+        assert_eq!(
+            table.resolve("ABCE"),
+            Resolution::Unique(0xabce_0000_0000_0001)
+        );
+        assert_eq!(table.resolve("abc"), Resolution::Ambiguous(2));
+        assert_eq!(table.resolve(""), Resolution::Ambiguous(3));
+        assert_eq!(
+            table.resolve("0000000000000001"),
+            Resolution::Unique(0x0001)
+        );
+        assert_eq!(table.resolve("abcf"), Resolution::NotFound);
+        assert_eq!(table.resolve("0000000000000002"), Resolution::NotFound);
+        assert_eq!(table.resolve("00000000000000010"), Resolution::NotFound);
+        assert_eq!(table.resolve("xyz"), Resolution::NotFound);
+        assert_eq!(table.resolve("+a"), Resolution::NotFound);
+    }
+
+    #[test]
+    fn duplicates_are_kept_once() {
+        let table = abbreviate(vec![0x1111, 0x1111, 0x8000_0000_0000_0000], 1);
+
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.abbreviation(0x8000_0000_0000_0000).unwrap(), "8");
+        assert_eq!(table.abbreviation(0x1111).unwrap(), "0");
+        assert_eq!(table.resolve("0"), Resolution::Unique(0x1111));
+    }
+
+    #[test]
+    fn min_len_is_clamped() {
+        let table = abbreviate(vec![0x8594_4171_f739_67e8], 40);
+        assert_eq!(
+            table.abbreviation(0x8594_4171_f739_67e8).unwrap(),
+            "85944171f73967e8"
+        );
+
+        let table = abbreviate(vec![0x8594_4171_f739_67e8], 0);
+        assert_eq!(table.abbreviation(0x8594_4171_f739_67e8).unwrap(), "8");
+    }
+
+    #[test]
+    fn empty_table() {
+        let table = abbreviate(Vec::new(), 7);
+
+        assert!(table.is_empty());
+        assert_eq!(table.iter().count(), 0);
+        assert_eq!(table.resolve(""), Resolution::NotFound);
+        assert_eq!(table.resolve("abc"), Resolution::NotFound);
+    }
+}
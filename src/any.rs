@@ -0,0 +1,123 @@
+//! Runtime selection between the 64-bit hashers without boxing.
+
+use {Fnv0, Fnv1, Fnv1a, FnvHash};
+
+/// One of the 64-bit FNV hashers, selected at runtime.
+///
+/// This dispatches by matching on the variant, so it needs no allocation.
+///
+/// ```
+/// use lz_fnv::{AnyFnv64, Fnv1a, FnvHash};
+///
+/// let mut fnv_hasher = AnyFnv64::Fnv1a(Fnv1a::<u64>::new());
+/// fnv_hasher.write(b"foobar");
+///
+/// assert_eq!(fnv_hasher.finish(), 0x8594_4171_f739_67e8);
+/// ```
+#[derive(Debug, Clone)]
+pub enum AnyFnv64 {
+    /// The FNV-0 hash.
+    Fnv0(Fnv0<u64>),
+    /// The FNV-1 hash.
+    Fnv1(Fnv1<u64>),
+    /// The FNV-1a hash.
+    Fnv1a(Fnv1a<u64>),
+}
+
+impl FnvHash for AnyFnv64 {
+    type Hash = u64;
+
+    fn finish(&self) -> u64 {
+        match *self {
+            AnyFnv64::Fnv0(ref hasher) => hasher.finish(),
+            AnyFnv64::Fnv1(ref hasher) => hasher.finish(),
+            AnyFnv64::Fnv1a(ref hasher) => hasher.finish(),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        match *self {
+            AnyFnv64::Fnv0(ref mut hasher) => hasher.write(bytes),
+            AnyFnv64::Fnv1(ref mut hasher) => hasher.write(bytes),
+            AnyFnv64::Fnv1a(ref mut hasher) => hasher.write(bytes),
+        }
+    }
+}
+
+impl ::std::hash::Hasher for AnyFnv64 {
+    fn finish(&self) -> u64 {
+        FnvHash::finish(self)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        FnvHash::write(self, bytes);
+    }
+}
+
+impl From<Fnv0<u64>> for AnyFnv64 {
+    fn from(hasher: Fnv0<u64>) -> Self {
+        AnyFnv64::Fnv0(hasher)
+    }
+}
+
+impl From<Fnv1<u64>> for AnyFnv64 {
+    fn from(hasher: Fnv1<u64>) -> Self {
+        AnyFnv64::Fnv1(hasher)
+    }
+}
+
+impl From<Fnv1a<u64>> for AnyFnv64 {
+    fn from(hasher: Fnv1a<u64>) -> Self {
+        AnyFnv64::Fnv1a(hasher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {AnyFnv64, Fnv0, Fnv1, Fnv1a, FnvHash};
+
+    const INPUTS: [&[u8]; 3] = [b"", b"a", b"chongo was here!\n"];
+
+    fn check<H: FnvHash<Hash = u64> + Clone + Into<AnyFnv64>>(hasher: H) {
+        for input in &INPUTS {
+            let mut direct = hasher.clone();
+            direct.write(input);
+
+            let mut any = hasher.clone().into();
+            FnvHash::write(&mut any, input);
+
+            assert_eq!(FnvHash::finish(&any), direct.finish());
+        }
+    }
+
+    #[test]
+    fn dispatches_to_each_variant() {
+        check(Fnv0::<u64>::new());
+        check(Fnv1::<u64>::new());
+        check(Fnv1a::<u64>::new());
+    }
+
+    #[test]
+    fn variants_differ() {
+        let mut hashers = [
+            AnyFnv64::Fnv0(Fnv0::<u64>::new()),
+            AnyFnv64::Fnv1(Fnv1::<u64>::new()),
+            AnyFnv64::Fnv1a(Fnv1a::<u64>::new()),
+        ];
+        for hasher in &mut hashers {
+            FnvHash::write(hasher, b"foobar");
+        }
+
+        assert_eq!(FnvHash::finish(&hashers[1]), 0x340d_8765_a4dd_a9c2);
+        assert_eq!(FnvHash::finish(&hashers[2]), 0x8594_4171_f739_67e8);
+        assert_ne!(FnvHash::finish(&hashers[0]), FnvHash::finish(&hashers[1]));
+    }
+
+    #[test]
+    fn implements_std_hasher() {
+        let mut hasher = AnyFnv64::from(Fnv1a::<u64>::new());
+        ::std::hash::Hasher::write(&mut hasher, b"foobar");
+
+        assert_eq!(::std::hash::Hasher::finish(&hasher), 0x8594_4171_f739_67e8);
+    }
+}
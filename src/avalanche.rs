@@ -0,0 +1,77 @@
+//! Avalanche diagnostics.
+
+use {Fnv1a, FnvHash};
+
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Measures how well FNV-1a 64 avalanches over `corpus`.
+///
+/// Every bit of every input is flipped in turn, and the fraction of the 64
+/// output bits which change is averaged over all of the flips. An ideal hash
+/// changes each output bit with probability one half, so scores near 0.5 are
+/// good. FNV-1a falls short of this for flips near the end of an input,
+/// whose effects never reach the low output bits.
+///
+/// Returns NaN if the corpus contains no bits to flip. This performs a hash
+/// of each input per bit, so it is intended as a diagnostic rather than for
+/// use on large corpora.
+///
+/// ```
+/// use lz_fnv::avalanche_score;
+///
+/// let score = avalanche_score(&[b"foobar", b"chongo was here!\n"]);
+///
+/// assert!(score > 0.3 && score < 0.6);
+/// ```
+pub fn avalanche_score(corpus: &[&[u8]]) -> f64 {
+    let mut changed = 0u64;
+    let mut flips = 0u64;
+
+    for input in corpus {
+        let hash = fnv1a_64(input);
+        let mut flipped = input.to_vec();
+
+        for bit in 0..input.len() * 8 {
+            flipped[bit / 8] ^= 1 << (bit % 8);
+            changed += u64::from((fnv1a_64(&flipped) ^ hash).count_ones());
+            flipped[bit / 8] ^= 1 << (bit % 8);
+            flips += 1;
+        }
+    }
+
+    changed as f64 / (flips * 64) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use avalanche_score;
+
+    #[test]
+    fn score_is_near_half() {
+        let corpus: [&[u8]; 4] = [b"a", b"foobar", b"chongo was here!\n", b"hello world"];
+        let score = avalanche_score(&corpus);
+
+        assert!((score - 0.4134).abs() < 0.0001, "score {}", score);
+    }
+
+    #[test]
+    fn longer_inputs_score_better() {
+        let long: Vec<u8> = (0..64).collect();
+
+        let short = avalanche_score(&[b"a"]);
+        let long = avalanche_score(&[&long]);
+
+        assert!(short < long);
+        assert!(long > 0.45 && long < 0.5, "score {}", long);
+    }
+
+    #[test]
+    fn empty_corpus_is_nan() {
+        assert!(avalanche_score(&[]).is_nan());
+        assert!(avalanche_score(&[b""]).is_nan());
+    }
+}
@@ -0,0 +1,339 @@
+//! Construction of hashers from runtime configuration.
+
+use std::error::Error;
+use std::fmt;
+
+use {DynFnvHasher, Fnv, Fnv0, Fnv1, Fnv1a, FnvHash, V1a};
+
+/// An FNV algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// The FNV-0 hash.
+    Fnv0,
+    /// The FNV-1 hash.
+    Fnv1,
+    /// The FNV-1a hash.
+    Fnv1a,
+}
+
+/// The width of an FNV hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Width {
+    /// A 32-bit hash.
+    U32,
+    /// A 64-bit hash.
+    U64,
+    /// A 128-bit hash.
+    U128,
+}
+
+impl Width {
+    /// Returns the number of bits in the hash.
+    pub fn bits(self) -> u32 {
+        match self {
+            Width::U32 => 32,
+            Width::U64 => 64,
+            Width::U128 => 128,
+        }
+    }
+}
+
+/// A byte order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endian {
+    /// Least significant byte first.
+    Little,
+    /// Most significant byte first.
+    Big,
+}
+
+/// An error building a hasher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildError {
+    /// The seed does not fit in the width of the hash.
+    SeedTooWide {
+        /// The seed.
+        seed: u128,
+        /// The width of the hash in bits.
+        bits: u32,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BuildError::SeedTooWide { seed, bits } => {
+                write!(f, "seed {:#x} does not fit in a {}-bit hash", seed, bits)
+            }
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+/// A builder of boxed `DynFnvHasher`s, selecting the algorithm, width and
+/// seed at runtime.
+///
+/// By default the builder creates an unseeded FNV-1a 64 hasher. A seeded
+/// hasher starts at the state reached by hashing the seed as exactly as many
+/// bytes as the width, in the chosen byte order, which is little-endian by
+/// default. A 64-bit little-endian seed therefore starts at the same state
+/// as `FnvBuildHasher::with_seed`.
+///
+/// ```
+/// use lz_fnv::{Algorithm, DynFnvHasher, Endian, Fnv, Width};
+///
+/// let mut hasher = Fnv::builder()
+///     .algorithm(Algorithm::Fnv1)
+///     .width(Width::U32)
+///     .seed(0x5eed)
+///     .endian(Endian::Big)
+///     .build()
+///     .unwrap();
+///
+/// hasher.write_bytes(b"foobar");
+/// assert_eq!(hasher.hash_bits(), 32);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HasherBuilder {
+    algorithm: Algorithm,
+    width: Width,
+    seed: Option<u128>,
+    endian: Endian,
+}
+
+impl Default for HasherBuilder {
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::Fnv1a,
+            width: Width::U64,
+            seed: None,
+            endian: Endian::Little,
+        }
+    }
+}
+
+impl HasherBuilder {
+    /// Creates a new `HasherBuilder` for an unseeded FNV-1a 64 hasher.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the algorithm.
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Sets the width of the hash.
+    pub fn width(mut self, width: Width) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the seed, which must fit in the width of the hash.
+    pub fn seed(mut self, seed: u128) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the byte order in which the seed is hashed.
+    pub fn endian(mut self, endian: Endian) -> Self {
+        self.endian = endian;
+        self
+    }
+
+    /// Builds the hasher.
+    ///
+    /// Returns an error if the seed does not fit in the width of the hash.
+    pub fn build(&self) -> Result<Box<dyn DynFnvHasher>, BuildError> {
+        let bits = self.width.bits();
+        let len = bits as usize / 8;
+
+        let mut seed = [0u8; 16];
+        let seed = match self.seed {
+            Some(value) if bits < 128 && value >> bits != 0 => {
+                return Err(BuildError::SeedTooWide { seed: value, bits });
+            }
+            Some(value) => match self.endian {
+                Endian::Little => {
+                    seed = value.to_le_bytes();
+                    &seed[..len]
+                }
+                Endian::Big => {
+                    seed = value.to_be_bytes();
+                    &seed[16 - len..]
+                }
+            },
+            None => &seed[..0],
+        };
+
+        Ok(match (self.algorithm, self.width) {
+            (Algorithm::Fnv0, Width::U32) => seeded(Fnv0::<u32>::new(), seed),
+            (Algorithm::Fnv0, Width::U64) => seeded(Fnv0::<u64>::new(), seed),
+            (Algorithm::Fnv0, Width::U128) => seeded(Fnv0::<u128>::new(), seed),
+            (Algorithm::Fnv1, Width::U32) => seeded(Fnv1::<u32>::new(), seed),
+            (Algorithm::Fnv1, Width::U64) => seeded(Fnv1::<u64>::new(), seed),
+            (Algorithm::Fnv1, Width::U128) => seeded(Fnv1::<u128>::new(), seed),
+            (Algorithm::Fnv1a, Width::U32) => seeded(Fnv1a::<u32>::new(), seed),
+            (Algorithm::Fnv1a, Width::U64) => seeded(Fnv1a::<u64>::new(), seed),
+            (Algorithm::Fnv1a, Width::U128) => seeded(Fnv1a::<u128>::new(), seed),
+        })
+    }
+}
+
+impl Fnv<V1a, u64> {
+    /// Creates a new `HasherBuilder`, as `HasherBuilder::new`.
+    ///
+    /// This is defined on `Fnv<V1a, u64>` so that `Fnv::builder()` needs no
+    /// type parameters; the builder's defaults are the same FNV-1a 64.
+    pub fn builder() -> HasherBuilder {
+        HasherBuilder::new()
+    }
+}
+
+fn seeded<H>(mut hasher: H, seed: &[u8]) -> Box<dyn DynFnvHasher>
+where
+    H: FnvHash + 'static,
+    H::Hash: Into<u128>,
+{
+    hasher.write(seed);
+    Box::new(hasher)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::BuildHasher;
+    use {
+        Algorithm, BuildError, Endian, Fnv, Fnv0, Fnv1, Fnv1a, FnvBuildHasher, FnvHash,
+        HasherBuilder, Width,
+    };
+
+    fn hash(builder: HasherBuilder, bytes: &[u8]) -> u128 {
+        let mut hasher = builder.build().unwrap();
+        hasher.write_bytes(bytes);
+        hasher.finish_u128()
+    }
+
+    fn direct<H>(mut hasher: H, bytes: &[u8]) -> u128
+    where
+        H: FnvHash,
+        H::Hash: Into<u128>,
+    {
+        hasher.write(bytes);
+        hasher.finish().into()
+    }
+
+    #[test]
+    fn defaults_to_fnv1a_64() {
+        assert_eq!(hash(Fnv::builder(), b"foobar"), 0x8594_4171_f739_67e8);
+        assert_eq!(Fnv::builder(), HasherBuilder::new());
+        assert_eq!(HasherBuilder::new().build().unwrap().hash_bits(), 64);
+    }
+
+    #[test]
+    fn matches_direct_types() {
+        let builder = |algorithm, width| HasherBuilder::new().algorithm(algorithm).width(width);
+
+        let cases: [(HasherBuilder, u128); 9] = [
+            (
+                builder(Algorithm::Fnv0, Width::U32),
+                direct(Fnv0::<u32>::new(), b"foobar"),
+            ),
+            (
+                builder(Algorithm::Fnv0, Width::U64),
+                direct(Fnv0::<u64>::new(), b"foobar"),
+            ),
+            (
+                builder(Algorithm::Fnv0, Width::U128),
+                direct(Fnv0::<u128>::new(), b"foobar"),
+            ),
+            (
+                builder(Algorithm::Fnv1, Width::U32),
+                direct(Fnv1::<u32>::new(), b"foobar"),
+            ),
+            (
+                builder(Algorithm::Fnv1, Width::U64),
+                direct(Fnv1::<u64>::new(), b"foobar"),
+            ),
+            (
+                builder(Algorithm::Fnv1, Width::U128),
+                direct(Fnv1::<u128>::new(), b"foobar"),
+            ),
+            (
+                builder(Algorithm::Fnv1a, Width::U32),
+                direct(Fnv1a::<u32>::new(), b"foobar"),
+            ),
+            (
+                builder(Algorithm::Fnv1a, Width::U64),
+                direct(Fnv1a::<u64>::new(), b"foobar"),
+            ),
+            (
+                builder(Algorithm::Fnv1a, Width::U128),
+                direct(Fnv1a::<u128>::new(), b"foobar"),
+            ),
+        ];
+
+        for &(builder, expected) in &cases {
+            assert_eq!(hash(builder, b"foobar"), expected, "{:?}", builder);
+            assert_eq!(builder.build().unwrap().hash_bits(), builder.width.bits());
+        }
+    }
+
+    #[test]
+    fn seeds_are_hashed_in_byte_order() {
+        let little = HasherBuilder::new().width(Width::U32).seed(0x0102_0304);
+        let big = little.endian(Endian::Big);
+
+        let mut expected = Fnv1a::<u32>::new();
+        expected.write(&[0x04, 0x03, 0x02, 0x01]);
+        assert_eq!(hash(little, b""), u128::from(expected.finish()));
+
+        let mut expected = Fnv1a::<u32>::new();
+        expected.write(&[0x01, 0x02, 0x03, 0x04]);
+        assert_eq!(hash(big, b""), u128::from(expected.finish()));
+
+        let mut zero = Fnv0::<u128>::new();
+        zero.write(&[0; 16]);
+        let builder = HasherBuilder::new()
+            .algorithm(Algorithm::Fnv0)
+            .width(Width::U128)
+            .seed(0);
+        assert_eq!(hash(builder, b""), zero.finish());
+    }
+
+    #[test]
+    fn little_endian_64_bit_seeds_match_build_hasher() {
+        let mut expected = FnvBuildHasher::with_seed(0x5eed).build_hasher();
+        expected.write(b"foobar");
+
+        let builder = HasherBuilder::new().seed(0x5eed);
+        assert_eq!(hash(builder, b"foobar"), u128::from(expected.finish()));
+    }
+
+    #[test]
+    fn rejects_seeds_wider_than_the_hash() {
+        let builder = HasherBuilder::new().width(Width::U32).seed(1 << 32);
+        let error = builder.build().err().unwrap();
+
+        assert_eq!(
+            error,
+            BuildError::SeedTooWide {
+                seed: 1 << 32,
+                bits: 32
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "seed 0x100000000 does not fit in a 32-bit hash"
+        );
+
+        assert!(builder.width(Width::U64).build().is_ok());
+        assert!(HasherBuilder::new().seed(u128::MAX).build().is_err());
+        assert!(HasherBuilder::new()
+            .width(Width::U128)
+            .seed(u128::MAX)
+            .build()
+            .is_ok());
+    }
+}
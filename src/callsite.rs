@@ -0,0 +1,157 @@
+//! Identifiers for source locations, computed at compile time.
+
+use fnv1a_64_advance;
+
+const fn write_str(state: u64, string: &str) -> u64 {
+    let state = fnv1a_64_advance(state, &(string.len() as u64).to_le_bytes());
+    fnv1a_64_advance(state, string.as_bytes())
+}
+
+/// Computes the identifier of a source location, as produced by
+/// [`fnv_callsite_id!`].
+///
+/// The identifier is the FNV-1a 64 hash of, in order:
+///
+/// 1. `file`, as its length in bytes as 8 little-endian bytes followed by
+///    its UTF-8 bytes,
+/// 2. `line` as 4 little-endian bytes,
+/// 3. `column` as 4 little-endian bytes,
+/// 4. `module_path`, framed as `file`, and
+/// 5. `extra`, framed as `file`, only if it is `Some`.
+///
+/// This encoding is frozen, so tooling can reproduce identifiers from debug
+/// information.
+///
+/// [`fnv_callsite_id!`]: macro.fnv_callsite_id.html
+///
+/// ```
+/// use lz_fnv::callsite_id;
+///
+/// assert_eq!(callsite_id("src/main.rs", 10, 5, "app", None), 0x3acd_2cf8_a7b1_db28);
+/// ```
+pub const fn callsite_id(
+    file: &str,
+    line: u32,
+    column: u32,
+    module_path: &str,
+    extra: Option<&str>,
+) -> u64 {
+    let state = write_str(0xcbf2_9ce4_8422_2325, file);
+    let state = fnv1a_64_advance(state, &line.to_le_bytes());
+    let state = fnv1a_64_advance(state, &column.to_le_bytes());
+    let state = write_str(state, module_path);
+
+    match extra {
+        Some(extra) => write_str(state, extra),
+        None => state,
+    }
+}
+
+/// Evaluates to a `u64` identifying the source location of the invocation,
+/// computed at compile time.
+///
+/// The identifier is [`callsite_id`] of `file!()`, `line!()`, `column!()` and
+/// `module_path!()`, so it is stable for as long as the invocation does not
+/// move. An optional string literal is also hashed, to distinguish several
+/// identifiers at one location, such as within another macro.
+///
+/// [`callsite_id`]: fn.callsite_id.html
+///
+/// ```
+/// #[macro_use]
+/// extern crate lz_fnv;
+///
+/// # fn main() {
+/// let first = fnv_callsite_id!();
+/// let second = fnv_callsite_id!();
+/// let tagged = fnv_callsite_id!("retry");
+///
+/// assert_ne!(first, second);
+/// assert_ne!(second, tagged);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! fnv_callsite_id {
+    () => {{
+        const ID: u64 = $crate::callsite_id(file!(), line!(), column!(), module_path!(), None);
+        ID
+    }};
+    ($extra: expr) => {{
+        const ID: u64 =
+            $crate::callsite_id(file!(), line!(), column!(), module_path!(), Some($extra));
+        ID
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use callsite_id;
+
+    fn same_callsite() -> u64 {
+        fnv_callsite_id!()
+    }
+
+    #[test]
+    fn pinned_encoding() {
+        assert_eq!(
+            callsite_id("src/main.rs", 10, 5, "app", None),
+            0x3acd_2cf8_a7b1_db28
+        );
+        assert_eq!(
+            callsite_id("src/main.rs", 10, 5, "app", Some("retry")),
+            0x5268_d63a_6279_91a1
+        );
+    }
+
+    #[test]
+    fn fields_are_framed() {
+        let id = callsite_id("ab", 1, 2, "c", None);
+
+        assert_ne!(id, callsite_id("a", 1, 2, "bc", None));
+        assert_ne!(id, callsite_id("ab", 2, 1, "c", None));
+        assert_ne!(id, callsite_id("ab", 1, 2, "c", Some("")));
+    }
+
+    #[test]
+    fn different_lines_differ() {
+        let first = fnv_callsite_id!();
+        let second = fnv_callsite_id!();
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn same_callsite_is_stable() {
+        assert_eq!(same_callsite(), same_callsite());
+    }
+
+    #[test]
+    fn literal_changes_id() {
+        let (plain, tagged, other) = (
+            fnv_callsite_id!(),
+            fnv_callsite_id!("tag"),
+            fnv_callsite_id!("other"),
+        );
+
+        assert_ne!(plain, tagged);
+        assert_ne!(tagged, other);
+    }
+
+    // `line!()` and `column!()` give the location of the outermost macro
+    // invocation, so these are the location `fnv_callsite_id!` sees
+    macro_rules! id_and_location {
+        ($extra: expr) => {
+            (fnv_callsite_id!($extra), line!(), column!())
+        };
+    }
+
+    #[test]
+    fn matches_callsite_id() {
+        let (id, line, column) = id_and_location!("x");
+
+        assert_eq!(
+            id,
+            callsite_id(file!(), line, column, module_path!(), Some("x"))
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Checksums built on FNV-1a.
+
+use {Fnv1a, FnvHash};
+
+/// Computes FNV-1a 64 over `data`, starting from `seed` rather than the
+/// offset basis.
+///
+/// Passing the result of one call as the seed of the next gives the same
+/// hash as a single call over the concatenated data, so checksums can be
+/// chained across records. Seeding with the offset basis gives the standard
+/// hash.
+///
+/// ```
+/// use lz_fnv::checksum;
+///
+/// const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+///
+/// let first = checksum(OFFSET_BASIS, b"foo");
+///
+/// assert_eq!(checksum(first, b"bar"), checksum(OFFSET_BASIS, b"foobar"));
+/// ```
+pub fn checksum<B: AsRef<[u8]>>(seed: u64, data: B) -> u64 {
+    let mut hasher = Fnv1a::with_key(seed);
+    hasher.write(data.as_ref());
+    hasher.finish()
+}
+
+/// A checksum pairing FNV-1a 64 with the sum of the bytes, both computed in
+/// one pass.
+///
+/// The byte sum wraps modulo `2^64`. It is weak on its own, being blind to
+/// reordering, but it catches different errors than FNV, such as those which
+/// happen to cancel in the hash. `finish` returns the FNV-1a hash alone.
+///
+/// ```
+/// use lz_fnv::{DualChecksum, FnvHash};
+///
+/// let mut dual = DualChecksum::new();
+/// dual.write(b"foobar");
+///
+/// assert_eq!(dual.finish_dual(), (0x8594_4171_f739_67e8, 0x279));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DualChecksum {
+    fnv: Fnv1a<u64>,
+    sum: u64,
+}
+
+impl DualChecksum {
+    /// Creates a new `DualChecksum`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the FNV-1a 64 hash and the byte sum of the data written.
+    pub fn finish_dual(&self) -> (u64, u64) {
+        (self.fnv.finish(), self.sum)
+    }
+}
+
+impl FnvHash for DualChecksum {
+    type Hash = u64;
+
+    fn finish(&self) -> u64 {
+        self.fnv.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.fnv.write(bytes);
+        self.sum = bytes
+            .iter()
+            .fold(self.sum, |sum, &byte| sum.wrapping_add(u64::from(byte)));
+    }
+}
+
+/// A checksum computed over a byte slice in one call, as a seam for code
+/// generic over its checksum algorithm.
+///
+/// This is implemented for `u32`, `u64` and `u128` as the FNV-1a hash of
+/// that width, so `let c: u64 = Checksum::compute(data)` is the FNV-1a 64
+/// hash of `data`.
+///
+/// ```
+/// use lz_fnv::Checksum;
+///
+/// fn tag<C: Checksum>(data: &[u8]) -> C {
+///     C::compute(data)
+/// }
+///
+/// assert_eq!(tag::<u64>(b"foobar"), 0x8594_4171_f739_67e8);
+/// assert_eq!(tag::<u32>(b"foobar"), 0xbf9c_f968);
+/// ```
+pub trait Checksum {
+    /// Computes the checksum of `data`.
+    fn compute(data: &[u8]) -> Self;
+}
+
+macro_rules! checksum_impl {
+    ($($type: ty),*) => {
+        $(
+            impl Checksum for $type {
+                fn compute(data: &[u8]) -> Self {
+                    let mut hasher = Fnv1a::<$type>::new();
+                    hasher.write(data);
+                    hasher.finish()
+                }
+            }
+        )*
+    };
+}
+
+checksum_impl!(u32, u64, u128);
+
+#[cfg(test)]
+mod tests {
+    use {checksum, Checksum, DualChecksum, Fnv1a, FnvExt, FnvHash};
+
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+    #[test]
+    fn offset_basis_seed_is_standard() {
+        let mut hasher = Fnv1a::<u64>::new();
+        hasher.write(b"foobar");
+
+        assert_eq!(checksum(OFFSET_BASIS, "foobar"), hasher.finish());
+        assert_eq!(checksum(OFFSET_BASIS, b""), OFFSET_BASIS);
+    }
+
+    #[test]
+    fn chained_records_equal_concatenation() {
+        let records: [&[u8]; 3] = [b"record one\n", b"", b"record two\n"];
+
+        let chained = records.iter().fold(OFFSET_BASIS, checksum);
+
+        assert_eq!(chained, checksum(OFFSET_BASIS, b"record one\nrecord two\n"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn accepts_owned_data() {
+        assert_eq!(checksum(1, vec![1u8, 2, 3]), checksum(1, [1u8, 2, 3]));
+    }
+
+    #[test]
+    fn dual_foobar() {
+        let mut dual = DualChecksum::new();
+        dual.write(b"foo");
+        dual.write(b"bar");
+
+        assert_eq!(dual.finish_dual(), (0x8594_4171_f739_67e8, 0x279));
+        assert_eq!(dual.finish(), 0x8594_4171_f739_67e8);
+        assert_eq!(DualChecksum::new().finish_dual(), (OFFSET_BASIS, 0));
+    }
+
+    #[test]
+    fn dual_sum_wraps() {
+        let mut dual = DualChecksum::new();
+        dual.sum = u64::MAX;
+        dual.write(&[2]);
+
+        assert_eq!(dual.finish_dual().1, 1);
+    }
+
+    #[test]
+    fn checksum_trait_is_fnv1a() {
+        assert_eq!(u64::compute(b"foobar"), 0x8594_4171_f739_67e8);
+        assert_eq!(u32::compute(b""), 0x811c_9dc5);
+
+        for input in &[&b""[..], b"a", b"chongo was here!\n"] {
+            assert_eq!(u32::compute(input), input.fnv1a_32());
+            assert_eq!(u64::compute(input), input.fnv1a_64());
+            assert_eq!(u128::compute(input), input.fnv1a_128());
+        }
+    }
+}
@@ -0,0 +1,576 @@
+//! Parsing and formatting of checksum listing files.
+//!
+//! Two line styles are supported, matching the output of `sha256sum` and
+//! `sha256sum --tag`:
+//!
+//! ```text
+//! 85944171f73967e8  path/to/file
+//! FNV1a-64 (path/to/file) = 85944171f73967e8
+//! ```
+//!
+//! In the GNU style a `*` may replace the second space to mark binary mode,
+//! which is accepted and ignored. The hash is lowercase hex of exactly the
+//! algorithm's width; uppercase hex is accepted when parsing.
+//!
+//! A path containing a backslash, newline or carriage return is escaped as
+//! `\\`, `\n` and `\r` respectively, and the whole line is then prefixed with
+//! a single backslash, as GNU coreutils does.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+
+/// An FNV algorithm and width named in a checksum listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    /// FNV-0 32.
+    Fnv0_32,
+    /// FNV-0 64.
+    Fnv0_64,
+    /// FNV-0 128.
+    Fnv0_128,
+    /// FNV-1 32.
+    Fnv1_32,
+    /// FNV-1 64.
+    Fnv1_64,
+    /// FNV-1 128.
+    Fnv1_128,
+    /// FNV-1a 32.
+    Fnv1a32,
+    /// FNV-1a 64.
+    Fnv1a64,
+    /// FNV-1a 128.
+    Fnv1a128,
+}
+
+const ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::Fnv0_32,
+    Algorithm::Fnv0_64,
+    Algorithm::Fnv0_128,
+    Algorithm::Fnv1_32,
+    Algorithm::Fnv1_64,
+    Algorithm::Fnv1_128,
+    Algorithm::Fnv1a32,
+    Algorithm::Fnv1a64,
+    Algorithm::Fnv1a128,
+];
+
+impl Algorithm {
+    /// Returns the name used for this algorithm in tagged lines, such as
+    /// `FNV1a-64`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Fnv0_32 => "FNV0-32",
+            Algorithm::Fnv0_64 => "FNV0-64",
+            Algorithm::Fnv0_128 => "FNV0-128",
+            Algorithm::Fnv1_32 => "FNV1-32",
+            Algorithm::Fnv1_64 => "FNV1-64",
+            Algorithm::Fnv1_128 => "FNV1-128",
+            Algorithm::Fnv1a32 => "FNV1a-32",
+            Algorithm::Fnv1a64 => "FNV1a-64",
+            Algorithm::Fnv1a128 => "FNV1a-128",
+        }
+    }
+
+    /// Returns the width of this algorithm's hash in bits.
+    pub fn bits(self) -> u32 {
+        match self {
+            Algorithm::Fnv0_32 | Algorithm::Fnv1_32 | Algorithm::Fnv1a32 => 32,
+            Algorithm::Fnv0_64 | Algorithm::Fnv1_64 | Algorithm::Fnv1a64 => 64,
+            Algorithm::Fnv0_128 | Algorithm::Fnv1_128 | Algorithm::Fnv1a128 => 128,
+        }
+    }
+
+    /// Returns the algorithm with the specified tagged line name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        ALGORITHMS.iter().cloned().find(|a| a.name() == name)
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A single entry parsed from a checksum listing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumEntry {
+    /// The algorithm named by a tagged line, or `None` for an untagged line.
+    pub algorithm: Option<Algorithm>,
+    /// The width of the hash in bits, as determined by its hex length.
+    pub bits: u32,
+    /// The hash.
+    pub hash: u128,
+    /// The unescaped path.
+    pub path: String,
+}
+
+/// The reason a checksum line failed to parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The line did not match either supported style.
+    Malformed,
+    /// A tagged line named an unknown algorithm.
+    UnknownAlgorithm,
+    /// The hash contained a non-hex character.
+    InvalidHash,
+    /// The hash length did not match a supported width or the tagged
+    /// algorithm's width.
+    InvalidHashLength,
+    /// An escaped line contained an unknown escape sequence.
+    InvalidEscape,
+    /// The path was empty.
+    EmptyPath,
+}
+
+/// An error parsing a checksum line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    line: Option<usize>,
+}
+
+impl ParseError {
+    fn new(kind: ParseErrorKind) -> Self {
+        Self { kind, line: None }
+    }
+
+    /// Returns the reason the line failed to parse.
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Returns the 1-based line number of the failing line, when parsed from
+    /// a listing.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self.kind {
+            ParseErrorKind::Malformed => "improperly formatted checksum line",
+            ParseErrorKind::UnknownAlgorithm => "unknown checksum algorithm",
+            ParseErrorKind::InvalidHash => "checksum contains a non-hex character",
+            ParseErrorKind::InvalidHashLength => "checksum has an invalid length",
+            ParseErrorKind::InvalidEscape => "invalid escape sequence in path",
+            ParseErrorKind::EmptyPath => "checksum line has an empty path",
+        };
+
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, description),
+            None => f.write_str(description),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+fn needs_escape(path: &str) -> bool {
+    path.contains(['\\', '\n', '\r'])
+}
+
+fn escape(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+
+    for c in path.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn unescape(path: &str) -> Result<String, ParseError> {
+    let mut unescaped = String::with_capacity(path.len());
+    let mut chars = path.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('\\') => unescaped.push('\\'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            _ => return Err(ParseError::new(ParseErrorKind::InvalidEscape)),
+        }
+    }
+
+    Ok(unescaped)
+}
+
+fn format_hash(hash: u128, algorithm: Algorithm) -> String {
+    let digits = (algorithm.bits() / 4) as usize;
+    let mask = u128::MAX >> (128 - algorithm.bits());
+
+    format!("{:0width$x}", hash & mask, width = digits)
+}
+
+fn parse_hash(hex: &str) -> Result<(u128, u32), ParseError> {
+    let bits = match hex.len() {
+        8 => 32,
+        16 => 64,
+        32 => 128,
+        _ => return Err(ParseError::new(ParseErrorKind::InvalidHashLength)),
+    };
+
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ParseError::new(ParseErrorKind::InvalidHash));
+    }
+
+    let hash =
+        u128::from_str_radix(hex, 16).map_err(|_| ParseError::new(ParseErrorKind::InvalidHash))?;
+
+    Ok((hash, bits))
+}
+
+/// Formats an untagged, GNU style, checksum line without a trailing newline.
+///
+/// Only the low `algorithm.bits()` bits of `hash` are used.
+///
+/// ```
+/// use lz_fnv::checksum_file::{format_line, Algorithm};
+///
+/// let line = format_line(0x8594_4171_f739_67e8, "foobar.txt", Algorithm::Fnv1a64);
+///
+/// assert_eq!(line, "85944171f73967e8  foobar.txt");
+/// ```
+pub fn format_line(hash: u128, path: &str, algorithm: Algorithm) -> String {
+    if needs_escape(path) {
+        format!("\\{}  {}", format_hash(hash, algorithm), escape(path))
+    } else {
+        format!("{}  {}", format_hash(hash, algorithm), path)
+    }
+}
+
+/// Formats a tagged, BSD style, checksum line without a trailing newline.
+///
+/// Only the low `algorithm.bits()` bits of `hash` are used.
+///
+/// ```
+/// use lz_fnv::checksum_file::{format_tagged_line, Algorithm};
+///
+/// let line = format_tagged_line(0x8594_4171_f739_67e8, "foobar.txt", Algorithm::Fnv1a64);
+///
+/// assert_eq!(line, "FNV1a-64 (foobar.txt) = 85944171f73967e8");
+/// ```
+pub fn format_tagged_line(hash: u128, path: &str, algorithm: Algorithm) -> String {
+    if needs_escape(path) {
+        format!(
+            "\\{} ({}) = {}",
+            algorithm,
+            escape(path),
+            format_hash(hash, algorithm)
+        )
+    } else {
+        format!(
+            "{} ({}) = {}",
+            algorithm,
+            path,
+            format_hash(hash, algorithm)
+        )
+    }
+}
+
+fn parse_tagged(line: &str) -> Option<(&str, &str, &str)> {
+    let open = line.find(" (")?;
+    let close = line.rfind(") = ")?;
+    if close < open + 2 || line[..open].contains(' ') {
+        return None;
+    }
+
+    Some((&line[..open], &line[open + 2..close], &line[close + 4..]))
+}
+
+fn parse_untagged(line: &str) -> Option<(&str, &str)> {
+    let space = line.find(' ')?;
+    let hex = &line[..space];
+    let rest = &line[space + 1..];
+
+    if rest.starts_with(' ') || rest.starts_with('*') {
+        Some((hex, &rest[1..]))
+    } else {
+        None
+    }
+}
+
+/// Parses a single checksum line of either style.
+///
+/// A trailing newline, if present, is ignored.
+///
+/// ```
+/// use lz_fnv::checksum_file::{parse_line, Algorithm};
+///
+/// let gnu = parse_line("85944171f73967e8  foobar.txt").unwrap();
+/// let bsd = parse_line("FNV1a-64 (foobar.txt) = 85944171f73967e8").unwrap();
+///
+/// assert_eq!(gnu.hash, bsd.hash);
+/// assert_eq!(gnu.path, bsd.path);
+/// assert_eq!(bsd.algorithm, Some(Algorithm::Fnv1a64));
+/// ```
+pub fn parse_line(line: &str) -> Result<ChecksumEntry, ParseError> {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    let line = line.strip_suffix('\r').unwrap_or(line);
+
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+
+    let (algorithm, path, hex) = match parse_tagged(line) {
+        Some((name, path, hex)) => match Algorithm::from_name(name) {
+            Some(algorithm) => (Some(algorithm), path, hex),
+            None => return Err(ParseError::new(ParseErrorKind::UnknownAlgorithm)),
+        },
+        None => match parse_untagged(line) {
+            Some((hex, path)) => (None, path, hex),
+            None => return Err(ParseError::new(ParseErrorKind::Malformed)),
+        },
+    };
+
+    let (hash, bits) = parse_hash(hex)?;
+    if algorithm.is_some_and(|a| a.bits() != bits) {
+        return Err(ParseError::new(ParseErrorKind::InvalidHashLength));
+    }
+
+    if path.is_empty() {
+        return Err(ParseError::new(ParseErrorKind::EmptyPath));
+    }
+
+    let path = if escaped {
+        unescape(path)?
+    } else {
+        path.to_owned()
+    };
+
+    Ok(ChecksumEntry {
+        algorithm,
+        bits,
+        hash,
+        path,
+    })
+}
+
+/// An iterator over the entries of a checksum listing.
+///
+/// This is created by [`parse_reader`](fn.parse_reader.html).
+#[derive(Debug)]
+pub struct Entries<R> {
+    reader: R,
+    line: usize,
+    buffer: String,
+}
+
+impl<R: BufRead> Iterator for Entries<R> {
+    type Item = io::Result<ChecksumEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buffer.clear();
+
+        match self.reader.read_line(&mut self.buffer) {
+            Ok(0) => None,
+            Ok(_) => {
+                self.line += 1;
+
+                Some(parse_line(&self.buffer).map_err(|mut e| {
+                    e.line = Some(self.line);
+                    io::Error::new(io::ErrorKind::InvalidData, e)
+                }))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Parses a checksum listing line by line.
+///
+/// A line which fails to parse yields an `io::Error` of kind `InvalidData`
+/// wrapping a [`ParseError`](struct.ParseError.html) carrying the line
+/// number. Parsing may continue past such an error.
+///
+/// ```
+/// use lz_fnv::checksum_file::parse_reader;
+///
+/// let listing = "85944171f73967e8  foobar.txt\nFNV1a-32 (a b.txt) = bf9cf968\n";
+///
+/// let entries = parse_reader(listing.as_bytes())
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+///
+/// assert_eq!(entries.len(), 2);
+/// assert_eq!(entries[1].path, "a b.txt");
+/// ```
+pub fn parse_reader<R: BufRead>(reader: R) -> Entries<R> {
+    Entries {
+        reader,
+        line: 0,
+        buffer: String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(line: &str) -> ParseErrorKind {
+        parse_line(line).unwrap_err().kind()
+    }
+
+    #[test]
+    fn round_trip_untagged() {
+        for &algorithm in &ALGORITHMS {
+            let hash =
+                0x0123_4567_89ab_cdef_0123_4567_89ab_cdef & (u128::MAX >> (128 - algorithm.bits()));
+            let entry = parse_line(&format_line(hash, "dir/file.txt", algorithm)).unwrap();
+
+            assert_eq!(entry.algorithm, None);
+            assert_eq!(entry.bits, algorithm.bits());
+            assert_eq!(entry.hash, hash);
+            assert_eq!(entry.path, "dir/file.txt");
+        }
+    }
+
+    #[test]
+    fn round_trip_tagged() {
+        for &algorithm in &ALGORITHMS {
+            let hash =
+                0xfedc_ba98_7654_3210_fedc_ba98_7654_3210 & (u128::MAX >> (128 - algorithm.bits()));
+            let entry = parse_line(&format_tagged_line(hash, "file.txt", algorithm)).unwrap();
+
+            assert_eq!(entry.algorithm, Some(algorithm));
+            assert_eq!(entry.bits, algorithm.bits());
+            assert_eq!(entry.hash, hash);
+            assert_eq!(entry.path, "file.txt");
+        }
+    }
+
+    #[test]
+    fn format_zero_pads() {
+        assert_eq!(format_line(0x1, "a", Algorithm::Fnv1a32), "00000001  a");
+        assert_eq!(
+            format_tagged_line(0x1, "a", Algorithm::Fnv1_64),
+            "FNV1-64 (a) = 0000000000000001"
+        );
+    }
+
+    #[test]
+    fn paths_with_spaces() {
+        let line = format_line(0xbf9c_f968, " leading and trailing ", Algorithm::Fnv1a32);
+        assert_eq!(parse_line(&line).unwrap().path, " leading and trailing ");
+
+        let line = format_tagged_line(0xbf9c_f968, "a (b) = c", Algorithm::Fnv1a32);
+        assert_eq!(parse_line(&line).unwrap().path, "a (b) = c");
+
+        let line = format_line(0xbf9c_f968, "a (b) = c", Algorithm::Fnv1a32);
+        assert_eq!(parse_line(&line).unwrap().path, "a (b) = c");
+    }
+
+    #[test]
+    fn paths_with_newlines_are_escaped() {
+        let line = format_line(0xbf9c_f968, "a\nb\\c\rd", Algorithm::Fnv1a32);
+        assert_eq!(line, "\\bf9cf968  a\\nb\\\\c\\rd");
+        assert!(!line.contains('\n'));
+        assert_eq!(parse_line(&line).unwrap().path, "a\nb\\c\rd");
+
+        let line = format_tagged_line(0xbf9c_f968, "a\nb", Algorithm::Fnv1a32);
+        assert_eq!(line, "\\FNV1a-32 (a\\nb) = bf9cf968");
+        assert_eq!(parse_line(&line).unwrap().path, "a\nb");
+    }
+
+    #[test]
+    fn unescaped_lines_keep_backslashes_literal() {
+        assert_eq!(parse_line("bf9cf968  a\\nb").unwrap().path, "a\\nb");
+    }
+
+    #[test]
+    fn binary_mode_marker() {
+        let entry = parse_line("bf9cf968 *file.bin").unwrap();
+
+        assert_eq!(entry.hash, 0xbf9c_f968);
+        assert_eq!(entry.path, "file.bin");
+    }
+
+    #[test]
+    fn cross_acceptance() {
+        let gnu = parse_line("85944171F73967E8  foobar.txt\n").unwrap();
+        let bsd = parse_line("FNV1a-64 (foobar.txt) = 85944171f73967e8\r\n").unwrap();
+
+        assert_eq!(gnu.hash, bsd.hash);
+        assert_eq!(gnu.bits, bsd.bits);
+        assert_eq!(gnu.path, bsd.path);
+    }
+
+    #[test]
+    fn malformed_lines() {
+        assert_eq!(parse_error(""), ParseErrorKind::Malformed);
+        assert_eq!(parse_error("bf9cf968"), ParseErrorKind::Malformed);
+        assert_eq!(parse_error("bf9cf968 file"), ParseErrorKind::Malformed);
+        assert_eq!(
+            parse_error("bf9cf96  file"),
+            ParseErrorKind::InvalidHashLength
+        );
+        assert_eq!(parse_error("bf9cf96g  file"), ParseErrorKind::InvalidHash);
+        assert_eq!(parse_error("bf9cf968  "), ParseErrorKind::EmptyPath);
+        assert_eq!(
+            parse_error("\\bf9cf968  a\\tb"),
+            ParseErrorKind::InvalidEscape
+        );
+        assert_eq!(
+            parse_error("SHA256 (file) = bf9cf968"),
+            ParseErrorKind::UnknownAlgorithm
+        );
+        assert_eq!(
+            parse_error("FNV1a-64 (file) = bf9cf968"),
+            ParseErrorKind::InvalidHashLength
+        );
+    }
+
+    #[test]
+    fn reader_reports_line_numbers() {
+        let listing = "bf9cf968  a\nnot a checksum\nFNV1a-32 (b) = bf9cf968\nbf9cf96x  c\n";
+
+        let results: Vec<_> = parse_reader(listing.as_bytes()).collect();
+        assert_eq!(results.len(), 4);
+
+        assert_eq!(results[0].as_ref().unwrap().path, "a");
+        assert_eq!(results[2].as_ref().unwrap().path, "b");
+
+        let error = |index: usize| {
+            let error = results[index].as_ref().unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            *error
+                .get_ref()
+                .unwrap()
+                .downcast_ref::<ParseError>()
+                .unwrap()
+        };
+
+        assert_eq!(error(1).line(), Some(2));
+        assert_eq!(error(1).kind(), ParseErrorKind::Malformed);
+        assert_eq!(error(3).line(), Some(4));
+        assert_eq!(error(3).kind(), ParseErrorKind::InvalidHash);
+        assert_eq!(
+            error(3).to_string(),
+            "line 4: checksum contains a non-hex character"
+        );
+    }
+
+    #[test]
+    fn reader_without_trailing_newline() {
+        let entries: Vec<_> = parse_reader("bf9cf968  a\nbf9cf968  b".as_bytes())
+            .map(Result::unwrap)
+            .collect();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].path, "b");
+    }
+}
@@ -0,0 +1,230 @@
+//! Hashing of data delivered in chunks.
+
+use {Fnv1a, FnvHash};
+
+const RING_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Writes each successful chunk into the hasher in turn, stopping at the
+/// first error.
+///
+/// The error is returned as soon as it is reached, without taking any further
+/// items from the iterator, and the hasher is left holding the chunks before
+/// it.
+///
+/// ```
+/// use lz_fnv::{write_try_chunks, Fnv1, FnvHash};
+///
+/// let chunks: Vec<Result<&[u8], ()>> = vec![Ok(b"foo"), Ok(b"bar")];
+///
+/// let mut hasher = Fnv1::<u32>::new();
+/// write_try_chunks(&mut hasher, chunks).unwrap();
+///
+/// let mut expected = Fnv1::<u32>::new();
+/// expected.write(b"foobar");
+///
+/// assert_eq!(hasher.finish(), expected.finish());
+/// ```
+pub fn write_try_chunks<H, I, C, E>(hasher: &mut H, chunks: I) -> Result<(), E>
+where
+    H: FnvHash + ?Sized,
+    I: IntoIterator<Item = Result<C, E>>,
+    C: AsRef<[u8]>,
+{
+    for chunk in chunks {
+        hasher.write(chunk?.as_ref());
+    }
+
+    Ok(())
+}
+
+/// Returns the FNV-1a 64 hash of the concatenation of the successful chunks,
+/// or the first error.
+///
+/// This suits data delivered as an iterator of `Result<Vec<u8>, E>`, such as
+/// by an object store client. An empty iterator hashes to the offset basis.
+/// See [`write_try_chunks`] for the other hashers.
+///
+/// ```
+/// use lz_fnv::hash_try_chunks;
+///
+/// let chunks = vec![Ok(b"foo".to_vec()), Ok(b"bar".to_vec())];
+/// assert_eq!(hash_try_chunks::<_, _, ()>(chunks), Ok(0x8594_4171_f739_67e8));
+///
+/// let chunks = vec![Ok(b"foo".to_vec()), Err("connection reset")];
+/// assert_eq!(hash_try_chunks(chunks), Err("connection reset"));
+/// ```
+///
+/// [`write_try_chunks`]: fn.write_try_chunks.html
+pub fn hash_try_chunks<I, C, E>(chunks: I) -> Result<u64, E>
+where
+    I: IntoIterator<Item = Result<C, E>>,
+    C: AsRef<[u8]>,
+{
+    let mut hasher = Fnv1a::<u64>::new();
+    write_try_chunks(&mut hasher, chunks)?;
+    Ok(hasher.finish())
+}
+
+/// Repeatedly fills a buffer with `fill` and writes what it filled into the
+/// hasher, until `fill` returns 0.
+///
+/// Each call to `fill` is given the whole of an 8 KiB buffer owned by this
+/// function and returns how many bytes it wrote at its start. The buffer is
+/// reused for every call, so no more than it is ever held, and its contents
+/// are hashed in place with `FnvHash::write_in_place`. A fill of 0 bytes ends
+/// the loop, so `fill` must return 0 only once its data is exhausted.
+///
+/// # Panics
+///
+/// Panics if `fill` returns more than the length of the buffer.
+///
+/// ```
+/// use lz_fnv::{hash_ring, Fnv1a, FnvHash};
+///
+/// let mut source = &b"chongo was here!\n"[..];
+///
+/// let mut hasher = Fnv1a::<u64>::new();
+/// hash_ring(&mut hasher, |buf| {
+///     let len = source.len().min(5).min(buf.len());
+///     buf[..len].copy_from_slice(&source[..len]);
+///     source = &source[len..];
+///     len
+/// });
+///
+/// let mut expected = Fnv1a::<u64>::new();
+/// expected.write(b"chongo was here!\n");
+///
+/// assert_eq!(hasher.finish(), expected.finish());
+/// ```
+pub fn hash_ring<H, F>(hasher: &mut H, mut fill: F)
+where
+    H: FnvHash + ?Sized,
+    F: FnMut(&mut [u8]) -> usize,
+{
+    let mut buffer = [0u8; RING_BUFFER_SIZE];
+
+    loop {
+        let filled = fill(&mut buffer);
+        if filled == 0 {
+            return;
+        }
+        assert!(
+            filled <= buffer.len(),
+            "hash_ring fill returned {} for a buffer of {} bytes",
+            filled,
+            buffer.len()
+        );
+
+        hasher.write_in_place(&buffer[..filled]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use write_try_chunks;
+    use {hash_ring, hash_try_chunks, Fnv1a, FnvExt, FnvHash};
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn matches_concatenation() {
+        let chunks: Vec<Result<Vec<u8>, ()>> = vec![
+            Ok(b"chongo ".to_vec()),
+            Ok(b"was ".to_vec()),
+            Ok(b"here!\n".to_vec()),
+        ];
+
+        assert_eq!(
+            hash_try_chunks(chunks),
+            Ok(b"chongo was here!\n".fnv1a_64())
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn empty_and_zero_length_chunks() {
+        let none: Vec<Result<&[u8], ()>> = Vec::new();
+        assert_eq!(hash_try_chunks(none), Ok(0xcbf2_9ce4_8422_2325));
+
+        let chunks: Vec<Result<&[u8], ()>> =
+            vec![Ok(b""), Ok(b"foo"), Ok(b""), Ok(b""), Ok(b"bar")];
+        assert_eq!(hash_try_chunks(chunks), Ok(0x8594_4171_f739_67e8));
+
+        let empty: Vec<Result<&[u8], ()>> = vec![Ok(b""), Ok(b"")];
+        assert_eq!(hash_try_chunks(empty), Ok(0xcbf2_9ce4_8422_2325));
+    }
+
+    #[test]
+    fn stops_at_the_first_error() {
+        let chunks = (0..).map(|i| match i {
+            0 => Ok(&b"foo"[..]),
+            1 => Err(i),
+            2 => Err(i),
+            _ => panic!("item {} was taken after the error", i),
+        });
+
+        assert_eq!(hash_try_chunks(chunks), Err(1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn other_hashers_keep_the_chunks_before_an_error() {
+        let chunks = vec![Ok(&b"foo"[..]), Err(()), Ok(&b"bar"[..])];
+
+        let mut hasher = Fnv1a::<u32>::new();
+        assert_eq!(write_try_chunks(&mut hasher, chunks), Err(()));
+
+        let mut expected = Fnv1a::<u32>::new();
+        expected.write(b"foo");
+        assert_eq!(hasher.finish(), expected.finish());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hash_ring_matches_slice_hash() {
+        let data: Vec<u8> = (0..50_000u32).map(|i| (i * 31) as u8).collect();
+        let mut position = 0;
+        let mut sizes = [1, 7, 8 * 1024, 3, 5_000].iter().cycle();
+
+        let mut hasher = Fnv1a::<u64>::new();
+        hash_ring(&mut hasher, |buf| {
+            let len = (*sizes.next().unwrap()).min(data.len() - position);
+            buf[..len].copy_from_slice(&data[position..position + len]);
+            position += len;
+            len
+        });
+
+        assert_eq!(position, data.len());
+        assert_eq!(hasher.finish(), data.fnv1a_64());
+    }
+
+    #[test]
+    fn hash_ring_stops_at_zero_length_fill() {
+        let mut calls = 0;
+        let mut hasher = Fnv1a::<u64>::new();
+        hash_ring(&mut hasher, |buf| {
+            calls += 1;
+            match calls {
+                1 => {
+                    buf[..3].copy_from_slice(b"foo");
+                    3
+                }
+                2 => 0,
+                _ => panic!("fill was called after returning 0"),
+            }
+        });
+
+        assert_eq!(calls, 2);
+        assert_eq!(hasher.finish(), b"foo".fnv1a_64());
+
+        let mut empty = Fnv1a::<u64>::new();
+        hash_ring(&mut empty, |_| 0);
+        assert_eq!(empty.finish(), 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    #[should_panic(expected = "hash_ring fill returned 8193 for a buffer of 8192 bytes")]
+    fn hash_ring_overfill() {
+        hash_ring(&mut Fnv1a::<u64>::new(), |buf| buf.len() + 1);
+    }
+}
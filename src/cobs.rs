@@ -0,0 +1,345 @@
+//! COBS framing of packets with an FNV-1a 32 checksum trailer.
+//!
+//! A frame is the payload followed by the FNV-1a 32 hash of the payload, as 4
+//! little-endian bytes, encoded with Consistent Overhead Byte Stuffing (COBS)
+//! and terminated by a `0x00` delimiter. COBS removes every zero byte from
+//! the encoded data, so the delimiter unambiguously ends each frame.
+
+use std::error::Error;
+use std::fmt;
+
+use {Fnv1a, FnvHash};
+
+const CHECKSUM_LEN: usize = 4;
+
+/// An error encoding or decoding a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// The output buffer was too small; `required` bytes are needed.
+    BufferTooSmall {
+        /// The number of bytes the output buffer must hold.
+        required: usize,
+    },
+    /// The frame is not valid COBS, as it contains a zero byte or a code
+    /// which runs past its end.
+    InvalidEncoding,
+    /// The frame decoded to fewer bytes than the checksum trailer.
+    TooShort,
+    /// The checksum trailer does not match the decoded payload.
+    ChecksumMismatch {
+        /// The checksum stored in the frame.
+        stored: u32,
+        /// The checksum of the decoded payload.
+        computed: u32,
+    },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FrameError::BufferTooSmall { required } => {
+                write!(f, "output buffer too small, {} bytes required", required)
+            }
+            FrameError::InvalidEncoding => write!(f, "invalid COBS encoding"),
+            FrameError::TooShort => write!(f, "frame too short for its checksum"),
+            FrameError::ChecksumMismatch { stored, computed } => write!(
+                f,
+                "checksum mismatch, stored {:#010x} but computed {:#010x}",
+                stored, computed
+            ),
+        }
+    }
+}
+
+impl Error for FrameError {}
+
+fn fnv1a_32(bytes: &[u8]) -> u32 {
+    let mut hasher = Fnv1a::<u32>::new();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Returns the maximum length of the frame encoding a payload of the
+/// specified length, including the checksum and delimiter.
+///
+/// COBS adds one code byte for every 254 bytes of data, rounded up, so this
+/// is `n + ceil(n / 254) + 1` where `n` is the payload length plus 4.
+///
+/// ```
+/// use lz_fnv::max_frame_len;
+///
+/// assert_eq!(max_frame_len(0), 6);
+/// assert_eq!(max_frame_len(250), 256);
+/// assert_eq!(max_frame_len(251), 258);
+/// ```
+pub const fn max_frame_len(payload_len: usize) -> usize {
+    let data_len = payload_len + CHECKSUM_LEN;
+    data_len + data_len.div_ceil(254) + 1
+}
+
+/// Encodes `payload` as a frame into `out`, returning the length of the
+/// frame.
+///
+/// The frame ends with its `0x00` delimiter. Returns an error, giving the
+/// required size, if `out` is shorter than `max_frame_len(payload.len())`.
+///
+/// ```
+/// use lz_fnv::{decode_frame, encode_frame};
+///
+/// let mut frame = [0; 32];
+/// let len = encode_frame(b"\x01\x00\x02", &mut frame).unwrap();
+///
+/// assert_eq!(frame[len - 1], 0);
+/// assert!(!frame[..len - 1].contains(&0));
+///
+/// let mut payload = [0; 32];
+/// let len = decode_frame(&frame[..len], &mut payload).unwrap();
+///
+/// assert_eq!(&payload[..len], b"\x01\x00\x02");
+/// ```
+pub fn encode_frame(payload: &[u8], out: &mut [u8]) -> Result<usize, FrameError> {
+    let required = max_frame_len(payload.len());
+    if out.len() < required {
+        return Err(FrameError::BufferTooSmall { required });
+    }
+
+    let checksum = fnv1a_32(payload).to_le_bytes();
+    let data_len = payload.len() + CHECKSUM_LEN;
+
+    let mut code_index = 0;
+    let mut len = 1;
+    let mut code = 1u8;
+
+    for (i, &byte) in payload.iter().chain(&checksum).enumerate() {
+        if byte != 0 {
+            out[len] = byte;
+            len += 1;
+            code += 1;
+        }
+
+        if byte == 0 || code == 0xFF {
+            out[code_index] = code;
+            code_index = len;
+            code = 1;
+
+            // A full block ending the data needs no empty block after it
+            if byte == 0 || i + 1 < data_len {
+                len += 1;
+            }
+        }
+    }
+
+    if code_index < len {
+        out[code_index] = code;
+    }
+    out[len] = 0;
+
+    Ok(len + 1)
+}
+
+/// Decodes the COBS data of a frame, calling `emit` with each decoded byte,
+/// and returns the number of bytes decoded.
+fn decode_with<F: FnMut(usize, u8)>(frame: &[u8], mut emit: F) -> Result<usize, FrameError> {
+    let mut len = 0;
+    let mut i = 0;
+
+    while i < frame.len() {
+        let code = frame[i] as usize;
+        if code == 0 || i + code > frame.len() {
+            return Err(FrameError::InvalidEncoding);
+        }
+
+        for &byte in &frame[i + 1..i + code] {
+            if byte == 0 {
+                return Err(FrameError::InvalidEncoding);
+            }
+            emit(len, byte);
+            len += 1;
+        }
+
+        i += code;
+        if code < 0xFF && i < frame.len() {
+            emit(len, 0);
+            len += 1;
+        }
+    }
+
+    Ok(len)
+}
+
+/// Decodes a frame into `out`, verifying and stripping its checksum, and
+/// returns the length of the payload.
+///
+/// The frame's trailing `0x00` delimiter may be included or omitted. COBS
+/// errors and checksum mismatches are reported as distinct errors. Returns an
+/// error, giving the required size, if `out` is shorter than the payload.
+pub fn decode_frame(frame: &[u8], out: &mut [u8]) -> Result<usize, FrameError> {
+    let frame = match frame.split_last() {
+        Some((&0, data)) => data,
+        _ => frame,
+    };
+
+    let decoded_len = decode_with(frame, |_, _| {})?;
+    if decoded_len < CHECKSUM_LEN {
+        return Err(FrameError::TooShort);
+    }
+
+    let payload_len = decoded_len - CHECKSUM_LEN;
+    if out.len() < payload_len {
+        return Err(FrameError::BufferTooSmall {
+            required: payload_len,
+        });
+    }
+
+    let mut trailer = [0; CHECKSUM_LEN];
+    decode_with(frame, |index, byte| {
+        if index < payload_len {
+            out[index] = byte;
+        } else {
+            trailer[index - payload_len] = byte;
+        }
+    })?;
+
+    let stored = u32::from_le_bytes(trailer);
+    let computed = fnv1a_32(&out[..payload_len]);
+    if stored != computed {
+        return Err(FrameError::ChecksumMismatch { stored, computed });
+    }
+
+    Ok(payload_len)
+}
+
+#[cfg(test)]
+mod tests {
+    use {decode_frame, encode_frame, max_frame_len, FrameError};
+
+    fn round_trip(payload: &[u8]) -> usize {
+        let mut frame = [0xAA; 1024];
+        let len = encode_frame(payload, &mut frame).unwrap();
+
+        assert!(len <= max_frame_len(payload.len()));
+        assert_eq!(frame[len - 1], 0);
+        assert!(!frame[..len - 1].contains(&0));
+
+        let mut decoded = [0; 1024];
+        let decoded_len = decode_frame(&frame[..len], &mut decoded).unwrap();
+        assert_eq!(&decoded[..decoded_len], payload);
+
+        let without_delimiter = decode_frame(&frame[..len - 1], &mut decoded).unwrap();
+        assert_eq!(without_delimiter, decoded_len);
+
+        len
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut payload = [0; 700];
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte = (i * 31 % 7) as u8;
+        }
+
+        for len in 0..payload.len() {
+            round_trip(&payload[..len]);
+        }
+        round_trip(&[0; 600]);
+        round_trip(&[0xFF; 600]);
+    }
+
+    #[test]
+    fn encodes_known_frame() {
+        // FNV-1a 32 of "\x11\x00\x22" is 0xdc35_49e2
+        let mut frame = [0; 16];
+        let len = encode_frame(b"\x11\x00\x22", &mut frame).unwrap();
+
+        assert_eq!(&frame[..len], b"\x02\x11\x06\x22\xe2\x49\x35\xdc\x00");
+    }
+
+    #[test]
+    fn maximum_size_framing() {
+        assert_eq!(max_frame_len(0), 4 + 1 + 1);
+        assert_eq!(max_frame_len(250), 254 + 1 + 1);
+        assert_eq!(max_frame_len(251), 255 + 2 + 1);
+        assert_eq!(max_frame_len(504), 508 + 2 + 1);
+        assert_eq!(max_frame_len(505), 509 + 3 + 1);
+
+        // Without zeros in the payload or checksum the maximum is reached
+        for &len in &[250, 251, 504, 505] {
+            let payload = [0x01; 505];
+            assert_eq!(round_trip(&payload[..len]), max_frame_len(len));
+        }
+    }
+
+    #[test]
+    fn small_buffers_report_required_size() {
+        let mut frame = [0; 32];
+        assert_eq!(
+            encode_frame(&[1; 12], &mut frame[..17]),
+            Err(FrameError::BufferTooSmall { required: 18 })
+        );
+
+        let len = encode_frame(&[1; 11], &mut frame).unwrap();
+        let mut payload = [0; 10];
+        assert_eq!(
+            decode_frame(&frame[..len], &mut payload),
+            Err(FrameError::BufferTooSmall { required: 11 })
+        );
+    }
+
+    #[test]
+    fn detects_checksum_mismatches() {
+        let mut frame = [0; 16];
+        let len = encode_frame(b"\x11\x00\x22", &mut frame).unwrap();
+        frame[1] = 0x12;
+
+        let mut payload = [0; 16];
+        assert_eq!(
+            decode_frame(&frame[..len], &mut payload),
+            Err(FrameError::ChecksumMismatch {
+                stored: 0xdc35_49e2,
+                computed: 0x94e9_5d77,
+            })
+        );
+    }
+
+    #[test]
+    fn detects_invalid_encodings() {
+        let mut payload = [0; 16];
+
+        // A zero within the frame
+        assert_eq!(
+            decode_frame(b"\x02\x11\x00\x22\xe2\x49\x35\xdc\x00", &mut payload),
+            Err(FrameError::InvalidEncoding)
+        );
+        // A code running past the end
+        assert_eq!(
+            decode_frame(b"\x02\x11\x09\x22\xe2\x49\x35\xdc\x00", &mut payload),
+            Err(FrameError::InvalidEncoding)
+        );
+        assert_eq!(
+            decode_frame(b"\x05\x11\x00", &mut payload),
+            Err(FrameError::InvalidEncoding)
+        );
+        assert_eq!(
+            decode_frame(b"\x03\x11\x22\x00", &mut payload),
+            Err(FrameError::TooShort)
+        );
+        assert_eq!(decode_frame(b"", &mut payload), Err(FrameError::TooShort));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn error_messages() {
+        assert_eq!(
+            FrameError::BufferTooSmall { required: 18 }.to_string(),
+            "output buffer too small, 18 bytes required"
+        );
+        assert_eq!(
+            FrameError::ChecksumMismatch {
+                stored: 1,
+                computed: 0xabcd
+            }
+            .to_string(),
+            "checksum mismatch, stored 0x00000001 but computed 0x0000abcd"
+        );
+    }
+}
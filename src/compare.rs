@@ -0,0 +1,83 @@
+//! Human-readable comparisons of the hashes of two inputs.
+
+use FnvExt;
+
+fn report(a: &[u8], b: &[u8], hash_a: u64, hash_b: u64) -> String {
+    let verdict = if hash_a != hash_b {
+        "no collision: the hashes differ"
+    } else if a == b {
+        "no collision: the inputs are identical"
+    } else {
+        "collision: the inputs differ but their hashes are equal"
+    };
+
+    format!(
+        "a: {:016x} (len {})\nb: {:016x} (len {})\n{}",
+        hash_a,
+        a.len(),
+        hash_b,
+        b.len(),
+        verdict
+    )
+}
+
+/// Returns a report comparing the FNV-1a 64 hashes of two inputs.
+///
+/// The report has three lines: the hash of `a` as 16 lowercase hex digits
+/// and the length of `a` in bytes, the same for `b`, and a verdict. The
+/// verdict is a collision only if the inputs differ but their hashes are
+/// equal; equal inputs always have equal hashes. The format is meant to be
+/// read, not parsed, and may change.
+///
+/// ```
+/// use lz_fnv::compare_report;
+///
+/// let report = compare_report(b"foobar", b"foobaz");
+///
+/// assert_eq!(
+///     report,
+///     "a: 85944171f73967e8 (len 6)\n\
+///      b: 85943971f7395a50 (len 6)\n\
+///      no collision: the hashes differ"
+/// );
+/// ```
+pub fn compare_report(a: &[u8], b: &[u8]) -> String {
+    report(a, b, a.fnv1a_64(), b.fnv1a_64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::report;
+    use compare_report;
+
+    #[test]
+    fn distinct_inputs() {
+        let report = compare_report(b"", b"a");
+
+        assert!(report.contains("cbf29ce484222325"));
+        assert!(report.contains("af63dc4c8601ec8c"));
+        assert!(report.contains("(len 0)"));
+        assert!(report.contains("(len 1)"));
+        assert!(report.ends_with("no collision: the hashes differ"));
+    }
+
+    #[test]
+    fn identical_inputs() {
+        let report = compare_report(b"foobar", b"foobar");
+
+        assert_eq!(report.matches("85944171f73967e8").count(), 2);
+        assert!(report.ends_with("no collision: the inputs are identical"));
+    }
+
+    #[test]
+    fn colliding_inputs() {
+        let report = report(b"first", b"second", 0x0123, 0x0123);
+
+        assert_eq!(
+            report,
+            "a: 0000000000000123 (len 5)\n\
+             b: 0000000000000123 (len 6)\n\
+             collision: the inputs differ but their hashes are equal"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Seedable hashing of integer coordinates for procedural generation.
+//!
+//! The construction is frozen. A point of arity `n` is hashed with FNV-1a 64
+//! over the byte `n`, each coordinate as 4 little-endian two's complement
+//! bytes and the seed as 8 little-endian bytes, and the hash is post-mixed
+//! with the MurmurHash3 64-bit finalizer. The arity byte separates points of
+//! different arities, and the finalizer spreads every input bit into the low
+//! bits of the result, which FNV-1a alone leaves periodic over a lattice.
+
+use step::fnv1a_64_advance;
+use unordered::fmix64;
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Hashes the two-dimensional point `(x, y)` with the specified seed.
+///
+/// ```
+/// use lz_fnv::hash_coords2;
+///
+/// const ORIGIN: u64 = hash_coords2(0, 0, 42);
+///
+/// assert_eq!(ORIGIN, hash_coords2(0, 0, 42));
+/// assert_ne!(hash_coords2(1, 2, 42), hash_coords2(2, 1, 42));
+/// ```
+pub const fn hash_coords2(x: i32, y: i32, seed: u64) -> u64 {
+    let mut hash = fnv1a_64_advance(OFFSET_BASIS, &[2]);
+    hash = fnv1a_64_advance(hash, &x.to_le_bytes());
+    hash = fnv1a_64_advance(hash, &y.to_le_bytes());
+    hash = fnv1a_64_advance(hash, &seed.to_le_bytes());
+    fmix64(hash)
+}
+
+/// Hashes the three-dimensional point `(x, y, z)` with the specified seed.
+///
+/// ```
+/// use lz_fnv::{hash_coords2, hash_coords3};
+///
+/// assert_ne!(hash_coords3(1, 2, 0, 42), hash_coords2(1, 2, 42));
+/// ```
+pub const fn hash_coords3(x: i32, y: i32, z: i32, seed: u64) -> u64 {
+    let mut hash = fnv1a_64_advance(OFFSET_BASIS, &[3]);
+    hash = fnv1a_64_advance(hash, &x.to_le_bytes());
+    hash = fnv1a_64_advance(hash, &y.to_le_bytes());
+    hash = fnv1a_64_advance(hash, &z.to_le_bytes());
+    hash = fnv1a_64_advance(hash, &seed.to_le_bytes());
+    fmix64(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use {hash_coords2, hash_coords3};
+
+    #[test]
+    fn pinned_vectors() {
+        assert_eq!(hash_coords2(0, 0, 0), 0x4466_0b95_51ea_f736);
+        assert_eq!(hash_coords2(-1, 7, 42), 0x1fda_b646_ee89_525f);
+        assert_eq!(hash_coords3(1, -2, 3, 0xdead_beef), 0xcbda_8bbf_f7cc_5a0c);
+    }
+
+    #[test]
+    fn arities_are_separated() {
+        assert_ne!(hash_coords2(1, 2, 0), hash_coords3(1, 2, 0, 0));
+        assert_ne!(hash_coords2(0, 0, 0), hash_coords3(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn seeds_change_hashes() {
+        assert_ne!(hash_coords2(5, 5, 1), hash_coords2(5, 5, 2));
+        assert_ne!(hash_coords3(5, 5, 5, 1), hash_coords3(5, 5, 5, 2));
+    }
+
+    #[test]
+    fn negative_coordinates() {
+        assert_ne!(hash_coords2(-1, 0, 0), hash_coords2(1, 0, 0));
+        assert_ne!(hash_coords2(i32::MIN, 0, 0), hash_coords2(i32::MAX, 0, 0));
+    }
+
+    #[test]
+    fn low_bits_are_evenly_spread_over_a_lattice() {
+        const TABLE: usize = 16;
+        let mut counts = [0u32; TABLE];
+        let mut row_counts = [[0u32; TABLE]; 64];
+
+        for x in -32..32 {
+            for y in -32..32 {
+                let slot = hash_coords2(x, y, 7) as usize % TABLE;
+                counts[slot] += 1;
+                row_counts[(x + 32) as usize][slot] += 1;
+            }
+        }
+
+        // 4096 points over 16 slots gives 256 expected in each
+        for &count in &counts {
+            assert!(count > 200 && count < 312, "counts {:?}", counts);
+        }
+
+        // A striped hash would give each row only a few distinct slots
+        for row in &row_counts {
+            assert!(row.iter().filter(|&&count| count > 0).count() >= 10);
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! A hasher wrapper counting the bytes written through it.
+
+use {FnvError, FnvHash};
+
+/// A hasher which counts the bytes written into it, so that too short an
+/// input can be rejected when finishing.
+///
+/// Writes are passed to the wrapped hasher unchanged, so the hash is the
+/// same as it would be without the wrapper.
+///
+/// ```
+/// use lz_fnv::{CountingHasher, Fnv1a, FnvError, FnvHash};
+///
+/// let mut hasher = CountingHasher::new(Fnv1a::<u64>::new());
+/// hasher.write(b"");
+///
+/// assert_eq!(
+///     hasher.finish_min_len(16),
+///     Err(FnvError::InputTooShort { got: 0, min: 16 })
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CountingHasher<H> {
+    inner: H,
+    bytes_written: u64,
+}
+
+impl<H: FnvHash> CountingHasher<H> {
+    /// Creates a new `CountingHasher` wrapping the specified hasher, with no
+    /// bytes counted.
+    pub fn new(inner: H) -> Self {
+        Self {
+            inner,
+            bytes_written: 0,
+        }
+    }
+
+    /// Returns the number of bytes written through this `CountingHasher`.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Returns the hash if at least `min` bytes have been written, or an
+    /// `FnvError::InputTooShort` error otherwise.
+    ///
+    /// This catches keys which are accidentally empty or truncated before
+    /// their hash is used.
+    pub fn finish_min_len(&self, min: u64) -> Result<H::Hash, FnvError> {
+        if self.bytes_written < min {
+            return Err(FnvError::InputTooShort {
+                got: self.bytes_written,
+                min,
+            });
+        }
+
+        Ok(self.inner.finish())
+    }
+
+    /// Gets a reference to the wrapped hasher.
+    pub fn get_ref(&self) -> &H {
+        &self.inner
+    }
+
+    /// Consumes this `CountingHasher`, returning the wrapped hasher.
+    pub fn into_inner(self) -> H {
+        self.inner
+    }
+}
+
+impl<H: FnvHash> FnvHash for CountingHasher<H> {
+    type Hash = H::Hash;
+
+    fn finish(&self) -> H::Hash {
+        self.inner.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.inner.write(bytes);
+        self.bytes_written += bytes.len() as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use FnvError;
+    use {CountingHasher, Fnv1, Fnv1a, FnvHash};
+
+    #[test]
+    fn counts_every_write() {
+        let mut hasher = CountingHasher::new(Fnv1a::<u64>::new());
+        assert_eq!(hasher.bytes_written(), 0);
+
+        hasher.write(b"foo");
+        hasher.write(b"");
+        hasher.write_varint(300);
+
+        assert_eq!(hasher.bytes_written(), 5);
+    }
+
+    #[test]
+    fn hash_is_unchanged() {
+        let mut counting = CountingHasher::new(Fnv1::<u32>::new());
+        counting.write(b"foo");
+        counting.write(b"bar");
+
+        let mut plain = Fnv1::<u32>::new();
+        plain.write(b"foobar");
+
+        assert_eq!(counting.finish(), plain.finish());
+        assert_eq!(counting.into_inner().finish(), plain.finish());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn too_short_input_is_rejected() {
+        let mut hasher = CountingHasher::new(Fnv1a::<u64>::new());
+        assert_eq!(
+            hasher.finish_min_len(1),
+            Err(FnvError::InputTooShort { got: 0, min: 1 })
+        );
+
+        hasher.write(b"foobar");
+        let error = hasher.finish_min_len(7).unwrap_err();
+
+        assert_eq!(error, FnvError::InputTooShort { got: 6, min: 7 });
+        assert_eq!(
+            error.to_string(),
+            "6 bytes were hashed but at least 7 are required"
+        );
+    }
+
+    #[test]
+    fn long_enough_input_finishes() {
+        let mut hasher = CountingHasher::new(Fnv1a::<u64>::new());
+        assert_eq!(hasher.finish_min_len(0), Ok(0xcbf2_9ce4_8422_2325));
+
+        hasher.write(b"foobar");
+
+        assert_eq!(hasher.finish_min_len(6), Ok(0x8594_4171_f739_67e8));
+        assert_eq!(hasher.finish_min_len(3), Ok(0x8594_4171_f739_67e8));
+    }
+}
@@ -0,0 +1,159 @@
+//! Canonical hashing of `rust_decimal` decimals.
+
+use rust_decimal::Decimal;
+
+use {Fnv1a, FnvHash};
+
+/// Returns the 14 bytes which `FnvHash::write_decimal` writes for a
+/// decimal.
+pub(crate) fn canonical_bytes(d: &Decimal) -> [u8; 14] {
+    let mut bytes = [0u8; 14];
+
+    let d = d.normalize();
+    let mantissa = d.mantissa();
+    if mantissa == 0 {
+        return bytes;
+    }
+
+    bytes[0] = (mantissa < 0) as u8;
+    bytes[1] = d.scale() as u8;
+    bytes[2..].copy_from_slice(&mantissa.unsigned_abs().to_le_bytes()[..12]);
+    bytes
+}
+
+/// Returns the FNV-1a 64 hash of the canonical form of a decimal, as written
+/// by `FnvHash::write_decimal`.
+///
+/// Numerically equal decimals hash equally however many trailing zeros they
+/// carry, and the hash of a decimal will not change between versions.
+///
+/// ```
+/// extern crate lz_fnv;
+/// extern crate rust_decimal;
+///
+/// use lz_fnv::hash_decimal;
+/// use rust_decimal::Decimal;
+///
+/// # fn main() {
+/// assert_eq!(hash_decimal(&Decimal::new(150, 2)), hash_decimal(&Decimal::new(15, 1)));
+/// assert_eq!(hash_decimal(&Decimal::new(15, 1)), 0x33b0_6638_e788_caa5);
+/// # }
+/// ```
+pub fn hash_decimal(d: &Decimal) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write_decimal(d);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal::Decimal;
+
+    use super::canonical_bytes;
+    use {hash_decimal, Fnv1a, FnvHash};
+
+    fn negative_zero(scale: u32) -> Decimal {
+        let mut zero = Decimal::new(0, scale);
+        zero.set_sign_negative(true);
+        zero
+    }
+
+    #[test]
+    fn pinned_vectors() {
+        let one_e_minus_28 = Decimal::from_i128_with_scale(1, 28);
+
+        let vectors = [
+            (Decimal::ZERO, 0x8df3_52d4_f9fa_3add),
+            (Decimal::ONE, 0x98af_cf34_9f2c_022c),
+            (Decimal::new(15, 1), 0x33b0_6638_e788_caa5),
+            (Decimal::new(-15, 1), 0x11b6_b01f_c884_c0f8),
+            (Decimal::MAX, 0x8e02_ddcf_d76c_e831),
+            (Decimal::MIN, 0x6d25_629f_e14f_3ed8),
+            (one_e_minus_28, 0xda49_e9d4_438a_bcd8),
+        ];
+
+        for &(d, expected) in &vectors {
+            assert_eq!(hash_decimal(&d), expected, "{}", d);
+        }
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(canonical_bytes(&Decimal::ZERO), [0; 14]);
+        assert_eq!(
+            canonical_bytes(&Decimal::new(-1_500, 3)),
+            [1, 1, 15, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+        assert_eq!(
+            canonical_bytes(&Decimal::MAX),
+            [0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+        );
+    }
+
+    #[test]
+    fn equal_values_hash_equally() {
+        let equal = [
+            [
+                Decimal::new(15, 1),
+                Decimal::new(150, 2),
+                Decimal::new(1_500_000, 6),
+            ],
+            [Decimal::ZERO, Decimal::new(0, 28), negative_zero(0)],
+            [negative_zero(5), Decimal::new(0, 3), Decimal::new(-0, 1)],
+            [
+                Decimal::ONE,
+                Decimal::new(10, 1),
+                Decimal::from_i128_with_scale(10i128.pow(28), 28),
+            ],
+            [
+                Decimal::new(-42, 0),
+                Decimal::new(-4_200, 2),
+                Decimal::from_i128_with_scale(-42 * 10i128.pow(26), 26),
+            ],
+        ];
+
+        for values in &equal {
+            for d in values {
+                assert_eq!(hash_decimal(d), hash_decimal(&values[0]), "{}", d);
+            }
+        }
+    }
+
+    #[test]
+    fn distinct_values_hash_differently() {
+        let distinct = [
+            Decimal::ZERO,
+            Decimal::ONE,
+            Decimal::NEGATIVE_ONE,
+            Decimal::new(15, 1),
+            Decimal::new(-15, 1),
+            Decimal::new(15, 2),
+            Decimal::new(15, 0),
+            Decimal::new(151, 2),
+            Decimal::MAX,
+            Decimal::MIN,
+            Decimal::from_i128_with_scale(1, 28),
+            Decimal::from_i128_with_scale(-1, 28),
+            Decimal::from_i128_with_scale(1, 27),
+        ];
+
+        for (i, a) in distinct.iter().enumerate() {
+            for b in &distinct[i + 1..] {
+                assert_ne!(hash_decimal(a), hash_decimal(b), "{} and {}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn writes_compose() {
+        let mut decimals = Fnv1a::<u64>::new();
+        decimals.write_decimal(&Decimal::new(150, 2));
+        decimals.write_decimal(&Decimal::new(-2, 0));
+
+        let mut bytes = Fnv1a::<u64>::new();
+        bytes.write(&canonical_bytes(&Decimal::new(15, 1)));
+        bytes.write(&canonical_bytes(&Decimal::new(-2, 0)));
+
+        assert_eq!(decimals.finish(), bytes.finish());
+    }
+}
@@ -0,0 +1,188 @@
+//! Hashing of every file in a directory tree.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Component, Path};
+
+use file::write_reader;
+use walk::{walk, DirHashOptions};
+use {CountingHasher, Fnv1a, FnvHash};
+
+/// Writes the components of a relative path joined by `/`, so that a path
+/// hashes the same on every platform.
+fn write_relative_path(hasher: &mut Fnv1a<u64>, path: &Path) -> io::Result<()> {
+    let mut joined = String::new();
+
+    for component in path.components() {
+        let name = match component {
+            Component::Normal(name) => name.to_str().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: file name is not valid UTF-8", path.display()),
+                )
+            })?,
+            _ => unreachable!("walked paths are relative and normalized"),
+        };
+
+        if !joined.is_empty() {
+            joined.push('/');
+        }
+        joined.push_str(name);
+    }
+
+    hasher.write(&(joined.len() as u64).to_le_bytes());
+    hasher.write(joined.as_bytes());
+    Ok(())
+}
+
+/// Hashes the paths and contents of every file under `root` with FNV-1a 64,
+/// as a fingerprint of the whole tree.
+///
+/// Files are visited in the order of their paths relative to `root`,
+/// compared component by component, so the hash does not depend on the
+/// order in which the filesystem lists entries. For each file the hash is
+/// fed, with framing which will not change between versions:
+///
+/// 1. the length of its relative path as 8 little-endian bytes,
+/// 2. the relative path as UTF-8, with components joined by `/` on every
+///    platform,
+/// 3. the length of its contents as 8 little-endian bytes, and
+/// 4. its contents.
+///
+/// Only regular files are hashed, so empty directories do not affect the
+/// hash, and a tree with no files hashes as the empty input. Symbolic links
+/// are not followed and do not affect the hash either; hidden entries are
+/// included. A file name which is not valid UTF-8 is an
+/// `io::ErrorKind::InvalidData` error, and a file which shrinks while it is
+/// read an `io::ErrorKind::UnexpectedEof` error, each with the path
+/// prepended to its message.
+///
+/// ```no_run
+/// use lz_fnv::hash_dir;
+///
+/// let fingerprint = hash_dir("src").unwrap();
+/// println!("{:016x}", fingerprint);
+/// ```
+pub fn hash_dir<P: AsRef<Path>>(root: P) -> io::Result<u64> {
+    let root = root.as_ref();
+    let files = walk(root, &DirHashOptions::default())?;
+
+    let mut hasher = Fnv1a::<u64>::new();
+    for path in &files {
+        write_relative_path(&mut hasher, path)?;
+
+        let file = File::open(root.join(path))?;
+        let len = file.metadata()?.len();
+        hasher.write(&len.to_le_bytes());
+
+        let mut counting = CountingHasher::new(hasher);
+        write_reader(&mut counting, &mut file.take(len))?;
+        if counting.bytes_written() != len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{}: file shrank while it was hashed", path.display()),
+            ));
+        }
+        hasher = counting.into_inner();
+    }
+
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempfile::tempdir;
+
+    use walk::tests::create_tree;
+    use {hash_dir, Fnv1a, FnvHash};
+
+    #[test]
+    fn hash_dir_is_deterministic() {
+        let dir = tempdir().unwrap();
+        create_tree(dir.path());
+        fs::create_dir_all(dir.path().join("a/empty_dir")).unwrap();
+
+        let first = hash_dir(dir.path()).unwrap();
+        assert_eq!(hash_dir(dir.path()).unwrap(), first);
+
+        // The same files created in another order hash equally
+        let other = tempdir().unwrap();
+        fs::create_dir_all(other.path().join(".hidden_dir")).unwrap();
+        fs::create_dir_all(other.path().join("a/b")).unwrap();
+        fs::write(other.path().join(".hidden_dir/inner"), b"inner").unwrap();
+        fs::write(other.path().join(".hidden"), b"hidden").unwrap();
+        fs::write(other.path().join("a/b/empty"), b"").unwrap();
+        fs::write(other.path().join("a/b/two.txt"), b"two").unwrap();
+        fs::write(other.path().join("a/one.txt"), b"one").unwrap();
+        fs::write(other.path().join("top.txt"), b"top").unwrap();
+        assert_eq!(hash_dir(other.path()).unwrap(), first);
+    }
+
+    #[test]
+    fn hash_dir_detects_changes() {
+        let dir = tempdir().unwrap();
+        create_tree(dir.path());
+        let before = hash_dir(dir.path()).unwrap();
+
+        fs::write(dir.path().join("a/one.txt"), b"One").unwrap();
+        let changed = hash_dir(dir.path()).unwrap();
+        assert_ne!(changed, before);
+
+        fs::rename(dir.path().join("a/one.txt"), dir.path().join("a/won.txt")).unwrap();
+        assert_ne!(hash_dir(dir.path()).unwrap(), changed);
+    }
+
+    #[test]
+    fn hash_dir_frames_paths_and_contents() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("ab"), b"c").unwrap();
+        fs::write(dir.path().join("sub/x"), b"yz").unwrap();
+
+        let mut expected = Fnv1a::<u64>::new();
+        expected.write(&2u64.to_le_bytes());
+        expected.write(b"ab");
+        expected.write(&1u64.to_le_bytes());
+        expected.write(b"c");
+        expected.write(&5u64.to_le_bytes());
+        expected.write(b"sub/x");
+        expected.write(&2u64.to_le_bytes());
+        expected.write(b"yz");
+        assert_eq!(hash_dir(dir.path()).unwrap(), expected.finish());
+
+        // Moving a byte from the contents into the path changes the hash
+        let moved = tempdir().unwrap();
+        fs::write(moved.path().join("abc"), b"").unwrap();
+        fs::create_dir_all(moved.path().join("sub")).unwrap();
+        fs::write(moved.path().join("sub/x"), b"yz").unwrap();
+        assert_ne!(hash_dir(moved.path()).unwrap(), expected.finish());
+    }
+
+    #[test]
+    fn hash_dir_of_empty_tree() {
+        let dir = tempdir().unwrap();
+        assert_eq!(hash_dir(dir.path()).unwrap(), 0xcbf2_9ce4_8422_2325);
+
+        fs::create_dir_all(dir.path().join("a/b/c")).unwrap();
+        assert_eq!(hash_dir(dir.path()).unwrap(), 0xcbf2_9ce4_8422_2325);
+
+        assert!(hash_dir(dir.path().join("missing")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hash_dir_skips_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir().unwrap();
+        create_tree(dir.path());
+        let before = hash_dir(dir.path()).unwrap();
+
+        symlink(dir.path().join("top.txt"), dir.path().join("link.txt")).unwrap();
+        symlink(dir.path().join("a"), dir.path().join("a/b/loop")).unwrap();
+        symlink(dir.path().join("missing"), dir.path().join("dangling")).unwrap();
+
+        assert_eq!(hash_dir(dir.path()).unwrap(), before);
+    }
+}
@@ -0,0 +1,179 @@
+//! Parallel hashing of every file in a directory tree.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use hash_file;
+use walk::{walk, DirHashOptions};
+
+/// Hashes every file under `root` with FNV-1a 64, in parallel.
+///
+/// Returns a map from each file's path, relative to `root`, to its hash.
+/// Only regular files are hashed; the map's ordering is that of
+/// `BTreeMap<PathBuf, _>` and so does not depend on the order in which the
+/// filesystem lists entries.
+///
+/// Errors are fail-fast: the directory tree is walked first, and an error
+/// walking it is returned immediately. Every file found is then hashed, and if
+/// any of them cannot be read the error for the first such path, in the
+/// map's ordering, is returned with the path prepended to its message.
+///
+/// ```no_run
+/// use lz_fnv::{hash_dir_files, DirHashOptions};
+/// use std::path::Path;
+///
+/// let manifest = hash_dir_files(Path::new("src"), &DirHashOptions::new()).unwrap();
+///
+/// for (path, hash) in &manifest {
+///     println!("{:016x}  {}", hash, path.display());
+/// }
+/// ```
+pub fn hash_dir_files(root: &Path, options: &DirHashOptions) -> io::Result<BTreeMap<PathBuf, u64>> {
+    hash_files(root, walk(root, options)?)
+}
+
+/// Hashes the files at `files`, relative to `root`, which the walk found.
+fn hash_files(root: &Path, files: Vec<PathBuf>) -> io::Result<BTreeMap<PathBuf, u64>> {
+    let hashes: Vec<io::Result<u64>> = files
+        .par_iter()
+        .map(|path| hash_file(root.join(path)))
+        .collect();
+
+    files
+        .into_iter()
+        .zip(hashes)
+        .map(|(path, hash)| match hash {
+            Ok(hash) => Ok((path, hash)),
+            Err(e) => Err(io::Error::new(
+                e.kind(),
+                format!("{}: {}", path.display(), e),
+            )),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use tempfile::tempdir;
+
+    use dir_par::hash_files;
+    use walk::tests::create_tree;
+    use walk::walk;
+    use {hash_dir_files, hash_file, DirHashOptions};
+
+    fn serial(root: &Path, paths: &[&str]) -> BTreeMap<PathBuf, u64> {
+        paths
+            .iter()
+            .map(|path| (PathBuf::from(path), hash_file(root.join(path)).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn matches_serial_hashing() {
+        let dir = tempdir().unwrap();
+        create_tree(dir.path());
+
+        let manifest = hash_dir_files(dir.path(), &DirHashOptions::new()).unwrap();
+
+        assert_eq!(
+            manifest,
+            serial(
+                dir.path(),
+                &[
+                    ".hidden",
+                    ".hidden_dir/inner",
+                    "a/b/empty",
+                    "a/b/two.txt",
+                    "a/one.txt",
+                    "top.txt",
+                ]
+            )
+        );
+        assert_eq!(manifest[Path::new("a/b/empty")], 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    fn excludes_hidden() {
+        let dir = tempdir().unwrap();
+        create_tree(dir.path());
+
+        let options = DirHashOptions::new().include_hidden(false);
+        let manifest = hash_dir_files(dir.path(), &options).unwrap();
+
+        assert_eq!(
+            manifest.keys().collect::<Vec<_>>(),
+            ["a/b/empty", "a/b/two.txt", "a/one.txt", "top.txt"]
+                .iter()
+                .map(Path::new)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_policy() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir().unwrap();
+        create_tree(dir.path());
+        symlink(dir.path().join("top.txt"), dir.path().join("link.txt")).unwrap();
+        symlink(dir.path().join("a"), dir.path().join("a/b/loop")).unwrap();
+
+        let skipped = hash_dir_files(dir.path(), &DirHashOptions::new()).unwrap();
+        assert!(!skipped.contains_key(Path::new("link.txt")));
+        assert_eq!(skipped.len(), 6);
+
+        let followed =
+            hash_dir_files(dir.path(), &DirHashOptions::new().follow_symlinks(true)).unwrap();
+        assert_eq!(
+            followed[Path::new("link.txt")],
+            followed[Path::new("top.txt")]
+        );
+        assert_eq!(followed.len(), 7);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unreadable_file_fails() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir().unwrap();
+        create_tree(dir.path());
+        symlink(dir.path().join("missing"), dir.path().join("dangling")).unwrap();
+
+        let options = DirHashOptions::new().follow_symlinks(true);
+        assert!(hash_dir_files(dir.path(), &options).is_err());
+    }
+
+    #[test]
+    fn file_removed_after_the_walk_fails() {
+        let dir = tempdir().unwrap();
+        create_tree(dir.path());
+        let files = walk(dir.path(), &DirHashOptions::new()).unwrap();
+
+        fs::remove_file(dir.path().join("a/one.txt")).unwrap();
+        fs::remove_file(dir.path().join("top.txt")).unwrap();
+        fs::create_dir(dir.path().join("top.txt")).unwrap();
+
+        // Both files fail to hash, and the first in path order is reported
+        let error = hash_files(dir.path(), files).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(error
+            .to_string()
+            .starts_with(&format!("{}: ", Path::new("a/one.txt").display())));
+    }
+
+    #[test]
+    fn missing_root_fails() {
+        let dir = tempdir().unwrap();
+
+        assert!(hash_dir_files(&dir.path().join("missing"), &DirHashOptions::new()).is_err());
+    }
+}
@@ -0,0 +1,62 @@
+//! An object-safe interface over the FNV hashers of every width.
+
+use FnvHash;
+
+/// An object-safe FNV hasher of any width.
+///
+/// This is implemented for every `FnvHash` and allows hashers of different
+/// widths to be used through `&mut dyn DynFnvHasher` or
+/// `Box<dyn DynFnvHasher>`. The methods are named distinctly from those of
+/// `FnvHash` so that both traits may be imported together.
+pub trait DynFnvHasher {
+    /// Writes some data into this hasher.
+    fn write_bytes(&mut self, bytes: &[u8]);
+
+    /// Returns the current hash, zero-extended to a `u128`.
+    fn finish_u128(&self) -> u128;
+
+    /// Returns the width of the hash in bits.
+    fn hash_bits(&self) -> u32;
+}
+
+impl<H> DynFnvHasher for H
+where
+    H: FnvHash,
+    H::Hash: Into<u128>,
+{
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write(bytes);
+    }
+
+    fn finish_u128(&self) -> u128 {
+        self.finish().into()
+    }
+
+    fn hash_bits(&self) -> u32 {
+        (::std::mem::size_of::<H::Hash>() * 8) as u32
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use {DynFnvHasher, Fnv0, Fnv1, Fnv1a};
+
+    #[test]
+    fn dispatches_to_every_width() {
+        let mut hashers: Vec<Box<dyn DynFnvHasher>> = vec![
+            Box::new(Fnv1a::<u32>::new()),
+            Box::new(Fnv1::<u64>::new()),
+            Box::new(Fnv0::<u128>::new()),
+        ];
+
+        for hasher in &mut hashers {
+            hasher.write_bytes(b"foobar");
+        }
+
+        assert_eq!(hashers[0].finish_u128(), 0xbf9c_f968);
+        assert_eq!(hashers[0].hash_bits(), 32);
+        assert_eq!(hashers[1].finish_u128(), 0x340d_8765_a4dd_a9c2);
+        assert_eq!(hashers[1].hash_bits(), 64);
+        assert_eq!(hashers[2].hash_bits(), 128);
+    }
+}
@@ -0,0 +1,214 @@
+//! Fingerprints of environment variables, for invalidating build caches.
+
+use std::collections::HashMap;
+use std::env;
+use std::ffi::{OsStr, OsString};
+
+use {Fnv1a, FnvHash};
+
+const ABSENT: u8 = 0x00;
+const PRESENT: u8 = 0x01;
+
+fn write_framed(hasher: &mut Fnv1a<u64>, bytes: &[u8]) {
+    hasher.write(&(bytes.len() as u64).to_le_bytes());
+    hasher.write(bytes);
+}
+
+fn fingerprint<'a, I, F>(keys: I, mut lookup: F) -> u64
+where
+    I: IntoIterator<Item = &'a str>,
+    F: FnMut(&str) -> Option<OsString>,
+{
+    let mut hasher = Fnv1a::<u64>::new();
+    for key in keys {
+        write_framed(&mut hasher, key.as_bytes());
+
+        match lookup(key) {
+            Some(value) => {
+                hasher.write(&[PRESENT]);
+                write_framed(&mut hasher, value.as_encoded_bytes());
+            }
+            None => hasher.write(&[ABSENT]),
+        }
+    }
+
+    hasher.finish()
+}
+
+fn sorted<'a>(keys: &[&'a str]) -> Vec<&'a str> {
+    let mut keys = keys.to_vec();
+    keys.sort_unstable();
+    keys
+}
+
+fn collect_env<I, K, V>(env: I) -> HashMap<OsString, OsString>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    env.into_iter()
+        .map(|(key, value)| (key.as_ref().to_owned(), value.as_ref().to_owned()))
+        .collect()
+}
+
+/// Returns a fingerprint of the specified environment variables of this
+/// process, in the given order.
+///
+/// The fingerprint is the FNV-1a 64 hash of, for each key in turn:
+///
+/// 1. the key, as its length in bytes as 8 little-endian bytes followed by
+///    its UTF-8 bytes,
+/// 2. the byte `0x00` if the variable is unset, or `0x01` if it is set, and
+/// 3. if it is set, its value, framed as the key.
+///
+/// An unset variable therefore fingerprints differently from one set to the
+/// empty string. Values are hashed as their platform encoding, the bytes of
+/// `OsStr::as_encoded_bytes`, so fingerprints of non-UTF-8 values are not
+/// portable between platforms. Reordering the keys changes the fingerprint;
+/// see [`env_fingerprint_sorted`] for an order-independent one.
+///
+/// ```
+/// use lz_fnv::{env_fingerprint, env_fingerprint_from};
+///
+/// let keys = ["CC", "CFLAGS"];
+///
+/// assert_eq!(
+///     env_fingerprint(&keys),
+///     env_fingerprint_from(std::env::vars_os(), &keys)
+/// );
+/// ```
+///
+/// [`env_fingerprint_sorted`]: fn.env_fingerprint_sorted.html
+pub fn env_fingerprint(keys: &[&str]) -> u64 {
+    fingerprint(keys.iter().cloned(), |key| env::var_os(key))
+}
+
+/// Returns a fingerprint of the specified environment variables of this
+/// process, independent of their order.
+///
+/// This is [`env_fingerprint`] of the keys sorted by their bytes.
+///
+/// [`env_fingerprint`]: fn.env_fingerprint.html
+pub fn env_fingerprint_sorted(keys: &[&str]) -> u64 {
+    env_fingerprint(&sorted(keys))
+}
+
+/// Returns a fingerprint, as [`env_fingerprint`], of the specified variables
+/// of an environment given as pairs of keys and values.
+///
+/// This fingerprints an environment other than this process's, such as one
+/// configured for a child process, equally to `env_fingerprint` of the same
+/// variables set in this process. If a key appears more than once its last
+/// value is used.
+///
+/// ```
+/// use lz_fnv::env_fingerprint_from;
+///
+/// let env = [("CC", "clang"), ("CFLAGS", "")];
+///
+/// assert_ne!(
+///     env_fingerprint_from(env.iter().cloned(), &["CC", "CFLAGS"]),
+///     env_fingerprint_from(env.iter().cloned(), &["CC", "LDFLAGS"])
+/// );
+/// ```
+///
+/// [`env_fingerprint`]: fn.env_fingerprint.html
+pub fn env_fingerprint_from<I, K, V>(env: I, keys: &[&str]) -> u64
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    let env = collect_env(env);
+    fingerprint(keys.iter().cloned(), |key| {
+        env.get(OsStr::new(key)).cloned()
+    })
+}
+
+/// Returns a fingerprint, as [`env_fingerprint_sorted`], of the specified
+/// variables of an environment given as pairs of keys and values.
+///
+/// [`env_fingerprint_sorted`]: fn.env_fingerprint_sorted.html
+pub fn env_fingerprint_sorted_from<I, K, V>(env: I, keys: &[&str]) -> u64
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<OsStr>,
+    V: AsRef<OsStr>,
+{
+    env_fingerprint_from(env, &sorted(keys))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use {
+        env_fingerprint, env_fingerprint_from, env_fingerprint_sorted, env_fingerprint_sorted_from,
+    };
+
+    const ENV: [(&str, &str); 3] = [("CC", "clang"), ("CFLAGS", "-O2"), ("EMPTY", "")];
+
+    fn fake(keys: &[&str]) -> u64 {
+        env_fingerprint_from(ENV.iter().cloned(), keys)
+    }
+
+    #[test]
+    fn stability_vectors() {
+        assert_eq!(fake(&[]), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fake(&["CC"]), 0xe49c_b4d3_8d48_52aa);
+        assert_eq!(fake(&["EMPTY"]), 0x7fb6_3228_43c2_3290);
+        assert_eq!(fake(&["UNSET"]), 0x5cb5_c935_0c21_5183);
+        assert_eq!(fake(&["CC", "CFLAGS", "UNSET"]), 0x66db_732f_3dd1_1958);
+    }
+
+    #[test]
+    fn unset_and_empty_differ() {
+        let unset = env_fingerprint_from(Vec::<(&str, &str)>::new(), &["EMPTY"]);
+
+        assert_ne!(fake(&["EMPTY"]), unset);
+        assert_ne!(fake(&["CC"]), fake(&[]));
+    }
+
+    #[test]
+    fn values_are_hashed() {
+        let changed = [("CC", "gcc")];
+
+        assert_ne!(
+            env_fingerprint_from(changed.iter().cloned(), &["CC"]),
+            fake(&["CC"])
+        );
+    }
+
+    #[test]
+    fn order_sensitivity() {
+        assert_ne!(fake(&["CC", "CFLAGS"]), fake(&["CFLAGS", "CC"]));
+
+        let sorted = env_fingerprint_sorted_from(ENV.iter().cloned(), &["CFLAGS", "UNSET", "CC"]);
+        assert_eq!(sorted, fake(&["CC", "CFLAGS", "UNSET"]));
+        assert_eq!(
+            sorted,
+            env_fingerprint_sorted_from(ENV.iter().cloned(), &["UNSET", "CC", "CFLAGS"])
+        );
+    }
+
+    // The process environment is only read, never modified, as other tests
+    // run in parallel
+    #[test]
+    fn process_environment() {
+        let mut keys: Vec<String> = env::vars_os()
+            .filter_map(|(key, _)| key.into_string().ok())
+            .take(8)
+            .collect();
+        keys.push("LZ_FNV_ENV_FINGERPRINT_UNSET".to_owned());
+        let keys: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+        assert_eq!(
+            env_fingerprint(&keys),
+            env_fingerprint_from(env::vars_os(), &keys)
+        );
+        assert_eq!(
+            env_fingerprint_sorted(&keys),
+            env_fingerprint_sorted_from(env::vars_os(), &keys)
+        );
+    }
+}
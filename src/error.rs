@@ -0,0 +1,63 @@
+//! The error type of the hashers' fallible operations.
+
+use std::error::Error;
+use std::fmt;
+
+/// An error finishing or formatting a hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FnvError {
+    /// Fewer bytes were written than the minimum required.
+    InputTooShort {
+        /// The number of bytes written.
+        got: u64,
+        /// The minimum number of bytes required.
+        min: u64,
+    },
+    /// The buffer given for formatting the hash was too small.
+    BufferTooSmall {
+        /// The length of the buffer given.
+        got: usize,
+        /// The length of buffer required.
+        needed: usize,
+    },
+}
+
+impl fmt::Display for FnvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FnvError::InputTooShort { got, min } => write!(
+                f,
+                "{} bytes were hashed but at least {} are required",
+                got, min
+            ),
+            FnvError::BufferTooSmall { got, needed } => write!(
+                f,
+                "a buffer of {} bytes was given but {} are required",
+                got, needed
+            ),
+        }
+    }
+}
+
+impl Error for FnvError {}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use FnvError;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            FnvError::InputTooShort { got: 6, min: 7 }.to_string(),
+            "6 bytes were hashed but at least 7 are required"
+        );
+        assert_eq!(
+            FnvError::BufferTooSmall {
+                got: 15,
+                needed: 16
+            }
+            .to_string(),
+            "a buffer of 15 bytes was given but 16 are required"
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! An extension trait hashing byte slices and strings in one call.
+
+use {Fnv0, Fnv1, Fnv1a, FnvHash};
+
+fn hash<H: FnvHash>(mut hasher: H, bytes: &[u8]) -> H::Hash {
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Hashes a byte slice or string with each FNV variant and width.
+///
+/// A string hashes as its UTF-8 bytes, with no terminator, so it hashes
+/// equally to `str::as_bytes`. This differs from `std::hash::Hash` for `str`,
+/// which appends a `0xff` byte.
+///
+/// ```
+/// use lz_fnv::FnvExt;
+///
+/// assert_eq!("foobar".fnv1a_64(), 0x8594_4171_f739_67e8);
+/// assert_eq!(b"foobar".fnv1a_64(), "foobar".fnv1a_64());
+/// ```
+pub trait FnvExt {
+    /// Returns the FNV-0 32 hash.
+    fn fnv0_32(&self) -> u32;
+
+    /// Returns the FNV-0 64 hash.
+    fn fnv0_64(&self) -> u64;
+
+    /// Returns the FNV-0 128 hash.
+    fn fnv0_128(&self) -> u128;
+
+    /// Returns the FNV-1 32 hash.
+    fn fnv1_32(&self) -> u32;
+
+    /// Returns the FNV-1 64 hash.
+    fn fnv1_64(&self) -> u64;
+
+    /// Returns the FNV-1 128 hash.
+    fn fnv1_128(&self) -> u128;
+
+    /// Returns the FNV-1a 32 hash.
+    fn fnv1a_32(&self) -> u32;
+
+    /// Returns the FNV-1a 64 hash.
+    fn fnv1a_64(&self) -> u64;
+
+    /// Returns the FNV-1a 128 hash.
+    fn fnv1a_128(&self) -> u128;
+}
+
+impl FnvExt for [u8] {
+    fn fnv0_32(&self) -> u32 {
+        hash(Fnv0::<u32>::new(), self)
+    }
+
+    fn fnv0_64(&self) -> u64 {
+        hash(Fnv0::<u64>::new(), self)
+    }
+
+    fn fnv0_128(&self) -> u128 {
+        hash(Fnv0::<u128>::new(), self)
+    }
+
+    fn fnv1_32(&self) -> u32 {
+        hash(Fnv1::<u32>::new(), self)
+    }
+
+    fn fnv1_64(&self) -> u64 {
+        hash(Fnv1::<u64>::new(), self)
+    }
+
+    fn fnv1_128(&self) -> u128 {
+        hash(Fnv1::<u128>::new(), self)
+    }
+
+    fn fnv1a_32(&self) -> u32 {
+        hash(Fnv1a::<u32>::new(), self)
+    }
+
+    fn fnv1a_64(&self) -> u64 {
+        hash(Fnv1a::<u64>::new(), self)
+    }
+
+    fn fnv1a_128(&self) -> u128 {
+        hash(Fnv1a::<u128>::new(), self)
+    }
+}
+
+impl FnvExt for str {
+    fn fnv0_32(&self) -> u32 {
+        self.as_bytes().fnv0_32()
+    }
+
+    fn fnv0_64(&self) -> u64 {
+        self.as_bytes().fnv0_64()
+    }
+
+    fn fnv0_128(&self) -> u128 {
+        self.as_bytes().fnv0_128()
+    }
+
+    fn fnv1_32(&self) -> u32 {
+        self.as_bytes().fnv1_32()
+    }
+
+    fn fnv1_64(&self) -> u64 {
+        self.as_bytes().fnv1_64()
+    }
+
+    fn fnv1_128(&self) -> u128 {
+        self.as_bytes().fnv1_128()
+    }
+
+    fn fnv1a_32(&self) -> u32 {
+        self.as_bytes().fnv1a_32()
+    }
+
+    fn fnv1a_64(&self) -> u64 {
+        self.as_bytes().fnv1a_64()
+    }
+
+    fn fnv1a_128(&self) -> u128 {
+        self.as_bytes().fnv1a_128()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use FnvExt;
+
+    #[test]
+    fn foobar_at_each_variant_and_width() {
+        assert_eq!("foobar".fnv0_32(), 0xb74b_b5ef);
+        assert_eq!("foobar".fnv0_64(), 0x0b91_ae3f_7ccd_c5ef);
+        assert_eq!(
+            "foobar".fnv0_128(),
+            0x9438_ff4b_ea00_0000_0001_20ab_5188_d04f
+        );
+        assert_eq!("foobar".fnv1_32(), 0x31f0_b262);
+        assert_eq!("foobar".fnv1_64(), 0x340d_8765_a4dd_a9c2);
+        assert_eq!(
+            "foobar".fnv1_128(),
+            0x7896_bfea_9c3c_64bf_6dc5_8353_d2c2_93aa
+        );
+        assert_eq!("foobar".fnv1a_32(), 0xbf9c_f968);
+        assert_eq!("foobar".fnv1a_64(), 0x8594_4171_f739_67e8);
+        assert_eq!(
+            "foobar".fnv1a_128(),
+            0x343e_1662_793c_64bf_6f0d_3597_ba44_6f18
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn strings_hash_as_their_bytes() {
+        for s in &["", "a", "foobar", "héllo wörld"] {
+            assert_eq!(s.fnv1a_64(), s.as_bytes().fnv1a_64());
+            assert_eq!(s.fnv1_32(), s.as_bytes().fnv1_32());
+            assert_eq!(s.fnv0_128(), s.as_bytes().fnv0_128());
+        }
+
+        let owned = String::from("foobar");
+        let bytes = b"foobar".to_vec();
+        assert_eq!(owned.fnv1a_64(), bytes.fnv1a_64());
+    }
+}
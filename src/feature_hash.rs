@@ -0,0 +1,312 @@
+//! Feature hashing, the hashing trick, for vectorizing sparse features.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::hash::BuildHasher;
+
+use {Fnv1a, FnvHash};
+
+/// An error hashing features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureHashError {
+    /// The number of features was zero.
+    ZeroFeatures,
+    /// The dense vector was shorter than the number of features.
+    VectorTooShort {
+        /// The length of the vector.
+        len: usize,
+        /// The number of features.
+        n_features: usize,
+    },
+}
+
+impl fmt::Display for FeatureHashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FeatureHashError::ZeroFeatures => write!(f, "the number of features is zero"),
+            FeatureHashError::VectorTooShort { len, n_features } => write!(
+                f,
+                "vector of length {} is shorter than the {} features",
+                len, n_features
+            ),
+        }
+    }
+}
+
+impl Error for FeatureHashError {}
+
+fn fnv1a_64(feature: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(feature);
+    hasher.finish()
+}
+
+fn reduce(hash: u64, n_features: usize) -> usize {
+    ((u128::from(hash) * n_features as u128) >> 64) as usize
+}
+
+fn sign(hash: u64) -> i8 {
+    if hash.count_ones() & 1 == 0 {
+        1
+    } else {
+        -1
+    }
+}
+
+/// Returns the index in `0..n_features` of the specified feature.
+///
+/// The feature is hashed with FNV-1a 64 and reduced with the fixed-point
+/// multiply `(hash * n_features) >> 64`, so every index receives a share of
+/// the possible hashes differing by at most one in `2^64 / n_features`.
+///
+/// Distinct features may share an index, and then their weights add. With 8
+/// features, `dog` and `bird` collide:
+///
+/// ```
+/// use lz_fnv::feature_index;
+///
+/// assert_eq!(feature_index(b"dog", 8), Ok(6));
+/// assert_eq!(feature_index(b"bird", 8), Ok(6));
+/// assert_eq!(feature_index(b"cat", 8), Ok(7));
+/// ```
+///
+/// Returns an error if `n_features` is zero.
+pub fn feature_index(feature: &[u8], n_features: usize) -> Result<usize, FeatureHashError> {
+    if n_features == 0 {
+        return Err(FeatureHashError::ZeroFeatures);
+    }
+
+    Ok(reduce(fnv1a_64(feature), n_features))
+}
+
+/// Returns the index in `0..n_features` of the specified feature, as
+/// [`feature_index`], and its sign.
+///
+/// The sign is 1 if the FNV-1a 64 hash has an even number of bits set and -1
+/// if it has an odd number. Multiplying weights by the sign makes collisions
+/// cancel out in expectation rather than accumulate. The parity depends on
+/// the low bits of the hash as well as the high bits selecting the index, and
+/// is better balanced than any single bit of FNV-1a over similar features.
+///
+/// Returns an error if `n_features` is zero.
+///
+/// ```
+/// use lz_fnv::feature_index_signed;
+///
+/// assert_eq!(feature_index_signed(b"cat", 8), Ok((7, -1)));
+/// assert_eq!(feature_index_signed(b"bird", 8), Ok((6, 1)));
+/// ```
+///
+/// [`feature_index`]: fn.feature_index.html
+pub fn feature_index_signed(
+    feature: &[u8],
+    n_features: usize,
+) -> Result<(usize, i8), FeatureHashError> {
+    if n_features == 0 {
+        return Err(FeatureHashError::ZeroFeatures);
+    }
+
+    let hash = fnv1a_64(feature);
+    Ok((reduce(hash, n_features), sign(hash)))
+}
+
+/// A vector which hashed feature weights can be accumulated into.
+///
+/// This is implemented for dense `[f64]` and `Vec<f64>` vectors, and for
+/// sparse `BTreeMap<usize, f64>` and `HashMap<usize, f64>` vectors.
+pub trait FeatureVector {
+    /// Adds `weight` to the element at `index`.
+    fn add(&mut self, index: usize, weight: f64);
+
+    /// Returns the length of a dense vector, or `None` for a sparse one.
+    fn dense_len(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl FeatureVector for [f64] {
+    fn add(&mut self, index: usize, weight: f64) {
+        self[index] += weight;
+    }
+
+    fn dense_len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl FeatureVector for Vec<f64> {
+    fn add(&mut self, index: usize, weight: f64) {
+        self[index] += weight;
+    }
+
+    fn dense_len(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
+impl FeatureVector for BTreeMap<usize, f64> {
+    fn add(&mut self, index: usize, weight: f64) {
+        *self.entry(index).or_insert(0.0) += weight;
+    }
+}
+
+impl<S: BuildHasher> FeatureVector for HashMap<usize, f64, S> {
+    fn add(&mut self, index: usize, weight: f64) {
+        *self.entry(index).or_insert(0.0) += weight;
+    }
+}
+
+/// Accumulates the weight of each feature into `vector` at its index.
+///
+/// When `signed` is set each weight is multiplied by the feature's sign, as
+/// given by [`feature_index_signed`]. Weights of features sharing an index
+/// add.
+///
+/// Returns an error, leaving `vector` unchanged, if `n_features` is zero or
+/// a dense vector is shorter than `n_features`.
+///
+/// ```
+/// use lz_fnv::hash_features;
+/// use std::collections::BTreeMap;
+///
+/// let features: [(&[u8], f64); 3] = [(b"cat", 1.0), (b"dog", 2.0), (b"bird", 0.5)];
+///
+/// let mut dense = vec![0.0; 8];
+/// hash_features(features.iter().cloned(), 8, false, &mut dense).unwrap();
+/// assert_eq!(dense, [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 2.5, 1.0]);
+///
+/// let mut sparse = BTreeMap::new();
+/// hash_features(features.iter().cloned(), 8, true, &mut sparse).unwrap();
+/// assert_eq!(sparse.into_iter().collect::<Vec<_>>(), [(6, -1.5), (7, -1.0)]);
+/// ```
+///
+/// [`feature_index_signed`]: fn.feature_index_signed.html
+pub fn hash_features<'a, I, V>(
+    features: I,
+    n_features: usize,
+    signed: bool,
+    vector: &mut V,
+) -> Result<(), FeatureHashError>
+where
+    I: IntoIterator<Item = (&'a [u8], f64)>,
+    V: FeatureVector + ?Sized,
+{
+    if n_features == 0 {
+        return Err(FeatureHashError::ZeroFeatures);
+    }
+    if let Some(len) = vector.dense_len() {
+        if len < n_features {
+            return Err(FeatureHashError::VectorTooShort { len, n_features });
+        }
+    }
+
+    for (feature, weight) in features {
+        let hash = fnv1a_64(feature);
+        let weight = if signed {
+            weight * f64::from(sign(hash))
+        } else {
+            weight
+        };
+
+        vector.add(reduce(hash, n_features), weight);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use {feature_index, feature_index_signed, hash_features, FeatureHashError};
+
+    fn corpus() -> Vec<Vec<u8>> {
+        (0..20_000)
+            .map(|i| format!("token_{}", i).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn deterministic() {
+        assert_eq!(feature_index(b"mouse", 1 << 20), Ok(54_886));
+        assert_eq!(feature_index(b"horse", 1 << 20), Ok(199_609));
+        assert_eq!(feature_index_signed(b"horse", 8), Ok((1, -1)));
+        assert_eq!(feature_index_signed(b"bird", 8), Ok((6, 1)));
+    }
+
+    #[test]
+    fn indices_are_in_range() {
+        for &n_features in &[1, 3, 10, 1_000, usize::MAX] {
+            for feature in corpus().iter().take(1_000) {
+                assert!(feature_index(feature, n_features).unwrap() < n_features);
+            }
+        }
+    }
+
+    #[test]
+    fn signs_are_balanced() {
+        let positive = corpus()
+            .iter()
+            .filter(|feature| feature_index_signed(feature, 1 << 20).unwrap().1 == 1)
+            .count();
+
+        let fraction = positive as f64 / 20_000.0;
+        assert!(
+            (fraction - 0.5).abs() < 0.02,
+            "positive fraction {}",
+            fraction
+        );
+    }
+
+    #[test]
+    fn indices_match_signed_indices() {
+        for feature in corpus().iter().take(1_000) {
+            assert_eq!(
+                feature_index(feature, 100).unwrap(),
+                feature_index_signed(feature, 100).unwrap().0
+            );
+        }
+    }
+
+    #[test]
+    fn zero_features_is_an_error() {
+        assert_eq!(
+            feature_index(b"cat", 0),
+            Err(FeatureHashError::ZeroFeatures)
+        );
+        assert_eq!(
+            feature_index_signed(b"cat", 0),
+            Err(FeatureHashError::ZeroFeatures)
+        );
+        assert_eq!(
+            hash_features(vec![(&b"cat"[..], 1.0)], 0, false, &mut vec![0.0; 4]),
+            Err(FeatureHashError::ZeroFeatures)
+        );
+    }
+
+    #[test]
+    fn short_dense_vector_is_an_error() {
+        let mut dense = [0.0; 4];
+
+        assert_eq!(
+            hash_features(vec![(&b"cat"[..], 1.0)], 8, false, &mut dense[..]),
+            Err(FeatureHashError::VectorTooShort {
+                len: 4,
+                n_features: 8
+            })
+        );
+        assert_eq!(dense, [0.0; 4]);
+    }
+
+    #[test]
+    fn collisions_accumulate() {
+        let features: Vec<(&[u8], f64)> = vec![(b"cat", 1.0), (b"cow", 3.0), (b"cat", 1.0)];
+
+        let mut sparse = HashMap::new();
+        hash_features(features, 8, false, &mut sparse).unwrap();
+
+        assert_eq!(feature_index(b"cow", 8), Ok(7));
+        assert_eq!(sparse.len(), 1);
+        assert_eq!(sparse[&7], 5.0);
+    }
+}
@@ -0,0 +1,57 @@
+//! Hashing of C strings received over FFI.
+
+use std::ffi::{c_char, CStr};
+
+use FnvExt;
+
+/// Hashes the nul-terminated C string at `ptr` with FNV-1a 64.
+///
+/// The length is found as by `strlen` and the bytes before the nul are hashed,
+/// so the result equals hashing the bytes of `CStr::to_bytes`.
+///
+/// # Safety
+///
+/// `ptr` must be non-null and point to a nul-terminated string which is
+/// valid for reads up to and including its nul, and which is not modified
+/// while it is hashed. The requirements are those of `CStr::from_ptr`.
+///
+/// ```
+/// use lz_fnv::hash_cstr;
+/// use std::ffi::CString;
+///
+/// let name = CString::new("foobar").unwrap();
+///
+/// assert_eq!(unsafe { hash_cstr(name.as_ptr()) }, 0x8594_4171_f739_67e8);
+/// ```
+pub unsafe fn hash_cstr(ptr: *const c_char) -> u64 {
+    debug_assert!(!ptr.is_null(), "hash_cstr called with a null pointer");
+
+    CStr::from_ptr(ptr).to_bytes().fnv1a_64()
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use std::ffi::CString;
+    use {hash_cstr, FnvExt};
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn matches_hashing_the_bytes() {
+        for s in &["", "a", "foobar", "chongo was here!\n", "h\u{e9}llo"] {
+            let c_string = CString::new(*s).unwrap();
+
+            assert_eq!(unsafe { hash_cstr(c_string.as_ptr()) }, s.fnv1a_64());
+        }
+    }
+
+    #[test]
+    fn stops_at_the_first_nul() {
+        let bytes = b"foo\0bar\0";
+
+        assert_eq!(
+            unsafe { hash_cstr(bytes.as_ptr() as *const _) },
+            b"foo".fnv1a_64()
+        );
+    }
+}
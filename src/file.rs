@@ -0,0 +1,399 @@
+//! Hashing of files and readers.
+
+use std::fs::File;
+use std::io::{self, Read};
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use {Fnv1a, FnvHash};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// Writes everything read from `reader` into `hasher`.
+pub(crate) fn write_reader<H: FnvHash, R: Read>(hasher: &mut H, reader: &mut R) -> io::Result<()> {
+    let mut buffer = [0u8; BUFFER_SIZE];
+
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(read) => hasher.write(&buffer[..read]),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Hashes everything read from `reader` with FNV-1a 64.
+pub(crate) fn hash_reader<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut hasher = Fnv1a::<u64>::new();
+    write_reader(&mut hasher, reader)?;
+    Ok(hasher.finish())
+}
+
+/// Hashes the contents of the file at `path` with FNV-1a 64.
+///
+/// The file is streamed through the hasher rather than read into memory.
+///
+/// ```no_run
+/// use lz_fnv::hash_file;
+///
+/// let hash = hash_file("Cargo.toml").unwrap();
+/// ```
+pub fn hash_file<P: AsRef<Path>>(path: P) -> io::Result<u64> {
+    hash_reader(&mut File::open(path)?)
+}
+
+/// Hashes the contents of the file at `path` with FNV-1a 64, reporting
+/// progress as it is read.
+///
+/// As with [`hash_file`] the file is streamed through the hasher. After each
+/// chunk is hashed, `progress` is called with the number of bytes hashed so
+/// far. An empty file calls it once with 0, so it is always called at least
+/// once and its last call is with the length of the file.
+///
+/// ```no_run
+/// use lz_fnv::hash_file_with_progress;
+///
+/// let hash = hash_file_with_progress("Cargo.toml", |bytes| {
+///     eprintln!("hashed {} bytes", bytes);
+/// })
+/// .unwrap();
+/// ```
+///
+/// [`hash_file`]: fn.hash_file.html
+pub fn hash_file_with_progress<P, F>(path: P, progress: F) -> io::Result<u64>
+where
+    P: AsRef<Path>,
+    F: FnMut(u64),
+{
+    let mut hasher = ProgressHasher {
+        hasher: Fnv1a::<u64>::new(),
+        total: 0,
+        progress,
+    };
+    write_reader(&mut hasher, &mut File::open(path)?)?;
+
+    if hasher.total == 0 {
+        (hasher.progress)(0);
+    }
+
+    Ok(hasher.hasher.finish())
+}
+
+/// A hasher reporting the total number of bytes written after each write.
+struct ProgressHasher<F> {
+    hasher: Fnv1a<u64>,
+    total: u64,
+    progress: F,
+}
+
+impl<F: FnMut(u64)> FnvHash for ProgressHasher<F> {
+    type Hash = u64;
+
+    fn finish(&self) -> u64 {
+        self.hasher.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.hasher.write(bytes);
+        self.total += bytes.len() as u64;
+        (self.progress)(self.total);
+    }
+}
+
+/// Hashes the metadata of the file at `path` with FNV-1a 64, as a cheap
+/// signature for detecting changes.
+///
+/// The signature detects a *likely* change to the file without reading it:
+/// a file rewritten with the same size within the filesystem's timestamp
+/// granularity keeps its signature, and a file merely touched changes it.
+/// Only when the signature changes need the contents be hashed, with
+/// [`hash_file`], to tell whether they really differ.
+///
+/// Symbolic links are followed. The signature hashes, with framing which will
+/// not change between versions:
+///
+/// 1. the length of the file in bytes as 8 little-endian bytes,
+/// 2. its modification time as by `FnvHash::write_timestamp`, the whole
+///    seconds since the Unix epoch as 8 and the nanoseconds past them as 4
+///    little-endian bytes, and
+/// 3. on Unix only, its device ID and then its inode number, each as 8
+///    little-endian bytes, so that a file replaced by another is detected.
+///
+/// Signatures are therefore not comparable between Unix and other platforms.
+/// Errors reading the metadata, including a platform not supporting
+/// modification times, are returned.
+///
+/// ```no_run
+/// use std::path::Path;
+/// use lz_fnv::{hash_file, quick_file_signature};
+///
+/// let path = Path::new("Cargo.toml");
+/// let signature = quick_file_signature(path).unwrap();
+///
+/// // Later, only hash the contents again if the signature has changed
+/// if quick_file_signature(path).unwrap() != signature {
+///     let hash = hash_file(path).unwrap();
+/// }
+/// ```
+///
+/// [`hash_file`]: fn.hash_file.html
+pub fn quick_file_signature(path: &Path) -> io::Result<u64> {
+    let metadata = path.metadata()?;
+    let mut hasher = Fnv1a::<u64>::new();
+
+    hasher.write(&metadata.len().to_le_bytes());
+    hasher.write_timestamp(&metadata.modified()?);
+    #[cfg(unix)]
+    {
+        hasher.write(&metadata.dev().to_le_bytes());
+        hasher.write(&metadata.ino().to_le_bytes());
+    }
+
+    Ok(hasher.finish())
+}
+
+/// Streams everything read from `reader` through FNV-1a 64 and returns
+/// whether the hash equals `expected`.
+///
+/// The input is read in fixed-size chunks rather than held in memory, but
+/// FNV cannot reject a mismatch early, so the reader is always read to its
+/// end. An empty reader matches the FNV-1a 64 offset basis. Errors reading,
+/// other than `Interrupted`, are returned.
+///
+/// ```
+/// use lz_fnv::verify_reader;
+/// use std::io::Cursor;
+///
+/// let mut download = Cursor::new(b"foobar");
+///
+/// assert!(verify_reader(&mut download, 0x8594_4171_f739_67e8).unwrap());
+/// ```
+pub fn verify_reader<R: Read>(reader: &mut R, expected: u64) -> io::Result<bool> {
+    Ok(hash_reader(reader)? == expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::io::{self, Cursor, Read};
+    use std::time::{Duration, UNIX_EPOCH};
+    use tempfile::tempdir;
+
+    use super::hash_reader;
+    use {hash_file, hash_file_with_progress, quick_file_signature, verify_reader, Fnv1a, FnvHash};
+
+    fn fnv1a_64(bytes: &[u8]) -> u64 {
+        let mut hasher = Fnv1a::<u64>::new();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    #[test]
+    fn hash_file_matches_hasher() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        let contents: Vec<u8> = (0..50_000u32).map(|i| i as u8).collect();
+        fs::write(&path, &contents).unwrap();
+
+        assert_eq!(hash_file(&path).unwrap(), fnv1a_64(&contents));
+    }
+
+    #[test]
+    fn hash_file_empty() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("empty");
+        fs::write(&path, b"").unwrap();
+
+        assert_eq!(hash_file(&path).unwrap(), 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    fn hash_file_missing() {
+        let dir = tempdir().unwrap();
+
+        assert!(hash_file(dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn hash_file_with_progress_reports_running_totals() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.bin");
+        let contents: Vec<u8> = (0..50_000u32).map(|i| (i * 7) as u8).collect();
+        fs::write(&path, &contents).unwrap();
+
+        let mut reports = Vec::new();
+        let hash = hash_file_with_progress(&path, |bytes| reports.push(bytes)).unwrap();
+
+        assert_eq!(hash, fnv1a_64(&contents));
+        assert!(reports.len() > 1, "{:?}", reports);
+        assert!(reports.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(reports.last(), Some(&50_000));
+    }
+
+    #[test]
+    fn hash_file_with_progress_empty() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("empty");
+        fs::write(&path, b"").unwrap();
+
+        let mut reports = Vec::new();
+        let hash = hash_file_with_progress(&path, |bytes| reports.push(bytes)).unwrap();
+
+        assert_eq!(hash, 0xcbf2_9ce4_8422_2325);
+        assert_eq!(reports, [0]);
+    }
+
+    #[test]
+    fn hash_file_with_progress_missing() {
+        let dir = tempdir().unwrap();
+        let mut called = false;
+
+        assert!(hash_file_with_progress(dir.path().join("missing"), |_| called = true).is_err());
+        assert!(!called);
+    }
+
+    #[test]
+    fn quick_file_signature_is_stable() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.txt");
+        fs::write(&path, b"foobar").unwrap();
+
+        let signature = quick_file_signature(&path).unwrap();
+
+        assert_eq!(quick_file_signature(&path).unwrap(), signature);
+        fs::read(&path).unwrap();
+        assert_eq!(quick_file_signature(&path).unwrap(), signature);
+    }
+
+    #[test]
+    fn quick_file_signature_detects_changes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.txt");
+        fs::write(&path, b"foobar").unwrap();
+        let mtime = UNIX_EPOCH + Duration::new(1_700_000_000, 5);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        let signature = quick_file_signature(&path).unwrap();
+
+        // Same length, different modification time
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime + Duration::from_nanos(1))
+            .unwrap();
+        let touched = quick_file_signature(&path).unwrap();
+        assert_ne!(touched, signature);
+
+        // Different length, same modification time
+        fs::write(&path, b"foobarbaz").unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        assert_ne!(quick_file_signature(&path).unwrap(), signature);
+        assert_ne!(quick_file_signature(&path).unwrap(), touched);
+    }
+
+    #[test]
+    fn quick_file_signature_framing() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.txt");
+        fs::write(&path, b"foobar").unwrap();
+        let mtime = UNIX_EPOCH + Duration::new(1_700_000_000, 5);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&6u64.to_le_bytes());
+        bytes.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        bytes.extend_from_slice(&5u32.to_le_bytes());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            let metadata = fs::metadata(&path).unwrap();
+            bytes.extend_from_slice(&metadata.dev().to_le_bytes());
+            bytes.extend_from_slice(&metadata.ino().to_le_bytes());
+        }
+
+        assert_eq!(quick_file_signature(&path).unwrap(), fnv1a_64(&bytes));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn quick_file_signature_detects_replacement() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.txt");
+        let replacement = dir.path().join("data.txt.new");
+        let mtime = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        fs::write(&path, b"foobar").unwrap();
+        fs::write(&replacement, b"foobaz").unwrap();
+        for file in &[&path, &replacement] {
+            File::options()
+                .write(true)
+                .open(file)
+                .unwrap()
+                .set_modified(mtime)
+                .unwrap();
+        }
+        let signature = quick_file_signature(&path).unwrap();
+
+        fs::rename(&replacement, &path).unwrap();
+
+        assert_ne!(quick_file_signature(&path).unwrap(), signature);
+    }
+
+    #[test]
+    fn quick_file_signature_missing() {
+        let dir = tempdir().unwrap();
+
+        assert!(quick_file_signature(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn hash_reader_foobar() {
+        assert_eq!(
+            hash_reader(&mut Cursor::new(b"foobar")).unwrap(),
+            0x8594_4171_f739_67e8
+        );
+    }
+
+    #[test]
+    fn verify_reader_compares_hash() {
+        assert!(verify_reader(&mut Cursor::new(b"foobar"), 0x8594_4171_f739_67e8).unwrap());
+        assert!(!verify_reader(&mut Cursor::new(b"foobaz"), 0x8594_4171_f739_67e8).unwrap());
+        assert!(!verify_reader(&mut Cursor::new(b"foobar"), 0).unwrap());
+    }
+
+    #[test]
+    fn verify_reader_empty() {
+        assert!(verify_reader(&mut Cursor::new(b""), 0xcbf2_9ce4_8422_2325).unwrap());
+    }
+
+    #[test]
+    fn verify_reader_propagates_errors() {
+        struct Failing;
+
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"))
+            }
+        }
+
+        let error = verify_reader(&mut Failing, 0).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::ConnectionReset);
+    }
+}
@@ -0,0 +1,409 @@
+//! A persistent cache of file content hashes, validated by quick signatures.
+//!
+//! A cache file is a single binary file, all of its integers little-endian:
+//!
+//! 1. the 7 bytes `LZFNVFC` followed by the format version byte `0x01`,
+//! 2. the number of entries as 8 bytes,
+//! 3. for each entry, the length in bytes of its path as 8 bytes, the UTF-8
+//!    bytes of the path, then its quick signature and its content hash as 8
+//!    bytes each, and
+//! 4. the FNV-1a 64 hash of everything before it as 8 bytes.
+//!
+//! The trailing hash detects a truncated or damaged cache file, which is then
+//! discarded as if it were empty.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use {hash_file, quick_file_signature, FnvExt};
+
+const MAGIC: [u8; 8] = *b"LZFNVFC\x01";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    signature: u64,
+    hash: u64,
+}
+
+fn encode(entries: &HashMap<PathBuf, Entry>) -> Vec<u8> {
+    let mut entries: Vec<_> = entries
+        .iter()
+        .filter_map(|(path, entry)| path.to_str().map(|path| (path, entry)))
+        .collect();
+    entries.sort_unstable_by_key(|&(path, _)| path);
+
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+    for (path, entry) in entries {
+        bytes.extend_from_slice(&(path.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(path.as_bytes());
+        bytes.extend_from_slice(&entry.signature.to_le_bytes());
+        bytes.extend_from_slice(&entry.hash.to_le_bytes());
+    }
+
+    let checksum = bytes.fnv1a_64();
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    bytes
+}
+
+/// Splits the next `n` bytes from the front of `bytes`.
+fn take<'a>(bytes: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    if bytes.len() < n {
+        return None;
+    }
+    let (front, rest) = bytes.split_at(n);
+    *bytes = rest;
+    Some(front)
+}
+
+fn take_u64(bytes: &mut &[u8]) -> Option<u64> {
+    let mut buffer = [0u8; 8];
+    buffer.copy_from_slice(take(bytes, 8)?);
+    Some(u64::from_le_bytes(buffer))
+}
+
+/// Decodes a cache file, returning `None` if it is corrupt.
+fn decode(bytes: &[u8]) -> Option<HashMap<PathBuf, Entry>> {
+    let body_len = bytes.len().checked_sub(8)?;
+    let (mut body, mut trailer) = bytes.split_at(body_len);
+    if take_u64(&mut trailer)? != body.fnv1a_64() || take(&mut body, 8)? != MAGIC {
+        return None;
+    }
+
+    let count = take_u64(&mut body)?;
+    let mut entries = HashMap::new();
+    for _ in 0..count {
+        let path_len = take_u64(&mut body)?;
+        let path = take(&mut body, usize::try_from(path_len).ok()?)?;
+        let path = ::std::str::from_utf8(path).ok()?;
+        let signature = take_u64(&mut body)?;
+        let hash = take_u64(&mut body)?;
+
+        entries.insert(PathBuf::from(path), Entry { signature, hash });
+    }
+
+    if body.is_empty() {
+        Some(entries)
+    } else {
+        None
+    }
+}
+
+/// A cache mapping file paths to the FNV-1a 64 hashes of their contents.
+///
+/// Each entry keeps the [`quick_file_signature`] of the file when it was
+/// hashed. [`get_or_compute`] returns the cached hash while the signature
+/// still matches and rehashes the file otherwise, so unchanged files are not
+/// read again. As the signature only detects likely changes, a file rewritten
+/// with the same length and modification time keeps its stale hash.
+///
+/// Paths are keys as given, so `a.txt` and `./a.txt` are cached separately.
+/// The cache is locked internally and can be shared between threads. Paths
+/// which are not valid Unicode are cached but not saved. See the module
+/// documentation for the cache file format.
+///
+/// ```no_run
+/// use std::path::Path;
+/// use lz_fnv::FileHashCache;
+///
+/// let cache = FileHashCache::load(Path::new("target/hashes.cache")).unwrap();
+///
+/// let hash = cache.get_or_compute(Path::new("Cargo.toml")).unwrap();
+///
+/// cache.prune();
+/// cache.save(Path::new("target/hashes.cache")).unwrap();
+/// ```
+///
+/// [`quick_file_signature`]: fn.quick_file_signature.html
+/// [`get_or_compute`]: #method.get_or_compute
+#[derive(Debug, Default)]
+pub struct FileHashCache {
+    entries: Mutex<HashMap<PathBuf, Entry>>,
+}
+
+impl FileHashCache {
+    /// Creates a new, empty `FileHashCache`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a cache saved by [`save`](#method.save) from the file at
+    /// `path`.
+    ///
+    /// A missing or corrupt cache file loads as an empty cache, so a damaged
+    /// cache only costs rehashing. Other errors reading the file are
+    /// returned.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let entries = match fs::read(path) {
+            Ok(bytes) => decode(&bytes).unwrap_or_default(),
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Saves this cache to the file at `path`.
+    ///
+    /// The cache is written to a temporary file beside `path`, which then
+    /// replaces it, so a reader never sees a partly written cache.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let bytes = encode(&self.lock());
+
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        fs::write(&temp, bytes)?;
+        fs::rename(&temp, path)
+    }
+
+    /// Returns the FNV-1a 64 hash of the contents of the file at `path`.
+    ///
+    /// The cached hash is returned if the file's quick signature is unchanged
+    /// since it was hashed. Otherwise the file is hashed as by `hash_file`
+    /// and its entry updated. The signature is read before the contents, so a
+    /// file changed while it is hashed is hashed again by the next call.
+    /// Errors reading the file leave the cache unchanged.
+    pub fn get_or_compute(&self, path: &Path) -> io::Result<u64> {
+        let signature = quick_file_signature(path)?;
+        if let Some(entry) = self.lock().get(path) {
+            if entry.signature == signature {
+                return Ok(entry.hash);
+            }
+        }
+
+        let hash = hash_file(path)?;
+        self.lock()
+            .insert(path.to_owned(), Entry { signature, hash });
+        Ok(hash)
+    }
+
+    /// Removes the entries of files which no longer exist, returning how
+    /// many were removed.
+    pub fn prune(&self) -> usize {
+        let mut entries = self.lock();
+        let before = entries.len();
+        entries.retain(|path, _| path.is_file());
+        before - entries.len()
+    }
+
+    /// Returns the number of cached files.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns whether no files are cached.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathBuf, Entry>> {
+        // The map is never left inconsistent, so a panic elsewhere while it
+        // was locked does not invalidate it
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, UNIX_EPOCH};
+    use tempfile::tempdir;
+
+    use {hash_file, FileHashCache};
+
+    fn set_mtime(path: &Path, secs: u64) {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn computes_content_hash() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.txt");
+        fs::write(&path, b"foobar").unwrap();
+        let cache = FileHashCache::new();
+
+        assert!(cache.is_empty());
+        assert_eq!(cache.get_or_compute(&path).unwrap(), 0x8594_4171_f739_67e8);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn hit_does_not_reread() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.txt");
+        fs::write(&path, b"foobar").unwrap();
+        set_mtime(&path, 1_700_000_000);
+        let cache = FileHashCache::new();
+        let hash = cache.get_or_compute(&path).unwrap();
+
+        // Same length and modification time, so the signature is unchanged
+        fs::write(&path, b"foobaz").unwrap();
+        set_mtime(&path, 1_700_000_000);
+
+        assert_eq!(cache.get_or_compute(&path).unwrap(), hash);
+        assert_ne!(hash_file(&path).unwrap(), hash);
+    }
+
+    #[test]
+    fn modification_invalidates() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data.txt");
+        fs::write(&path, b"foobar").unwrap();
+        set_mtime(&path, 1_700_000_000);
+        let cache = FileHashCache::new();
+        cache.get_or_compute(&path).unwrap();
+
+        fs::write(&path, b"foobaz").unwrap();
+        set_mtime(&path, 1_700_000_001);
+
+        assert_eq!(
+            cache.get_or_compute(&path).unwrap(),
+            hash_file(&path).unwrap()
+        );
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn missing_file_fails() {
+        let dir = tempdir().unwrap();
+        let cache = FileHashCache::new();
+
+        assert!(cache.get_or_compute(&dir.path().join("missing")).is_err());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempdir().unwrap();
+        let cache_path = dir.path().join("hashes.cache");
+        let paths: Vec<_> = (0..3)
+            .map(|i| dir.path().join(format!("{}.txt", i)))
+            .collect();
+        let cache = FileHashCache::new();
+        for (i, path) in paths.iter().enumerate() {
+            fs::write(path, format!("contents {}", i)).unwrap();
+            set_mtime(path, 1_700_000_000);
+            cache.get_or_compute(path).unwrap();
+        }
+        cache.save(&cache_path).unwrap();
+
+        // Change a file without changing its signature, so only a hash
+        // loaded from the cache file can be returned
+        fs::write(&paths[0], b"contents X").unwrap();
+        set_mtime(&paths[0], 1_700_000_000);
+
+        let loaded = FileHashCache::load(&cache_path).unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(
+            loaded.get_or_compute(&paths[0]).unwrap(),
+            cache.get_or_compute(&paths[0]).unwrap()
+        );
+        assert_ne!(
+            loaded.get_or_compute(&paths[0]).unwrap(),
+            hash_file(&paths[0]).unwrap()
+        );
+        assert!(!dir.path().join("hashes.cache.tmp").exists());
+    }
+
+    #[test]
+    fn missing_cache_file_loads_empty() {
+        let dir = tempdir().unwrap();
+
+        let cache = FileHashCache::load(&dir.path().join("missing.cache")).unwrap();
+
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn corrupt_cache_file_loads_empty() {
+        let dir = tempdir().unwrap();
+        let cache_path = dir.path().join("hashes.cache");
+        let path = dir.path().join("data.txt");
+        fs::write(&path, b"foobar").unwrap();
+        let cache = FileHashCache::new();
+        cache.get_or_compute(&path).unwrap();
+        cache.save(&cache_path).unwrap();
+        let saved = fs::read(&cache_path).unwrap();
+
+        let mut flipped = saved.clone();
+        flipped[20] ^= 0x01;
+        let corruptions = [
+            Vec::new(),
+            b"not a cache".to_vec(),
+            saved[..saved.len() - 1].to_vec(),
+            saved[..20].to_vec(),
+            flipped,
+        ];
+
+        for corrupt in &corruptions {
+            fs::write(&cache_path, corrupt).unwrap();
+            let loaded = FileHashCache::load(&cache_path).unwrap();
+
+            assert!(loaded.is_empty());
+            assert_eq!(loaded.get_or_compute(&path).unwrap(), 0x8594_4171_f739_67e8);
+        }
+    }
+
+    #[test]
+    fn prune_removes_missing_files() {
+        let dir = tempdir().unwrap();
+        let kept = dir.path().join("kept.txt");
+        let removed = dir.path().join("removed.txt");
+        fs::write(&kept, b"kept").unwrap();
+        fs::write(&removed, b"removed").unwrap();
+        let cache = FileHashCache::new();
+        cache.get_or_compute(&kept).unwrap();
+        cache.get_or_compute(&removed).unwrap();
+
+        fs::remove_file(&removed).unwrap();
+
+        assert_eq!(cache.prune(), 1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.prune(), 0);
+    }
+
+    #[test]
+    fn shared_between_threads() {
+        let dir = tempdir().unwrap();
+        let paths: Vec<_> = (0..8)
+            .map(|i| dir.path().join(format!("{}.txt", i)))
+            .collect();
+        for (i, path) in paths.iter().enumerate() {
+            fs::write(path, vec![i as u8; 1_000 * i]).unwrap();
+        }
+        let cache = Arc::new(FileHashCache::new());
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                let paths = paths.clone();
+                thread::spawn(move || {
+                    paths
+                        .iter()
+                        .all(|path| cache.get_or_compute(path).unwrap() == hash_file(path).unwrap())
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+        assert_eq!(cache.len(), 8);
+    }
+}
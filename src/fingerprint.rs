@@ -0,0 +1,179 @@
+//! Content fingerprinted file names for cache busting.
+
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use hash_file;
+
+/// The number of hex digits used by [`fingerprint_file`].
+///
+/// [`fingerprint_file`]: fn.fingerprint_file.html
+pub const DEFAULT_FINGERPRINT_LEN: usize = 8;
+
+/// Inserts a hex fingerprint of `contents_hash` into the file name of `path`.
+///
+/// The fingerprint is the first `hex_len` digits of the 16 digit, zero-padded,
+/// lowercase hex representation of `contents_hash`. It is inserted before the
+/// final extension only, so `app.min.js` becomes `app.min.<hash>.js`. A name
+/// without an extension, including a dot file such as `.htaccess`, has the
+/// fingerprint appended as a new extension, `.htaccess.<hash>`.
+///
+/// Only the path is computed; nothing is renamed on disk.
+///
+/// # Panics
+///
+/// Panics if `hex_len` is not within `1..=16` or if `path` has no file name.
+///
+/// ```
+/// use std::path::Path;
+/// use lz_fnv::fingerprint_name;
+///
+/// let name = fingerprint_name(Path::new("static/app.min.js"), 0x8594_4171_f739_67e8, 8);
+///
+/// assert_eq!(name, Path::new("static/app.min.85944171.js"));
+/// ```
+pub fn fingerprint_name(path: &Path, contents_hash: u64, hex_len: usize) -> PathBuf {
+    assert!(
+        (1..=16).contains(&hex_len),
+        "the fingerprint length must be between 1 and 16 hex digits"
+    );
+    let stem = path
+        .file_stem()
+        .expect("the path to fingerprint must have a file name");
+
+    let hex = format!("{:016x}", contents_hash);
+
+    let mut name = OsString::from(stem);
+    name.push(".");
+    name.push(&hex[..hex_len]);
+    if let Some(extension) = path.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+
+    path.with_file_name(name)
+}
+
+/// Hashes the file at `path` and returns its fingerprinted name.
+///
+/// The file is hashed with [`hash_file`] and named by [`fingerprint_name`]
+/// using [`DEFAULT_FINGERPRINT_LEN`] hex digits.
+///
+/// [`hash_file`]: fn.hash_file.html
+/// [`fingerprint_name`]: fn.fingerprint_name.html
+/// [`DEFAULT_FINGERPRINT_LEN`]: constant.DEFAULT_FINGERPRINT_LEN.html
+pub fn fingerprint_file<P: AsRef<Path>>(path: P) -> io::Result<PathBuf> {
+    let path = path.as_ref();
+    let hash = hash_file(path)?;
+
+    Ok(fingerprint_name(path, hash, DEFAULT_FINGERPRINT_LEN))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    use {fingerprint_file, fingerprint_name, hash_file};
+
+    const HASH: u64 = 0x0123_4567_89ab_cdef;
+
+    #[test]
+    fn single_extension() {
+        assert_eq!(
+            fingerprint_name(Path::new("app.js"), HASH, 8),
+            Path::new("app.01234567.js")
+        );
+    }
+
+    #[test]
+    fn multiple_extensions() {
+        assert_eq!(
+            fingerprint_name(Path::new("app.min.js"), HASH, 8),
+            Path::new("app.min.01234567.js")
+        );
+        assert_eq!(
+            fingerprint_name(Path::new("archive.tar.gz"), HASH, 4),
+            Path::new("archive.tar.0123.gz")
+        );
+    }
+
+    #[test]
+    fn no_extension() {
+        assert_eq!(
+            fingerprint_name(Path::new("LICENSE"), HASH, 8),
+            Path::new("LICENSE.01234567")
+        );
+    }
+
+    #[test]
+    fn dot_file() {
+        assert_eq!(
+            fingerprint_name(Path::new(".htaccess"), HASH, 8),
+            Path::new(".htaccess.01234567")
+        );
+    }
+
+    #[test]
+    fn keeps_parent_directory() {
+        assert_eq!(
+            fingerprint_name(Path::new("assets/css/site.css"), HASH, 16),
+            Path::new("assets/css/site.0123456789abcdef.css")
+        );
+    }
+
+    #[test]
+    fn zero_padded_lowercase() {
+        assert_eq!(
+            fingerprint_name(Path::new("a.js"), 0xAB, 16),
+            Path::new("a.00000000000000ab.js")
+        );
+        assert_eq!(
+            fingerprint_name(Path::new("a.js"), 0xABCD_0000_0000_0000, 1),
+            Path::new("a.a.js")
+        );
+    }
+
+    #[test]
+    fn deterministic() {
+        assert_eq!(
+            fingerprint_name(Path::new("app.js"), HASH, 8),
+            fingerprint_name(Path::new("app.js"), HASH, 8)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_length_panics() {
+        fingerprint_name(Path::new("app.js"), HASH, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn overlong_length_panics() {
+        fingerprint_name(Path::new("app.js"), HASH, 17);
+    }
+
+    #[test]
+    fn fingerprint_file_matches_hash_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("app.min.js");
+        fs::write(&path, b"console.log('hello');").unwrap();
+
+        let hex = format!("{:016x}", hash_file(&path).unwrap());
+        let expected = dir.path().join(format!("app.min.{}.js", &hex[..8]));
+
+        assert_eq!(fingerprint_file(&path).unwrap(), expected);
+        assert!(path.exists());
+        assert!(!expected.exists());
+    }
+
+    #[test]
+    fn fingerprint_file_missing() {
+        let dir = tempdir().unwrap();
+
+        assert!(fingerprint_file(dir.path().join("missing.js")).is_err());
+    }
+}
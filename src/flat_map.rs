@@ -0,0 +1,387 @@
+//! A fixed-capacity open-addressing map keyed by FNV-1a 32.
+
+use std::error::Error;
+use std::fmt;
+
+use {Fnv1a, FnvHash};
+
+/// The error returned when inserting a new key into a full `FnvFlatMap`,
+/// holding the rejected key and value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlatMapFullError<K, V> {
+    /// The key which was not inserted.
+    pub key: K,
+    /// The value which was not inserted.
+    pub value: V,
+}
+
+impl<K, V> fmt::Display for FlatMapFullError<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "the FnvFlatMap is full")
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> Error for FlatMapFullError<K, V> {}
+
+/// A map of at most `N` entries stored inline in an array, without
+/// allocating.
+///
+/// Keys are hashed with FNV-1a 32 of their bytes, as given by `AsRef<[u8]>`,
+/// and collisions are resolved by linear probing. Removal shifts the
+/// following entries of a probe run back rather than leaving tombstones, so
+/// lookups never slow down as entries are replaced. The map works without
+/// the `std` feature.
+///
+/// Lookups of a key which is absent probe until an empty slot, so they take
+/// longer as the map fills; keep `N` comfortably larger than the number of
+/// entries where that matters.
+///
+/// ```
+/// use lz_fnv::FnvFlatMap;
+///
+/// let mut devices: FnvFlatMap<&str, u16, 8> = FnvFlatMap::new();
+/// devices.insert("uart0", 0x3f8).unwrap();
+/// devices.insert("uart1", 0x2f8).unwrap();
+///
+/// assert_eq!(devices.get(&"uart1"), Some(&0x2f8));
+/// assert_eq!(devices.remove(&"uart0"), Some(0x3f8));
+/// assert_eq!(devices.len(), 1);
+/// ```
+pub struct FnvFlatMap<K, V, const N: usize> {
+    slots: [Option<(K, V)>; N],
+    len: usize,
+}
+
+impl<K: Eq + AsRef<[u8]>, V, const N: usize> FnvFlatMap<K, V, N> {
+    /// Creates a new, empty `FnvFlatMap`.
+    pub const fn new() -> Self {
+        Self {
+            slots: [const { None }; N],
+            len: 0,
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of entries, `N`.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    fn home(key: &K) -> usize {
+        let mut hasher = Fnv1a::<u32>::new();
+        hasher.write(key.as_ref());
+        hasher.finish() as usize % N
+    }
+
+    fn next(index: usize) -> usize {
+        if index + 1 == N {
+            0
+        } else {
+            index + 1
+        }
+    }
+
+    fn find(&self, key: &K) -> Option<usize> {
+        if N == 0 {
+            return None;
+        }
+
+        let mut index = Self::home(key);
+        for _ in 0..N {
+            match self.slots[index] {
+                None => return None,
+                Some((ref k, _)) if k == key => return Some(index),
+                Some(_) => index = Self::next(index),
+            }
+        }
+
+        None
+    }
+
+    /// Inserts a key and value, returning the key's previous value.
+    ///
+    /// Replacing the value of a key already in the map always succeeds. A new
+    /// key is rejected if the map is full, returning it and the value in the
+    /// error.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, FlatMapFullError<K, V>> {
+        if let Some(index) = self.find(&key) {
+            let slot = self.slots[index].as_mut().expect("found slots are full");
+            return Ok(Some(::std::mem::replace(&mut slot.1, value)));
+        }
+
+        if self.len == N {
+            return Err(FlatMapFullError { key, value });
+        }
+
+        let mut index = Self::home(&key);
+        while self.slots[index].is_some() {
+            index = Self::next(index);
+        }
+
+        self.slots[index] = Some((key, value));
+        self.len += 1;
+        Ok(None)
+    }
+
+    /// Returns a reference to the value of a key.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key)
+            .and_then(|index| self.slots[index].as_ref())
+            .map(|slot| &slot.1)
+    }
+
+    /// Returns a mutable reference to the value of a key.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        match self.find(key) {
+            Some(index) => self.slots[index].as_mut().map(|slot| &mut slot.1),
+            None => None,
+        }
+    }
+
+    /// Returns whether the map contains a key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Removes a key, returning its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.find(key)?;
+        let (_, value) = self.slots[index].take().expect("found slots are full");
+        self.len -= 1;
+
+        // Shift back each following entry of the probe run which may fill the
+        // hole, being one whose home is not between the hole and itself.
+        let mut hole = index;
+        let mut next = Self::next(index);
+        while let Some((ref k, _)) = self.slots[next] {
+            let from_home = (next + N - Self::home(k)) % N;
+            let from_hole = (next + N - hole) % N;
+
+            if from_home >= from_hole {
+                self.slots[hole] = self.slots[next].take();
+                hole = next;
+            }
+            next = Self::next(next);
+        }
+
+        Some(value)
+    }
+
+    /// Removes every entry.
+    pub fn clear(&mut self) {
+        for slot in self.slots.iter_mut() {
+            *slot = None;
+        }
+        self.len = 0;
+    }
+
+    /// Returns an iterator over the entries, in no particular order.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)> + 'a {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.as_ref().map(|entry| (&entry.0, &entry.1)))
+    }
+}
+
+impl<K: Eq + AsRef<[u8]>, V, const N: usize> Default for FnvFlatMap<K, V, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, const N: usize> fmt::Debug for FnvFlatMap<K, V, N>
+where
+    K: Eq + AsRef<[u8]> + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use FnvFlatMap;
+    #[cfg(feature = "std")]
+    use {FlatMapFullError, Fnv1a, FnvHash};
+
+    #[cfg(feature = "std")]
+    fn home(key: &str, n: usize) -> usize {
+        let mut hasher = Fnv1a::<u32>::new();
+        hasher.write(key.as_bytes());
+        hasher.finish() as usize % n
+    }
+
+    /// Returns `count` keys whose home slot in a map of `n` slots is `slot`.
+    #[cfg(feature = "std")]
+    fn colliding(slot: usize, n: usize, count: usize) -> Vec<String> {
+        (0..)
+            .map(|i| format!("key-{}", i))
+            .filter(|key| home(key, n) == slot)
+            .take(count)
+            .collect()
+    }
+
+    #[test]
+    fn insert_get_replace() {
+        let mut map: FnvFlatMap<&str, u32, 4> = FnvFlatMap::new();
+
+        assert!(map.is_empty());
+        assert_eq!(map.insert("a", 1), Ok(None));
+        assert_eq!(map.insert("b", 2), Ok(None));
+        assert_eq!(map.insert("a", 3), Ok(Some(1)));
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get(&"a"), Some(&3));
+        assert_eq!(map.get(&"b"), Some(&2));
+        assert_eq!(map.get(&"c"), None);
+
+        *map.get_mut(&"b").unwrap() += 10;
+        assert_eq!(map.get(&"b"), Some(&12));
+        assert!(map.contains_key(&"a"));
+        assert!(!map.contains_key(&"c"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn fill_to_capacity() {
+        let mut map: FnvFlatMap<String, usize, 16> = FnvFlatMap::new();
+        let keys: Vec<String> = (0..16).map(|i| format!("device-{}", i)).collect();
+
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(map.insert(key.clone(), i), Ok(None));
+        }
+        assert_eq!(map.len(), map.capacity());
+
+        assert_eq!(
+            map.insert("extra".to_string(), 99),
+            Err(FlatMapFullError {
+                key: "extra".to_string(),
+                value: 99
+            })
+        );
+        assert_eq!(map.insert(keys[3].clone(), 33), Ok(Some(3)));
+        assert_eq!(map.get(&"extra".to_string()), None);
+
+        for (i, key) in keys.iter().enumerate() {
+            let expected = if i == 3 { 33 } else { i };
+            assert_eq!(map.get(key), Some(&expected));
+        }
+
+        assert_eq!(map.iter().count(), 16);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn probing_wraps_around() {
+        let keys = colliding(7, 8, 3);
+        let mut map: FnvFlatMap<String, usize, 8> = FnvFlatMap::new();
+
+        for (i, key) in keys.iter().enumerate() {
+            map.insert(key.clone(), i).unwrap();
+        }
+
+        assert_eq!(map.slots[7].as_ref().map(|slot| slot.1), Some(0));
+        assert_eq!(map.slots[0].as_ref().map(|slot| slot.1), Some(1));
+        assert_eq!(map.slots[1].as_ref().map(|slot| slot.1), Some(2));
+        for (i, key) in keys.iter().enumerate() {
+            assert_eq!(map.get(key), Some(&i));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn removal_under_collisions() {
+        let run = colliding(6, 8, 4);
+        let other = colliding(7, 8, 1);
+        let mut map: FnvFlatMap<String, usize, 8> = FnvFlatMap::new();
+
+        for (i, key) in run.iter().chain(&other).enumerate() {
+            map.insert(key.clone(), i).unwrap();
+        }
+
+        assert_eq!(map.remove(&run[0]), Some(0));
+        assert_eq!(map.remove(&run[0]), None);
+        for (i, key) in run.iter().enumerate().skip(1) {
+            assert_eq!(map.get(key), Some(&i));
+        }
+        assert_eq!(map.get(&other[0]), Some(&4));
+
+        assert_eq!(map.remove(&run[2]), Some(2));
+        assert_eq!(map.get(&run[1]), Some(&1));
+        assert_eq!(map.get(&run[3]), Some(&3));
+        assert_eq!(map.get(&other[0]), Some(&4));
+        assert_eq!(map.len(), 3);
+
+        let occupied = map.slots.iter().filter(|slot| slot.is_some()).count();
+        assert_eq!(occupied, 3);
+        assert!(map.slots[6].is_some() && map.slots[7].is_some());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn removal_matches_a_model() {
+        let keys: Vec<String> = (0..24).map(|i| format!("k{}", i)).collect();
+        let mut map: FnvFlatMap<String, usize, 13> = FnvFlatMap::new();
+        let mut model: Vec<(String, usize)> = Vec::new();
+
+        for step in 0..2_000usize {
+            let key = &keys[(step * 7 + step / 5) % keys.len()];
+
+            if step % 3 == 0 {
+                let expected = model
+                    .iter()
+                    .position(|entry| entry.0 == *key)
+                    .map(|i| model.swap_remove(i).1);
+                assert_eq!(map.remove(key), expected);
+            } else {
+                let existing = model.iter_mut().find(|entry| entry.0 == *key);
+                match (existing, map.insert(key.clone(), step)) {
+                    (Some(entry), Ok(previous)) => {
+                        assert_eq!(previous, Some(entry.1));
+                        entry.1 = step;
+                    }
+                    (None, Ok(previous)) => {
+                        assert_eq!(previous, None);
+                        model.push((key.clone(), step));
+                    }
+                    (None, Err(_)) => assert_eq!(model.len(), 13),
+                    (Some(_), Err(_)) => panic!("replacing {} failed", key),
+                }
+            }
+
+            assert_eq!(map.len(), model.len());
+            for entry in &model {
+                assert_eq!(map.get(&entry.0), Some(&entry.1));
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn zero_capacity_and_clear() {
+        let mut empty: FnvFlatMap<&str, (), 0> = FnvFlatMap::default();
+        assert!(empty.insert("a", ()).is_err());
+        assert_eq!(empty.get(&"a"), None);
+        assert_eq!(empty.remove(&"a"), None);
+
+        let mut map: FnvFlatMap<&[u8], u8, 2> = FnvFlatMap::new();
+        map.insert(b"x", 1).unwrap();
+        map.insert(b"y", 2).unwrap();
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.iter().count(), 0);
+        assert_eq!(format!("{:?}", map), "{}");
+        map.insert(b"x", 3).unwrap();
+        assert_eq!(format!("{:?}", map), "{[120]: 3}");
+    }
+}
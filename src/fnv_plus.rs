@@ -0,0 +1,72 @@
+//! Compatibility with the 52-bit hashes of the `fnv-plus` JavaScript package.
+
+use {Fnv1a, FnvHash};
+
+/// Computes the 52-bit FNV-1a hash of `bytes` produced by the `fnv-plus`
+/// JavaScript package.
+///
+/// This is a nonstandard reduction, not part of the FNV specification. As in
+/// `fnv-plus`'s `_hash52_1a_fast` (<https://github.com/tjwebb/fnv-plus>), the
+/// 64-bit FNV-1a hash is truncated to its low 52 bits and its top 12 bits
+/// are XORed into the lowest, so the result fits losslessly in a JavaScript
+/// number. It is the same as `Fnv1a::<u64>::finish_52`.
+///
+/// `fnv-plus` hashes the UTF-16 code units of a string, or its UTF-8 bytes
+/// after `fnvplus.useUTF8(true)`. Hashing the UTF-8 bytes of a string
+/// therefore matches the default mode only for ASCII strings.
+///
+/// ```
+/// use lz_fnv::fnv1a_52;
+///
+/// assert_eq!(fnv1a_52(b"foobar"), 0x4_4171_f739_6fb1);
+/// ```
+pub fn fnv1a_52(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(bytes);
+    hasher.finish_52()
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use std::iter;
+    use {fnv1a_52, Fnv1a, FnvHash};
+
+    // Computed in Node with fnv-plus's `_hash52_1a_fast` limb arithmetic.
+    #[cfg(feature = "std")]
+    #[test]
+    fn matches_fnv_plus() {
+        let repeated: Vec<u8> = iter::repeat_n(b'a', 4096).collect();
+        let pattern: Vec<u8> = (0..10_000).map(|i| (i * 7) as u8).collect();
+
+        let vectors: [(&[u8], u64); 7] = [
+            (b"", 0x2_9ce4_8422_2f9a),
+            (b"a", 0x3_dc4c_8601_e67a),
+            (b"foobar", 0x4_4171_f739_6fb1),
+            (b"chongo was here!\n", 0x1_0940_eff5_fd7d),
+            (b"hello world", 0xa_65e7_023c_d59e),
+            (&repeated, 0x9_fd7b_219d_727f),
+            (&pattern, 0xf_9d00_84f9_fa56),
+        ];
+
+        for &(input, expected) in &vectors {
+            assert_eq!(fnv1a_52(input), expected, "input of {} bytes", input.len());
+        }
+    }
+
+    #[test]
+    fn fits_in_52_bits() {
+        for input in &[&b""[..], b"a", b"\xff\xff\xff\xff"] {
+            assert!(fnv1a_52(input) < 1 << 52);
+        }
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write(b"chongo ");
+        fnv1a.write(b"was here!\n");
+
+        assert_eq!(fnv1a.finish_52(), fnv1a_52(b"chongo was here!\n"));
+    }
+}
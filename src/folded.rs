@@ -0,0 +1,64 @@
+//! Hashes XOR-folded to widths which FNV does not compute directly.
+
+use {Fnv1a, FnvHash};
+
+const MASK_24: u32 = (1 << 24) - 1;
+
+/// Computes the 24-bit FNV-1a hash of `bytes`, such as for a stable RGB
+/// colour.
+///
+/// As the FNV specification recommends for widths without their own prime,
+/// the 32-bit FNV-1a hash is XOR-folded, `(hash >> 24) ^ (hash & 0xff_ffff)`,
+/// so the result is always less than `1 << 24`.
+///
+/// ```
+/// use lz_fnv::fnv1a_24;
+///
+/// let colour = fnv1a_24(b"foobar");
+///
+/// assert_eq!(colour, 0x9c_f9d7);
+/// assert_eq!(format!("#{:06x}", colour), "#9cf9d7");
+/// ```
+pub fn fnv1a_24(bytes: &[u8]) -> u32 {
+    let mut hasher = Fnv1a::<u32>::new();
+    hasher.write(bytes);
+
+    let hash = hasher.finish();
+    (hash >> 24) ^ (hash & MASK_24)
+}
+
+#[cfg(test)]
+mod tests {
+    use {fnv1a_24, Fnv1a, FnvHash};
+
+    #[test]
+    fn known_values() {
+        let vectors: [(&[u8], u32); 4] = [
+            (b"", 0x1c_9d44),
+            (b"a", 0x0c_29c8),
+            (b"foobar", 0x9c_f9d7),
+            (b"user-42", 0x60_73a9),
+        ];
+
+        for &(input, expected) in &vectors {
+            assert_eq!(fnv1a_24(input), expected);
+        }
+    }
+
+    #[test]
+    fn folds_the_32_bit_hash() {
+        let mut fnv1a = Fnv1a::<u32>::new();
+        fnv1a.write(b"foobar");
+
+        assert_eq!(fnv1a.finish(), 0xbf9c_f968);
+        assert_eq!(fnv1a_24(b"foobar"), 0xbf ^ 0x9c_f968);
+    }
+
+    #[test]
+    fn fits_in_24_bits() {
+        for i in 0..10_000u32 {
+            assert!(fnv1a_24(&i.to_le_bytes()) < 1 << 24);
+        }
+        assert!(fnv1a_24(b"\xff\xff\xff\xff") < 1 << 24);
+    }
+}
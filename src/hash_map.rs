@@ -0,0 +1,275 @@
+//! `HashMap` and `HashSet` support using FNV-1a 64.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::BuildHasher;
+
+use {Fnv, Fnv1a, FnvHash, FnvWidth, Variant};
+
+/// A `BuildHasher` creating FNV hashers of type `H`, which is `Fnv1a<u64>`
+/// by default.
+///
+/// The default build hasher starts every hasher at the standard FNV-1a 64
+/// offset basis. A seeded build hasher instead starts every hasher at the
+/// state reached by hashing the seed's 8 little-endian bytes, so maps built
+/// with different seeds place the same keys differently.
+///
+/// Any other FNV hasher may be chosen with `FnvBuildHasher::<H>::default()`.
+/// The 64-bit hashers are built as themselves, while the 32-bit and 128-bit
+/// hashers, which do not implement `std::hash::Hasher`, are built wrapped in
+/// a `StdFnvHasher`.
+///
+/// ```
+/// use std::collections::HashMap;
+/// use lz_fnv::{Fnv1, FnvBuildHasher};
+///
+/// let mut map = HashMap::with_hasher(FnvBuildHasher::<Fnv1<u32>>::default());
+/// map.insert("key", "value");
+///
+/// assert_eq!(map.get("key"), Some(&"value"));
+/// ```
+pub struct FnvBuildHasher<H: FnvHash = Fnv1a<u64>> {
+    key: H::Hash,
+}
+
+impl FnvBuildHasher {
+    /// Creates a new `FnvBuildHasher` which uses the standard offset basis.
+    ///
+    /// ```
+    /// use lz_fnv::FnvBuildHasher;
+    ///
+    /// let build_hasher = FnvBuildHasher::new();
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new `FnvBuildHasher` with the specified seed.
+    ///
+    /// ```
+    /// use lz_fnv::FnvBuildHasher;
+    ///
+    /// let build_hasher = FnvBuildHasher::with_seed(0x5eed);
+    /// ```
+    pub fn with_seed(seed: u64) -> Self {
+        let mut hasher = Fnv1a::<u64>::new();
+        hasher.write(&seed.to_le_bytes());
+
+        Self::from_hasher(&hasher)
+    }
+}
+
+impl<V: Variant, T: FnvWidth> FnvBuildHasher<Fnv<V, T>> {
+    /// Creates a new `FnvBuildHasher` whose hashers all start at the current
+    /// state of `hasher`.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvBuildHasher, FnvHash};
+    ///
+    /// let mut seeded = Fnv1a::<u32>::new();
+    /// seeded.write(b"seed");
+    ///
+    /// let build_hasher = FnvBuildHasher::from_hasher(&seeded);
+    /// assert_eq!(build_hasher.build_fnv_hasher().finish(), seeded.finish());
+    /// ```
+    pub fn from_hasher(hasher: &Fnv<V, T>) -> Self {
+        Self {
+            key: hasher.finish(),
+        }
+    }
+
+    /// Creates a new hasher as `build_hasher`, but unwrapped for the widths
+    /// which `build_hasher` wraps in a `StdFnvHasher`.
+    pub fn build_fnv_hasher(&self) -> Fnv<V, T> {
+        Fnv::with_key(self.key)
+    }
+}
+
+impl<V: Variant, T: FnvWidth> Default for FnvBuildHasher<Fnv<V, T>> {
+    fn default() -> Self {
+        Self::from_hasher(&Fnv::new())
+    }
+}
+
+impl<H: FnvHash> Clone for FnvBuildHasher<H>
+where
+    H::Hash: Copy,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<H: FnvHash> Copy for FnvBuildHasher<H> where H::Hash: Copy {}
+
+impl<H: FnvHash> PartialEq for FnvBuildHasher<H>
+where
+    H::Hash: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl<H: FnvHash> Eq for FnvBuildHasher<H> where H::Hash: Eq {}
+
+impl<H: FnvHash> fmt::Debug for FnvBuildHasher<H>
+where
+    H::Hash: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FnvBuildHasher")
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+impl<V: Variant> BuildHasher for FnvBuildHasher<Fnv<V, u64>> {
+    type Hasher = Fnv<V, u64>;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        self.build_fnv_hasher()
+    }
+}
+
+/// A `HashMap` using FNV-1a 64.
+pub type FnvHashMap<K, V> = HashMap<K, V, FnvBuildHasher>;
+
+/// A `HashSet` using FNV-1a 64.
+///
+/// ```
+/// use lz_fnv::FnvHashSet;
+///
+/// let mut set = FnvHashSet::default();
+/// set.insert("key");
+///
+/// assert!(set.contains("key"));
+/// ```
+pub type FnvHashSet<T> = HashSet<T, FnvBuildHasher>;
+
+/// Creates an empty `FnvHashMap` whose hashers are keyed by `seed`.
+///
+/// ```
+/// use lz_fnv::fnv_hashmap_with_seed;
+///
+/// let mut map = fnv_hashmap_with_seed(0x7e4a_47);
+/// map.insert("key", "value");
+///
+/// assert_eq!(map.get("key"), Some(&"value"));
+/// ```
+pub fn fnv_hashmap_with_seed<K, V>(seed: u64) -> FnvHashMap<K, V> {
+    HashMap::with_hasher(FnvBuildHasher::with_seed(seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::hash::BuildHasher;
+    use {
+        fnv_hashmap_with_seed, Fnv0, Fnv1, Fnv1a, FnvBuildHasher, FnvHash, FnvHashMap, FnvHashSet,
+    };
+
+    const BUCKETS: u64 = 64;
+
+    fn buckets(build_hasher: &FnvBuildHasher, keys: &[&str]) -> Vec<u64> {
+        keys.iter()
+            .map(|key| build_hasher.hash_one(key) & (BUCKETS - 1))
+            .collect()
+    }
+
+    #[test]
+    fn default_uses_offset_basis() {
+        let hasher = FnvBuildHasher::new().build_hasher();
+
+        assert_eq!(::FnvHash::finish(&hasher), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(FnvBuildHasher::new(), FnvBuildHasher::default());
+    }
+
+    #[test]
+    fn generic_over_the_hasher() {
+        let mut map: HashMap<&str, u32, FnvBuildHasher<Fnv1a<u32>>> = HashMap::default();
+        map.insert("alpha", 1);
+        map.insert("beta", 2);
+
+        assert_eq!(map.get("alpha"), Some(&1));
+        assert_eq!(map.get("beta"), Some(&2));
+        assert_eq!(map.get("gamma"), None);
+
+        let mut hasher = FnvBuildHasher::<Fnv1a<u32>>::default().build_hasher();
+        ::std::hash::Hasher::write(&mut hasher, b"foobar");
+        assert_eq!(::std::hash::Hasher::finish(&hasher), 0xbf9c_f968);
+    }
+
+    #[test]
+    fn each_hasher_starts_fresh() {
+        let fnv1 = FnvBuildHasher::<Fnv1<u64>>::default().build_hasher();
+        assert_eq!(FnvHash::finish(&fnv1), Fnv1::<u64>::new().finish());
+
+        let fnv0 = FnvBuildHasher::<Fnv0<u128>>::default();
+        assert_eq!(fnv0.build_fnv_hasher().finish(), 0);
+        assert_eq!(fnv0.build_hasher().into_inner().finish(), 0);
+
+        let mut seeded = Fnv1a::<u64>::new();
+        seeded.write(&7u64.to_le_bytes());
+        assert_eq!(
+            FnvBuildHasher::from_hasher(&seeded),
+            FnvBuildHasher::with_seed(7)
+        );
+    }
+
+    #[test]
+    fn seeds_change_bucket_placement() {
+        let keys = ["alpha", "beta", "gamma", "delta", "epsilon", "zeta"];
+
+        let first = buckets(&FnvBuildHasher::with_seed(1), &keys);
+        let second = buckets(&FnvBuildHasher::with_seed(2), &keys);
+
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn same_seed_same_placement() {
+        let keys = ["alpha", "beta", "gamma"];
+
+        assert_eq!(
+            buckets(&FnvBuildHasher::with_seed(7), &keys),
+            buckets(&FnvBuildHasher::with_seed(7), &keys)
+        );
+    }
+
+    #[test]
+    fn seeded_maps_behave_as_maps() {
+        let mut first = fnv_hashmap_with_seed(1);
+        let mut second: FnvHashMap<_, _> = fnv_hashmap_with_seed(2);
+
+        for i in 0..100 {
+            first.insert(i, i * 2);
+            second.insert(i, i * 2);
+        }
+
+        assert_eq!(first.len(), 100);
+        for i in 0..100 {
+            assert_eq!(first.get(&i), Some(&(i * 2)));
+            assert_eq!(first.get(&i), second.get(&i));
+        }
+    }
+
+    #[test]
+    fn default_collections_use_fnv1a_64() {
+        let mut map: FnvHashMap<&str, u32> = FnvHashMap::default();
+        map.insert("alpha", 1);
+        assert_eq!(map.get("alpha"), Some(&1));
+        assert_eq!(map.hasher(), &FnvBuildHasher::new());
+
+        let mut set: FnvHashSet<&str> = FnvHashSet::default();
+        assert!(set.insert("alpha"));
+        assert!(!set.insert("alpha"));
+        assert!(set.contains("alpha"));
+        assert!(!set.contains("beta"));
+        assert_eq!(set.hasher().hash_one(b"foobar"), {
+            let mut fnv1a = Fnv1a::<u64>::new();
+            ::std::hash::Hash::hash(b"foobar", &mut fnv1a);
+            ::std::hash::Hasher::finish(&fnv1a)
+        });
+    }
+}
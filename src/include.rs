@@ -0,0 +1,111 @@
+//! Hashing of files at compile time.
+
+/// Evaluates to the FNV hash of a file's contents, computed at compile time.
+///
+/// The path is resolved exactly as by `include_bytes!`, relative to the file
+/// containing the invocation, and a missing file is a compile error at the
+/// invocation. With only a path the hash is FNV-1a 64; otherwise the first
+/// argument selects the variant and width as one of `fnv0_32`, `fnv0_64`,
+/// `fnv0_128`, `fnv1_32`, `fnv1_64`, `fnv1_128`, `fnv1a_32`, `fnv1a_64` or
+/// `fnv1a_128`, matching the names of the step functions.
+///
+/// ```
+/// use lz_fnv::{Fnv1a, FnvHash};
+///
+/// const MANIFEST_HASH: u64 = lz_fnv::include_fnv!("../Cargo.toml");
+/// const MANIFEST_HASH_128: u128 = lz_fnv::include_fnv!(fnv1a_128, "../Cargo.toml");
+///
+/// let mut fnv_hasher = Fnv1a::<u64>::new();
+/// fnv_hasher.write(include_bytes!("../Cargo.toml"));
+///
+/// assert_eq!(MANIFEST_HASH, fnv_hasher.finish());
+/// ```
+#[macro_export]
+macro_rules! include_fnv {
+    ($path: expr) => {
+        $crate::include_fnv!(fnv1a_64, $path)
+    };
+    (fnv0_32, $path: expr) => {
+        $crate::include_fnv!(@hash u32, $crate::fnv1_32_advance, 0, $path)
+    };
+    (fnv0_64, $path: expr) => {
+        $crate::include_fnv!(@hash u64, $crate::fnv1_64_advance, 0, $path)
+    };
+    (fnv0_128, $path: expr) => {
+        $crate::include_fnv!(@hash u128, $crate::fnv1_128_advance, 0, $path)
+    };
+    (fnv1_32, $path: expr) => {
+        $crate::include_fnv!(@hash u32, $crate::fnv1_32_advance, $crate::Fnv1::<u32>::new().value(), $path)
+    };
+    (fnv1_64, $path: expr) => {
+        $crate::include_fnv!(@hash u64, $crate::fnv1_64_advance, $crate::Fnv1::<u64>::new().value(), $path)
+    };
+    (fnv1_128, $path: expr) => {
+        $crate::include_fnv!(@hash u128, $crate::fnv1_128_advance, $crate::Fnv1::<u128>::new().value(), $path)
+    };
+    (fnv1a_32, $path: expr) => {
+        $crate::include_fnv!(@hash u32, $crate::fnv1a_32_advance, $crate::Fnv1a::<u32>::new().value(), $path)
+    };
+    (fnv1a_64, $path: expr) => {
+        $crate::include_fnv!(@hash u64, $crate::fnv1a_64_advance, $crate::Fnv1a::<u64>::new().value(), $path)
+    };
+    (fnv1a_128, $path: expr) => {
+        $crate::include_fnv!(@hash u128, $crate::fnv1a_128_advance, $crate::Fnv1a::<u128>::new().value(), $path)
+    };
+    (@hash $type: ty, $advance: path, $basis: expr, $path: expr) => {{
+        const HASH: $type = $advance($basis, include_bytes!($path));
+        HASH
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use {Fnv0, Fnv1, Fnv1a, FnvHash};
+
+    const FIXTURE: &[u8] = include_bytes!("fnv_test_cases.rs");
+
+    const FNV1A_64: u64 = include_fnv!("fnv_test_cases.rs");
+
+    #[test]
+    fn default_is_fnv1a_64() {
+        let mut hasher = Fnv1a::<u64>::new();
+        hasher.write(FIXTURE);
+
+        assert_eq!(FNV1A_64, hasher.finish());
+        assert_eq!(include_fnv!(fnv1a_64, "fnv_test_cases.rs"), hasher.finish());
+    }
+
+    macro_rules! include_fnv_tests {
+        ($($name: ident: $variant: ident, $hasher: ty,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let mut hasher = <$hasher>::new();
+                    hasher.write(FIXTURE);
+
+                    assert_eq!(include_fnv!($variant, "fnv_test_cases.rs"), hasher.finish());
+                }
+            )*
+        };
+    }
+
+    include_fnv_tests! {
+        include_fnv0_32: fnv0_32, Fnv0<u32>,
+        include_fnv0_64: fnv0_64, Fnv0<u64>,
+        include_fnv0_128: fnv0_128, Fnv0<u128>,
+        include_fnv1_32: fnv1_32, Fnv1<u32>,
+        include_fnv1_64: fnv1_64, Fnv1<u64>,
+        include_fnv1_128: fnv1_128, Fnv1<u128>,
+        include_fnv1a_32: fnv1a_32, Fnv1a<u32>,
+        include_fnv1a_64: fnv1a_64, Fnv1a<u64>,
+        include_fnv1a_128: fnv1a_128, Fnv1a<u128>,
+    }
+
+    #[test]
+    fn parent_relative_path() {
+        let mut hasher = Fnv1a::<u128>::new();
+        hasher.write(include_bytes!("../Cargo.toml"));
+
+        assert_eq!(include_fnv!(fnv1a_128, "../Cargo.toml"), hasher.finish());
+    }
+}
@@ -0,0 +1,232 @@
+//! A `BuildHasher` which gathers statistics about the hashes it produces.
+
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use {Fnv1a, FnvBuildHasher, FnvHash};
+
+/// The number of buckets in the histogram of low hash bits.
+pub const HISTOGRAM_BUCKETS: usize = 64;
+
+#[derive(Debug)]
+struct Stats {
+    hashers: AtomicU64,
+    hashes: AtomicU64,
+    bytes: AtomicU64,
+    histogram: [AtomicU64; HISTOGRAM_BUCKETS],
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            hashers: AtomicU64::new(0),
+            hashes: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            histogram: ::std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+/// A snapshot of the statistics gathered by an `InstrumentedFnvBuildHasher`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HashStatsReport {
+    /// The number of hashers created.
+    pub hashers: u64,
+    /// The number of hashes finished.
+    pub hashes: u64,
+    /// The number of bytes written into hashers which were finished.
+    pub bytes: u64,
+    /// The number of finished hashes with each value of their low 6 bits.
+    pub histogram: [u64; HISTOGRAM_BUCKETS],
+    /// The count of the fullest histogram bucket divided by the mean count.
+    ///
+    /// This is 1.0 when the low bits are spread perfectly evenly and
+    /// `HISTOGRAM_BUCKETS` when every hash shares them, or 0.0 when no hashes
+    /// have been finished. Small samples are noisy, so it is most meaningful
+    /// once `hashes` is several times `HISTOGRAM_BUCKETS`.
+    pub imbalance: f64,
+}
+
+/// A `BuildHasher` creating `Fnv1a<u64>` hashers, which counts the hashers
+/// created, the bytes hashed and the distribution of the low bits of each
+/// hash.
+///
+/// The counters are atomics shared between clones, so a clone given to
+/// `HashMap::with_hasher` reports on the map's hashing. Each hash costs a few
+/// relaxed atomic operations, and nothing else is done until `report` is
+/// called. Hashes are recorded when `finish` is called, so a hasher finished
+/// twice is counted twice.
+///
+/// ```
+/// use lz_fnv::InstrumentedFnvBuildHasher;
+/// use std::collections::HashMap;
+///
+/// let build_hasher = InstrumentedFnvBuildHasher::new();
+/// let mut map = HashMap::with_capacity_and_hasher(16, build_hasher.clone());
+/// map.insert("key", "value");
+///
+/// assert_eq!(build_hasher.report().hashers, 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentedFnvBuildHasher {
+    inner: FnvBuildHasher,
+    stats: Arc<Stats>,
+}
+
+impl InstrumentedFnvBuildHasher {
+    /// Creates a new `InstrumentedFnvBuildHasher` wrapping the default
+    /// `FnvBuildHasher`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new `InstrumentedFnvBuildHasher` wrapping the specified
+    /// build hasher, such as a seeded one.
+    pub fn wrap(inner: FnvBuildHasher) -> Self {
+        Self {
+            inner,
+            stats: Arc::default(),
+        }
+    }
+
+    /// Returns a snapshot of the statistics gathered so far by this build
+    /// hasher and its clones.
+    pub fn report(&self) -> HashStatsReport {
+        let mut histogram = [0; HISTOGRAM_BUCKETS];
+        for (count, bucket) in histogram.iter_mut().zip(&self.stats.histogram) {
+            *count = bucket.load(Ordering::Relaxed);
+        }
+
+        let hashes = histogram.iter().sum::<u64>();
+        let max = histogram.iter().cloned().max().unwrap_or(0);
+        let imbalance = if hashes == 0 {
+            0.0
+        } else {
+            max as f64 * HISTOGRAM_BUCKETS as f64 / hashes as f64
+        };
+
+        HashStatsReport {
+            hashers: self.stats.hashers.load(Ordering::Relaxed),
+            hashes: self.stats.hashes.load(Ordering::Relaxed),
+            bytes: self.stats.bytes.load(Ordering::Relaxed),
+            histogram,
+            imbalance,
+        }
+    }
+}
+
+impl BuildHasher for InstrumentedFnvBuildHasher {
+    type Hasher = InstrumentedFnvHasher;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        self.stats.hashers.fetch_add(1, Ordering::Relaxed);
+
+        InstrumentedFnvHasher {
+            inner: self.inner.build_hasher(),
+            bytes: 0,
+            stats: Arc::clone(&self.stats),
+        }
+    }
+}
+
+/// The hasher created by an `InstrumentedFnvBuildHasher`.
+///
+/// This hashes exactly as `Fnv1a<u64>`, recording each hash when finished.
+#[derive(Debug, Clone)]
+pub struct InstrumentedFnvHasher {
+    inner: Fnv1a<u64>,
+    bytes: u64,
+    stats: Arc<Stats>,
+}
+
+impl Hasher for InstrumentedFnvHasher {
+    fn finish(&self) -> u64 {
+        let hash = FnvHash::finish(&self.inner);
+
+        self.stats.hashes.fetch_add(1, Ordering::Relaxed);
+        self.stats.bytes.fetch_add(self.bytes, Ordering::Relaxed);
+        self.stats.histogram[hash as usize % HISTOGRAM_BUCKETS].fetch_add(1, Ordering::Relaxed);
+
+        hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.bytes += bytes.len() as u64;
+        FnvHash::write(&mut self.inner, bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::hash::BuildHasher;
+    use {FnvBuildHasher, InstrumentedFnvBuildHasher, HISTOGRAM_BUCKETS};
+
+    #[test]
+    fn counts_map_operations() {
+        let build_hasher = InstrumentedFnvBuildHasher::new();
+        let mut map = HashMap::with_capacity_and_hasher(64, build_hasher.clone());
+
+        for key in &["a", "bb", "ccc"] {
+            map.insert(*key, key.len());
+        }
+        assert_eq!(map.get("bb"), Some(&2));
+        assert_eq!(map.get("dddd"), None);
+
+        // Hashing a str writes its bytes followed by a 0xff terminator
+        let report = build_hasher.report();
+        assert_eq!(report.hashers, 5);
+        assert_eq!(report.hashes, 5);
+        assert_eq!(report.bytes, 2 + 3 + 4 + 3 + 5);
+        assert_eq!(report.histogram.iter().sum::<u64>(), 5);
+    }
+
+    #[test]
+    fn hashes_match_wrapped_build_hasher() {
+        let inner = FnvBuildHasher::with_seed(7);
+        let instrumented = InstrumentedFnvBuildHasher::wrap(inner);
+
+        assert_eq!(instrumented.hash_one("key"), inner.hash_one("key"));
+        assert_eq!(instrumented.report().hashes, 1);
+    }
+
+    #[test]
+    fn spread_keys_are_balanced() {
+        let build_hasher = InstrumentedFnvBuildHasher::new();
+        for i in 0..64_000u64 {
+            build_hasher.hash_one(i);
+        }
+
+        let report = build_hasher.report();
+        assert_eq!(report.bytes, 64_000 * 8);
+        assert!(report.imbalance < 1.2, "imbalance {}", report.imbalance);
+    }
+
+    #[test]
+    fn flags_skewed_keys() {
+        let search = FnvBuildHasher::new();
+        let skewed: Vec<u64> = (0..)
+            .filter(|key| search.hash_one(key) % HISTOGRAM_BUCKETS as u64 == 0)
+            .take(1_000)
+            .collect();
+
+        let build_hasher = InstrumentedFnvBuildHasher::new();
+        let mut map = HashMap::with_capacity_and_hasher(1_000, build_hasher.clone());
+        for key in skewed {
+            map.insert(key, ());
+        }
+
+        let report = build_hasher.report();
+        assert_eq!(report.histogram[0], 1_000);
+        assert_eq!(report.imbalance, HISTOGRAM_BUCKETS as f64);
+    }
+
+    #[test]
+    fn empty_report() {
+        let report = InstrumentedFnvBuildHasher::new().report();
+
+        assert_eq!(report.hashers, 0);
+        assert_eq!(report.imbalance, 0.0);
+    }
+}
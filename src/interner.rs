@@ -0,0 +1,227 @@
+//! String interning keyed by FNV-1a 64.
+
+use {Fnv1a, FnvHash, FnvHashMap};
+
+/// A handle to a string interned by an [`FnvInterner`].
+///
+/// Symbols are assigned sequentially from zero and remain valid for the
+/// lifetime of the interner which created them.
+///
+/// [`FnvInterner`]: struct.FnvInterner.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Returns the index of this symbol.
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+fn fnv1a_64(string: &str) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(string.as_bytes());
+    hasher.finish()
+}
+
+/// A string interner.
+///
+/// Strings are looked up by their FNV-1a 64 hash and then compared in full,
+/// so distinct strings with colliding hashes still receive distinct symbols.
+/// Interned strings are stored contiguously in a single buffer.
+#[derive(Debug, Clone)]
+pub struct FnvInterner {
+    buffer: String,
+    spans: Vec<(usize, usize)>,
+    buckets: FnvHashMap<u64, Vec<Symbol>>,
+    hash: fn(&str) -> u64,
+}
+
+impl Default for FnvInterner {
+    fn default() -> Self {
+        Self::with_hash(fnv1a_64)
+    }
+}
+
+impl FnvInterner {
+    /// Creates a new, empty `FnvInterner`.
+    ///
+    /// ```
+    /// use lz_fnv::FnvInterner;
+    ///
+    /// let mut interner = FnvInterner::new();
+    /// let symbol = interner.get_or_intern("ident");
+    ///
+    /// assert_eq!(interner.resolve(symbol), Some("ident"));
+    /// assert_eq!(interner.get("ident"), Some(symbol));
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_hash(hash: fn(&str) -> u64) -> Self {
+        Self {
+            buffer: String::new(),
+            spans: Vec::new(),
+            buckets: FnvHashMap::default(),
+            hash,
+        }
+    }
+
+    fn find(&self, hash: u64, string: &str) -> Option<Symbol> {
+        self.buckets.get(&hash).and_then(|symbols| {
+            symbols
+                .iter()
+                .cloned()
+                .find(|&symbol| self.resolve(symbol) == Some(string))
+        })
+    }
+
+    /// Returns the symbol for the specified string, interning it if it has
+    /// not been interned already.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` strings are interned.
+    pub fn get_or_intern(&mut self, string: &str) -> Symbol {
+        let hash = (self.hash)(string);
+        if let Some(symbol) = self.find(hash, string) {
+            return symbol;
+        }
+
+        assert!(
+            self.spans.len() < u32::MAX as usize,
+            "an FnvInterner cannot hold more than u32::MAX strings"
+        );
+        let symbol = Symbol(self.spans.len() as u32);
+
+        let start = self.buffer.len();
+        self.buffer.push_str(string);
+        self.spans.push((start, self.buffer.len()));
+        self.buckets.entry(hash).or_default().push(symbol);
+
+        symbol
+    }
+
+    /// Returns the symbol for the specified string, if it has been interned.
+    pub fn get(&self, string: &str) -> Option<Symbol> {
+        self.find((self.hash)(string), string)
+    }
+
+    /// Returns the string for the specified symbol, if it was created by this
+    /// interner.
+    pub fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        self.spans
+            .get(symbol.0 as usize)
+            .map(|&(start, end)| &self.buffer[start..end])
+    }
+
+    /// Returns the number of interned strings.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns whether no strings have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FnvInterner;
+
+    #[test]
+    fn intern_resolve_round_trip() {
+        let mut interner = FnvInterner::new();
+
+        let foo = interner.get_or_intern("foo");
+        let bar = interner.get_or_intern("bar");
+        let empty = interner.get_or_intern("");
+
+        assert_eq!(interner.resolve(foo), Some("foo"));
+        assert_eq!(interner.resolve(bar), Some("bar"));
+        assert_eq!(interner.resolve(empty), Some(""));
+        assert_eq!(interner.len(), 3);
+    }
+
+    #[test]
+    fn interning_is_idempotent() {
+        let mut interner = FnvInterner::new();
+
+        let first = interner.get_or_intern("ident");
+        let second = interner.get_or_intern("ident");
+
+        assert_eq!(first, second);
+        assert_eq!(interner.len(), 1);
+    }
+
+    #[test]
+    fn get_does_not_intern() {
+        let mut interner = FnvInterner::new();
+        assert!(interner.is_empty());
+        assert_eq!(interner.get("missing"), None);
+
+        let symbol = interner.get_or_intern("present");
+
+        assert_eq!(interner.get("present"), Some(symbol));
+        assert_eq!(interner.get("missing"), None);
+        assert_eq!(interner.len(), 1);
+    }
+
+    #[test]
+    fn symbols_are_sequential() {
+        let mut interner = FnvInterner::new();
+
+        assert_eq!(interner.get_or_intern("a").as_u32(), 0);
+        assert_eq!(interner.get_or_intern("b").as_u32(), 1);
+        assert_eq!(interner.get_or_intern("a").as_u32(), 0);
+    }
+
+    #[test]
+    fn foreign_symbols_do_not_resolve() {
+        let mut larger = FnvInterner::new();
+        larger.get_or_intern("a");
+        let b = larger.get_or_intern("b");
+
+        let mut smaller = FnvInterner::new();
+        smaller.get_or_intern("a");
+
+        assert_eq!(smaller.resolve(b), None);
+    }
+
+    #[test]
+    fn many_symbols() {
+        let mut interner = FnvInterner::new();
+
+        let symbols: Vec<_> = (0..20_000)
+            .map(|i| interner.get_or_intern(&format!("ident_{}", i)))
+            .collect();
+
+        assert_eq!(interner.len(), 20_000);
+        for (i, &symbol) in symbols.iter().enumerate() {
+            let string = format!("ident_{}", i);
+            assert_eq!(interner.resolve(symbol), Some(&string[..]));
+            assert_eq!(interner.get(&string), Some(symbol));
+            assert_eq!(interner.get_or_intern(&string), symbol);
+        }
+        assert_eq!(interner.len(), 20_000);
+    }
+
+    #[test]
+    fn colliding_hashes_stay_distinct() {
+        let mut interner = FnvInterner::with_hash(|_| 42);
+
+        let foo = interner.get_or_intern("foo");
+        let bar = interner.get_or_intern("bar");
+
+        assert_ne!(foo, bar);
+        assert_eq!(interner.resolve(foo), Some("foo"));
+        assert_eq!(interner.resolve(bar), Some("bar"));
+        assert_eq!(interner.get("foo"), Some(foo));
+        assert_eq!(interner.get("bar"), Some(bar));
+        assert_eq!(interner.get("baz"), None);
+        assert_eq!(interner.get_or_intern("foo"), foo);
+        assert_eq!(interner.len(), 2);
+    }
+}
@@ -0,0 +1,194 @@
+//! Deterministic jitter derived from keys, for spreading cache expiries.
+//!
+//! The construction is frozen, so that every replica computes the same
+//! jitter for a key. The key is hashed with FNV-1a 64 and post-mixed with the
+//! MurmurHash3 64-bit finalizer, as FNV-1a alone leaves the high bits of
+//! similar keys, such as sequential IDs, unevenly spread. Offsets are then
+//! reduced from the mixed hash by multiply-shift rather than by remainder,
+//! which would favour the low offsets.
+
+use std::time::Duration;
+
+use unordered::fmix64;
+use {Fnv1a, FnvHash};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+fn mixed_hash(key: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(key);
+    fmix64(hasher.finish())
+}
+
+/// Returns `floor(x * n / 2^64)` without overflowing.
+fn scale(x: u64, n: u128) -> u128 {
+    let x = u128::from(x);
+    x * (n >> 64) + ((x * (n & u128::from(u64::MAX))) >> 64)
+}
+
+/// Returns a fraction in `[0, 1)` derived deterministically from a key.
+///
+/// Equal keys always give equal fractions, and the fractions of distinct
+/// keys are spread uniformly. The fraction is the top 53 bits of the mixed
+/// hash described by [`ttl_with_jitter`], divided by `2^53`.
+///
+/// ```
+/// use lz_fnv::jitter_fraction;
+///
+/// let fraction = jitter_fraction(b"user:42");
+///
+/// assert!(fraction >= 0.0 && fraction < 1.0);
+/// assert_eq!(fraction, jitter_fraction(b"user:42"));
+/// ```
+///
+/// [`ttl_with_jitter`]: fn.ttl_with_jitter.html
+pub fn jitter_fraction(key: &[u8]) -> f64 {
+    (mixed_hash(key) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Returns `base` plus an offset in `[0, spread)` derived deterministically
+/// from a key.
+///
+/// Every replica computes the same time to live for the same key, while the
+/// expiries of different keys are spread evenly across `spread` rather than
+/// all falling at once. The offset is a whole number of nanoseconds, reduced
+/// from the key's mixed FNV-1a 64 hash by multiply-shift:
+///
+/// 1. The key is hashed with FNV-1a 64 and post-mixed with the MurmurHash3
+///    64-bit finalizer, giving `x`.
+/// 2. For a spread of `n` nanoseconds, the offset is `floor(x * n / 2^64)`
+///    nanoseconds, so each possible offset is reached by `floor(2^64 / n)`
+///    or one more of the `2^64` values of `x`.
+///
+/// This construction will not change between versions. A zero spread
+/// returns `base` exactly, and the sum saturates at `Duration::MAX`.
+///
+/// ```
+/// use std::time::Duration;
+/// use lz_fnv::ttl_with_jitter;
+///
+/// let base = Duration::from_secs(300);
+/// let spread = Duration::from_secs(60);
+///
+/// let ttl = ttl_with_jitter(b"user:42", base, spread);
+///
+/// assert_eq!(ttl, Duration::new(335, 983_971_640));
+/// assert_eq!(ttl_with_jitter(b"user:42", base, Duration::ZERO), base);
+/// ```
+pub fn ttl_with_jitter(key: &[u8], base: Duration, spread: Duration) -> Duration {
+    let offset = scale(mixed_hash(key), spread.as_nanos());
+    let offset = Duration::new(
+        (offset / NANOS_PER_SEC) as u64,
+        (offset % NANOS_PER_SEC) as u32,
+    );
+
+    base.saturating_add(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::scale;
+    use {jitter_fraction, ttl_with_jitter};
+
+    const BASE: Duration = Duration::from_secs(300);
+    const SPREAD: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn pinned_vectors() {
+        let vectors: [(&[u8], Duration, f64); 3] = [
+            (b"", Duration::new(356, 206_166_903), 0.93676944839044),
+            (
+                b"user:42",
+                Duration::new(335, 983_971_640),
+                0.5997328606667391,
+            ),
+            (
+                b"session/abc",
+                Duration::new(352, 425_353_035),
+                0.8737558839306282,
+            ),
+        ];
+
+        for &(key, ttl, fraction) in &vectors {
+            assert_eq!(ttl_with_jitter(key, BASE, SPREAD), ttl);
+            assert_eq!(jitter_fraction(key), fraction);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn deterministic() {
+        for i in 0..100 {
+            let key = format!("key-{}", i);
+
+            assert_eq!(
+                ttl_with_jitter(key.as_bytes(), BASE, SPREAD),
+                ttl_with_jitter(key.as_bytes(), BASE, SPREAD)
+            );
+            assert_eq!(
+                jitter_fraction(key.as_bytes()),
+                jitter_fraction(key.as_bytes())
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn bounds() {
+        let spreads = [
+            Duration::from_nanos(1),
+            Duration::from_nanos(7),
+            Duration::from_millis(250),
+            SPREAD,
+            Duration::MAX,
+        ];
+
+        for i in 0..1_000 {
+            let key = format!("key-{}", i);
+            let fraction = jitter_fraction(key.as_bytes());
+            assert!((0.0..1.0).contains(&fraction));
+
+            for &spread in &spreads {
+                let ttl = ttl_with_jitter(key.as_bytes(), BASE, spread);
+                assert!(ttl >= BASE);
+                assert!(ttl - BASE < spread || ttl == Duration::MAX);
+            }
+            assert_eq!(ttl_with_jitter(key.as_bytes(), BASE, Duration::ZERO), BASE);
+            assert_eq!(
+                ttl_with_jitter(key.as_bytes(), BASE, Duration::from_nanos(1)),
+                BASE
+            );
+        }
+
+        assert_eq!(
+            ttl_with_jitter(b"user:42", Duration::MAX, SPREAD),
+            Duration::MAX
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn roughly_uniform() {
+        let mut buckets = [0u32; 10];
+        for i in 0..10_000 {
+            let key = format!("key-{}", i);
+            let offset = ttl_with_jitter(key.as_bytes(), Duration::ZERO, Duration::from_secs(10));
+            buckets[offset.as_secs() as usize] += 1;
+        }
+
+        for &count in &buckets {
+            assert!((900..1_100).contains(&count), "{:?}", buckets);
+        }
+    }
+
+    #[test]
+    fn multiply_shift_is_exact() {
+        assert_eq!(scale(u64::MAX, 1 << 64), u128::from(u64::MAX));
+        assert_eq!(scale(1 << 63, 10), 5);
+        assert_eq!(scale(u64::MAX, 10), 9);
+        assert_eq!(scale(0, u128::MAX), 0);
+        assert_eq!(scale(1 << 63, u128::MAX), u128::MAX >> 1);
+    }
+}
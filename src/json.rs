@@ -0,0 +1,144 @@
+//! Hashing of JSON values independent of their object key order.
+
+use std::io::{self, Write};
+
+use serde_json::Value;
+
+use {Fnv1a, FnvHash};
+
+struct HashingWriter<'a>(&'a mut Fnv1a<u64>);
+
+impl<'a> Write for HashingWriter<'a> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.write(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_canonical(writer: &mut HashingWriter, value: &Value) {
+    match *value {
+        Value::Array(ref values) => {
+            writer.0.write(b"[");
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    writer.0.write(b",");
+                }
+                write_canonical(writer, value);
+            }
+            writer.0.write(b"]");
+        }
+        Value::Object(ref map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+            writer.0.write(b"{");
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    writer.0.write(b",");
+                }
+                serde_json::to_writer(&mut *writer, key).expect("hashing cannot fail");
+                writer.0.write(b":");
+                write_canonical(writer, value);
+            }
+            writer.0.write(b"}");
+        }
+        ref scalar => serde_json::to_writer(writer, scalar).expect("hashing cannot fail"),
+    }
+}
+
+/// Returns the FNV-1a 64 hash of the canonical form of a JSON value, so that
+/// objects differing only in the order of their keys hash equally.
+///
+/// The canonical form is the compact JSON text of the value, as from
+/// `serde_json::to_string`, with the keys of every object, however deeply
+/// nested, sorted by their UTF-8 bytes. It is hashed as it is produced,
+/// without being collected into a `String`. The order of array elements is
+/// significant, and numbers are written as `serde_json` writes them, so `1`
+/// and `1.0` hash differently.
+///
+/// ```
+/// extern crate lz_fnv;
+/// #[macro_use]
+/// extern crate serde_json;
+///
+/// use lz_fnv::hash_json_value;
+///
+/// # fn main() {
+/// let a = json!({ "name": "lz_fnv", "tags": ["hash", "fnv"] });
+/// let b = json!({ "tags": ["hash", "fnv"], "name": "lz_fnv" });
+///
+/// assert_eq!(hash_json_value(&a), hash_json_value(&b));
+/// # }
+/// ```
+pub fn hash_json_value(value: &Value) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    write_canonical(&mut HashingWriter(&mut hasher), value);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, Value};
+
+    use {hash_json_value, FnvExt};
+
+    fn parse(json: &str) -> Value {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn object_key_order_is_ignored() {
+        let a = parse(r#"{"b": 1, "a": {"y": [1, 2], "x": null}, "c": "text"}"#);
+        let b = parse(r#"{"c": "text", "a": {"x": null, "y": [1, 2]}, "b": 1}"#);
+
+        assert_eq!(hash_json_value(&a), hash_json_value(&b));
+    }
+
+    #[test]
+    fn array_order_is_significant() {
+        let a = parse(r#"[1, {"a": 2}, "three"]"#);
+        let b = parse(r#"["three", {"a": 2}, 1]"#);
+
+        assert_ne!(hash_json_value(&a), hash_json_value(&b));
+        assert_ne!(
+            hash_json_value(&parse(r#"{"a": [1, 2]}"#)),
+            hash_json_value(&parse(r#"{"a": [2, 1]}"#))
+        );
+    }
+
+    #[test]
+    fn canonical_form() {
+        let value = parse(
+            r#"{
+                "z": [true, false, null, -1.5, 10],
+                "a": {"q\"uote": "é\n", "": {}},
+                "m": []
+            }"#,
+        );
+        let canonical = r#"{"a":{"":{},"q\"uote":"é\n"},"m":[],"z":[true,false,null,-1.5,10]}"#;
+
+        assert_eq!(hash_json_value(&value), canonical.fnv1a_64());
+        assert_eq!(
+            hash_json_value(&parse("\"foobar\"")),
+            b"\"foobar\"".fnv1a_64()
+        );
+        assert_eq!(hash_json_value(&Value::Null), b"null".fnv1a_64());
+    }
+
+    #[test]
+    fn values_are_hashed() {
+        assert_ne!(
+            hash_json_value(&parse(r#"{"a": 1}"#)),
+            hash_json_value(&parse(r#"{"a": 2}"#))
+        );
+        assert_ne!(
+            hash_json_value(&parse(r#"{"a": 1}"#)),
+            hash_json_value(&parse(r#"{"b": 1}"#))
+        );
+        assert_ne!(hash_json_value(&parse("1")), hash_json_value(&parse("1.0")));
+    }
+}
@@ -0,0 +1,72 @@
+//! Converting finished hashes into fixed-size byte keys.
+
+/// A finished hash which can be converted into a fixed-size key.
+///
+/// The key is the hash's big-endian bytes, so keys sort as their hashes do.
+///
+/// ```
+/// use lz_fnv::{Fnv1a, FnvHash, FnvKeyBytes};
+///
+/// let mut fnv_hasher = Fnv1a::<u32>::new();
+/// fnv_hasher.write(b"foobar");
+///
+/// assert_eq!(fnv_hasher.finish().as_key_bytes(), [0xbf, 0x9c, 0xf9, 0x68]);
+/// ```
+pub trait FnvKeyBytes {
+    /// The byte array type of the key.
+    type Bytes: AsRef<[u8]> + Copy;
+
+    /// Returns the key for this hash.
+    fn as_key_bytes(&self) -> Self::Bytes;
+}
+
+macro_rules! fnv_key_bytes_impl {
+    ($type: ty, $len: expr) => {
+        impl FnvKeyBytes for $type {
+            type Bytes = [u8; $len];
+
+            fn as_key_bytes(&self) -> [u8; $len] {
+                self.to_be_bytes()
+            }
+        }
+    };
+}
+
+fnv_key_bytes_impl!(u32, 4);
+fnv_key_bytes_impl!(u64, 8);
+fnv_key_bytes_impl!(u128, 16);
+
+#[cfg(test)]
+mod tests {
+    use {Fnv1a, FnvHash, FnvKeyBytes};
+
+    #[test]
+    fn big_endian_at_each_width() {
+        let mut fnv1a_32 = Fnv1a::<u32>::new();
+        let mut fnv1a_64 = Fnv1a::<u64>::new();
+        let mut fnv1a_128 = Fnv1a::<u128>::new();
+        fnv1a_32.write(b"foobar");
+        fnv1a_64.write(b"foobar");
+        fnv1a_128.write(b"foobar");
+
+        assert_eq!(fnv1a_32.finish().as_key_bytes(), [0xbf, 0x9c, 0xf9, 0x68]);
+        assert_eq!(
+            fnv1a_64.finish().as_key_bytes(),
+            [0x85, 0x94, 0x41, 0x71, 0xf7, 0x39, 0x67, 0xe8]
+        );
+        assert_eq!(
+            fnv1a_128.finish().as_key_bytes(),
+            [
+                0x34, 0x3e, 0x16, 0x62, 0x79, 0x3c, 0x64, 0xbf, 0x6f, 0x0d, 0x35, 0x97, 0xba, 0x44,
+                0x6f, 0x18
+            ]
+        );
+    }
+
+    #[test]
+    fn keys_sort_as_hashes() {
+        let (low, high) = (0x00ff_u64, 0x0100_u64);
+
+        assert!(low.as_key_bytes() < high.as_key_bytes());
+    }
+}
@@ -0,0 +1,328 @@
+//! Hashing of DNA k-mers.
+
+use std::error::Error;
+use std::fmt;
+
+use {Fnv1a, FnvHash};
+
+/// An error hashing a k-mer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KmerError {
+    /// A byte other than `A`, `C`, `G` or `T`, in either case, was found at
+    /// the specified position of the sequence.
+    InvalidBase {
+        /// The position of the byte in the sequence.
+        position: usize,
+        /// The byte.
+        base: u8,
+    },
+}
+
+impl fmt::Display for KmerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            KmerError::InvalidBase { position, base } => write!(
+                f,
+                "invalid base {:?} at position {}",
+                base as char, position
+            ),
+        }
+    }
+}
+
+impl Error for KmerError {}
+
+fn encode(base: u8) -> Option<u8> {
+    match base {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+/// Packs the 2-bit codes of a k-mer, four to a byte with the first in the
+/// high bits, and hashes them after the k-mer's length.
+fn hash_codes(codes: &[u8], packed: &mut Vec<u8>) -> u64 {
+    packed.clear();
+    for chunk in codes.chunks(4) {
+        let byte = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |byte, (i, &code)| byte | code << (6 - 2 * i));
+        packed.push(byte);
+    }
+
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(&(codes.len() as u64).to_le_bytes());
+    hasher.write(packed);
+    hasher.finish()
+}
+
+/// Hashes the 2-bit codes of a k-mer, or of the lesser of it and its reverse
+/// complement, reusing the specified buffers.
+fn hash_window(codes: &[u8], canonical: bool, buffers: &mut Buffers) -> u64 {
+    if canonical {
+        buffers.reverse.clear();
+        buffers
+            .reverse
+            .extend(codes.iter().rev().map(|code| 3 - code));
+
+        if buffers.reverse[..] < *codes {
+            return hash_codes(&buffers.reverse, &mut buffers.packed);
+        }
+    }
+
+    hash_codes(codes, &mut buffers.packed)
+}
+
+#[derive(Debug, Clone, Default)]
+struct Buffers {
+    codes: Vec<u8>,
+    reverse: Vec<u8>,
+    packed: Vec<u8>,
+}
+
+/// Hashes a DNA k-mer with FNV-1a 64.
+///
+/// Each of `A`, `C`, `G` and `T`, in either case, is encoded as 0 to 3
+/// respectively, and the codes are packed four to a byte, the first base in
+/// the high bits of the first byte, with the last byte padded with zero bits.
+/// The hash is over the number of bases, as 8 little-endian bytes, followed by
+/// the packed bytes. This encoding is frozen.
+///
+/// When `canonical` is set, the lexicographically lesser of the k-mer and its
+/// reverse complement is hashed, so both strands of a sequence hash equally.
+///
+/// Returns an error for any other byte.
+///
+/// ```
+/// use lz_fnv::hash_kmer;
+///
+/// assert_eq!(hash_kmer(b"GATTACA", true), hash_kmer(b"TGTAATC", true));
+/// assert_ne!(hash_kmer(b"GATTACA", false), hash_kmer(b"TGTAATC", false));
+/// ```
+pub fn hash_kmer(seq: &[u8], canonical: bool) -> Result<u64, KmerError> {
+    let mut codes = Vec::with_capacity(seq.len());
+    for (position, &base) in seq.iter().enumerate() {
+        match encode(base) {
+            Some(code) => codes.push(code),
+            None => return Err(KmerError::InvalidBase { position, base }),
+        }
+    }
+
+    Ok(hash_window(&codes, canonical, &mut Buffers::default()))
+}
+
+/// An iterator over the hashes of the k-mers of a sequence.
+///
+/// This is created by [`kmer_hashes`].
+///
+/// [`kmer_hashes`]: fn.kmer_hashes.html
+#[derive(Debug, Clone)]
+pub struct KmerHashes<'a> {
+    seq: &'a [u8],
+    k: usize,
+    canonical: bool,
+    skip_ambiguous: bool,
+    position: usize,
+    buffers: Buffers,
+}
+
+impl<'a> KmerHashes<'a> {
+    /// Sets whether k-mers containing a byte other than `A`, `C`, `G` or `T`
+    /// are skipped, rather than ending the iteration with an error.
+    pub fn skip_ambiguous(mut self, skip_ambiguous: bool) -> Self {
+        self.skip_ambiguous = skip_ambiguous;
+        self
+    }
+}
+
+impl<'a> Iterator for KmerHashes<'a> {
+    type Item = Result<u64, KmerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        'windows: while self.position + self.k <= self.seq.len() {
+            let window = &self.seq[self.position..self.position + self.k];
+
+            self.buffers.codes.clear();
+            for (offset, &base) in window.iter().enumerate() {
+                match encode(base) {
+                    Some(code) => self.buffers.codes.push(code),
+                    None if self.skip_ambiguous => {
+                        self.position += offset + 1;
+                        continue 'windows;
+                    }
+                    None => {
+                        let position = self.position + offset;
+                        self.position = self.seq.len();
+                        return Some(Err(KmerError::InvalidBase { position, base }));
+                    }
+                }
+            }
+
+            self.position += 1;
+            let codes = ::std::mem::take(&mut self.buffers.codes);
+            let hash = hash_window(&codes, self.canonical, &mut self.buffers);
+            self.buffers.codes = codes;
+
+            return Some(Ok(hash));
+        }
+
+        None
+    }
+}
+
+/// Returns an iterator over the hashes, as computed by [`hash_kmer`], of each
+/// k-mer of `seq` in turn.
+///
+/// By default, a k-mer containing a byte other than `A`, `C`, `G` or `T` ends
+/// the iteration with an error; `skip_ambiguous(true)` skips such k-mers
+/// instead. If `k` is greater than the length of the sequence the iterator is
+/// empty.
+///
+/// # Panics
+///
+/// Panics if `k` is 0.
+///
+/// ```
+/// use lz_fnv::{hash_kmer, kmer_hashes};
+///
+/// let hashes: Vec<_> = kmer_hashes(b"ACGNTA", 2, false)
+///     .skip_ambiguous(true)
+///     .collect();
+///
+/// assert_eq!(
+///     hashes,
+///     vec![hash_kmer(b"AC", false), hash_kmer(b"CG", false), hash_kmer(b"TA", false)]
+/// );
+/// ```
+///
+/// [`hash_kmer`]: fn.hash_kmer.html
+pub fn kmer_hashes(seq: &[u8], k: usize, canonical: bool) -> KmerHashes<'_> {
+    assert!(k != 0, "k-mer length must be non-zero");
+
+    KmerHashes {
+        seq,
+        k,
+        canonical,
+        skip_ambiguous: false,
+        position: 0,
+        buffers: Buffers::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {hash_kmer, kmer_hashes, KmerError};
+
+    #[test]
+    fn pinned_vectors() {
+        assert_eq!(hash_kmer(b"A", false), Ok(0x529a_2cdc_8ff5_33ac));
+        assert_eq!(hash_kmer(b"T", false), Ok(0x5299_ecdc_8ff4_c6ec));
+        assert_eq!(hash_kmer(b"ACGT", false), Ok(0x985b_45c3_d224_7bee));
+        assert_eq!(hash_kmer(b"GATTACA", false), Ok(0x21bb_f6c1_9897_c4c5));
+        assert_eq!(hash_kmer(b"TGTAATC", false), Ok(0x2079_60c1_9785_ec1a));
+        assert_eq!(hash_kmer(b"ACGTACGTA", false), Ok(0x7cc7_f9a5_21a4_961e));
+    }
+
+    #[test]
+    fn reverse_complements_hash_equal_when_canonical() {
+        let pairs: [(&[u8], &[u8]); 4] = [
+            (b"A", b"T"),
+            (b"GATTACA", b"TGTAATC"),
+            (b"AACCGGTTA", b"TAACCGGTT"),
+            (b"acgg", b"CCGT"),
+        ];
+
+        for &(kmer, reverse) in &pairs {
+            assert_eq!(hash_kmer(kmer, true), hash_kmer(reverse, true));
+        }
+        assert_eq!(hash_kmer(b"T", true), Ok(0x529a_2cdc_8ff5_33ac));
+    }
+
+    #[test]
+    fn case_is_ignored() {
+        assert_eq!(hash_kmer(b"gattaca", false), hash_kmer(b"GATTACA", false));
+    }
+
+    #[test]
+    fn length_is_hashed() {
+        assert_ne!(hash_kmer(b"A", false), hash_kmer(b"AA", false));
+        assert_ne!(hash_kmer(b"ACG", false), hash_kmer(b"ACGA", false));
+    }
+
+    #[test]
+    fn ambiguous_bases_are_rejected() {
+        assert_eq!(
+            hash_kmer(b"ACNT", false),
+            Err(KmerError::InvalidBase {
+                position: 2,
+                base: b'N'
+            })
+        );
+        assert_eq!(
+            KmerError::InvalidBase {
+                position: 2,
+                base: b'N'
+            }
+            .to_string(),
+            "invalid base 'N' at position 2"
+        );
+
+        let hashes: Vec<_> = kmer_hashes(b"ACGTNACGT", 3, false).collect();
+        assert_eq!(
+            hashes,
+            vec![
+                hash_kmer(b"ACG", false),
+                hash_kmer(b"CGT", false),
+                Err(KmerError::InvalidBase {
+                    position: 4,
+                    base: b'N'
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn ambiguous_bases_are_skipped() {
+        let hashes: Vec<_> = kmer_hashes(b"ACGTNACGTRA", 3, true)
+            .skip_ambiguous(true)
+            .collect();
+
+        assert_eq!(
+            hashes,
+            vec![
+                hash_kmer(b"ACG", true),
+                hash_kmer(b"CGT", true),
+                hash_kmer(b"ACG", true),
+                hash_kmer(b"CGT", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn windows_match_hash_kmer() {
+        let seq = b"GATTACAGATTACA";
+        let hashes: Vec<_> = kmer_hashes(seq, 5, true).collect();
+
+        assert_eq!(hashes.len(), seq.len() - 4);
+        for (window, hash) in seq.windows(5).zip(hashes) {
+            assert_eq!(hash, hash_kmer(window, true));
+        }
+    }
+
+    #[test]
+    fn k_longer_than_sequence_is_empty() {
+        assert_eq!(kmer_hashes(b"ACGT", 5, false).count(), 0);
+        assert_eq!(kmer_hashes(b"", 1, true).count(), 0);
+        assert_eq!(kmer_hashes(b"ACGT", 4, false).count(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "k-mer length must be non-zero")]
+    fn k_of_zero_panics() {
+        kmer_hashes(b"ACGT", 0, false);
+    }
+}
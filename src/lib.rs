@@ -4,13 +4,60 @@
 //! width integers.
 //!
 //! The FNV implementations for u64 also implement `Hasher`.
+//!
+//! Support for files, maps and other types from the standard library is
+//! behind the default `std` feature. Without it the crate is `no_std`.
 #![deny(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate core as std;
+
+#[cfg(feature = "bytemuck")]
+extern crate bytemuck;
+#[cfg(test)]
+extern crate bytes;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "futures")]
+extern crate futures;
+#[cfg(all(
+    feature = "std",
+    any(target_os = "linux", target_os = "android", target_os = "freebsd")
+))]
+extern crate libc;
+#[cfg(test)]
+extern crate proptest;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "rusqlite")]
+extern crate rusqlite;
+#[cfg(feature = "rust_decimal")]
+extern crate rust_decimal;
+#[cfg(feature = "serde_json")]
+extern crate serde_json;
+#[cfg(feature = "tar")]
+extern crate tar;
+#[cfg(test)]
+extern crate tempfile;
+#[cfg(feature = "time")]
+extern crate time;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "unicode-normalization")]
+extern crate unicode_normalization;
+#[cfg(all(unix, feature = "xattr"))]
+extern crate xattr;
+
+use std::fmt;
+use std::marker::PhantomData;
 
 /// A trait for all Fowler-Noll-Vo hash implementations.
 ///
 /// This matches the `std::hash::Hasher` definition but for multiple hash
-/// types.
-pub trait FnvHasher {
+/// types. It was formerly named `FnvHasher`, which remains as a deprecated
+/// alias.
+pub trait FnvHash {
     /// The type of the hash.
     type Hash;
 
@@ -19,232 +66,1006 @@ pub trait FnvHasher {
 
     /// Writes some data into this Hasher.
     fn write(&mut self, bytes: &[u8]);
-}
 
-/// The FNV-0 hash.
-///
-/// This is deprecated except for computing the FNV offset basis for FNV-1 and
-/// FNV-1a hashes.
-#[derive(Debug, Default)]
-pub struct Fnv0<T> {
-    hash: T,
-}
+    /// Writes an unsigned LEB128 (protobuf varint) encoding of `value` into
+    /// this Hasher.
+    ///
+    /// Each byte holds seven bits of the value, least significant group
+    /// first, with the high bit set on every byte but the last. Zero encodes
+    /// as a single byte and `u64::MAX` as ten bytes.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut varint = Fnv1a::<u64>::new();
+    /// varint.write_varint(300);
+    ///
+    /// let mut bytes = Fnv1a::<u64>::new();
+    /// bytes.write(&[0xAC, 0x02]);
+    ///
+    /// assert_eq!(varint.finish(), bytes.finish());
+    /// ```
+    fn write_varint(&mut self, value: u64) {
+        let mut buffer = [0u8; 10];
+        let mut value = value;
+        let mut len = 0;
 
-/// The FNV-1 hash.
-#[derive(Debug)]
-pub struct Fnv1<T> {
-    hash: T,
-}
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
 
-/// The FNV-1a hash.
-#[derive(Debug)]
-pub struct Fnv1a<T> {
-    hash: T,
-}
+            if value == 0 {
+                buffer[len] = byte;
+                len += 1;
+                break;
+            }
+
+            buffer[len] = byte | 0x80;
+            len += 1;
+        }
 
-impl<T: Default> Fnv0<T> {
-    /// Creates a new `Fnv0<T>`.
+        self.write(&buffer[..len]);
+    }
+
+    /// Writes at most `*remaining` bytes of `bytes` into this Hasher,
+    /// decrementing `*remaining` by the number written.
+    ///
+    /// Sharing one counter across several writes enforces a total budget:
+    /// bytes beyond it are ignored and do not affect the hash. Returns the
+    /// number of bytes written.
     ///
     /// ```
-    /// use lz_fnv::Fnv0;
+    /// use lz_fnv::{Fnv1a, FnvHash};
     ///
-    /// let fnv_hasher = Fnv0::<u32>::new();
+    /// let mut budget = 4;
+    /// let mut capped = Fnv1a::<u64>::new();
+    /// capped.write_capped(b"foo", &mut budget);
+    /// capped.write_capped(b"bar", &mut budget);
+    ///
+    /// let mut expected = Fnv1a::<u64>::new();
+    /// expected.write(b"foob");
+    ///
+    /// assert_eq!(capped.finish(), expected.finish());
+    /// assert_eq!(budget, 0);
     /// ```
-    pub fn new() -> Self {
-        Self::default()
+    fn write_capped(&mut self, bytes: &[u8], remaining: &mut usize) -> usize {
+        let len = bytes.len().min(*remaining);
+        self.write(&bytes[..len]);
+        *remaining -= len;
+        len
     }
-}
 
-impl<T> Fnv0<T> {
-    /// Creates a new `Fnv0<T>` with the specified key.
+    /// Writes `buf` into this Hasher, exactly as `write`.
+    ///
+    /// This names the zero-copy streaming pattern, in which data is hashed
+    /// straight from a buffer that is then refilled in place, such as a ring
+    /// buffer's readable region. Hashing only reads the bytes, so a buffer
+    /// held as `&mut [u8]` can be passed by reborrowing it. See [`hash_ring`]
+    /// for a driver of the pattern.
     ///
     /// ```
-    /// use lz_fnv::Fnv0;
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut ring = *b"foo...";
+    /// let readable: &mut [u8] = &mut ring[..3];
+    ///
+    /// let mut hasher = Fnv1a::<u64>::new();
+    /// hasher.write_in_place(readable);
+    /// readable.copy_from_slice(b"bar");
+    /// hasher.write_in_place(readable);
     ///
-    /// let fnv_hasher = Fnv0::with_key(872u32);
+    /// let mut expected = Fnv1a::<u64>::new();
+    /// expected.write(b"foobar");
+    ///
+    /// assert_eq!(hasher.finish(), expected.finish());
     /// ```
-    pub fn with_key(key: T) -> Self {
-        Self { hash: key }
+    ///
+    /// [`hash_ring`]: fn.hash_ring.html
+    fn write_in_place(&mut self, buf: &[u8]) {
+        self.write(buf);
     }
-}
 
-impl<T> Fnv1<T> {
-    /// Creates a new `Fnv1<T>` with the specified key.
+    /// Writes `bytes[range]` into this Hasher.
+    ///
+    /// An empty range writes nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range's start is greater than its end, or its end is
+    /// greater than the length of `bytes`.
     ///
     /// ```
-    /// use lz_fnv::Fnv1;
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut ranged = Fnv1a::<u64>::new();
+    /// ranged.write_range(b"foobarbaz", 3..6);
     ///
-    /// let fnv_hasher = Fnv1::with_key(872u32);
+    /// let mut sliced = Fnv1a::<u64>::new();
+    /// sliced.write(b"bar");
+    ///
+    /// assert_eq!(ranged.finish(), sliced.finish());
     /// ```
-    pub fn with_key(key: T) -> Self {
-        Self { hash: key }
+    fn write_range(&mut self, bytes: &[u8], range: ::std::ops::Range<usize>) {
+        assert!(
+            range.start <= range.end,
+            "write_range start {} is greater than end {}",
+            range.start,
+            range.end
+        );
+        assert!(
+            range.end <= bytes.len(),
+            "write_range end {} is out of range for {} bytes",
+            range.end,
+            bytes.len()
+        );
+
+        self.write(&bytes[range]);
     }
-}
 
-impl<T> Fnv1a<T> {
-    /// Creates a new `Fnv1a<T>` with the specified key.
+    /// Writes `bytes` into this Hasher without any trailing ASCII whitespace.
+    ///
+    /// Whitespace is as `u8::is_ascii_whitespace`. Bytes which are entirely
+    /// whitespace write nothing, leaving the hash of the empty input.
     ///
     /// ```
-    /// use lz_fnv::Fnv1a;
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut trimmed = Fnv1a::<u64>::new();
+    /// trimmed.write_trimmed_end(b"value = 1 \t\r\n");
+    ///
+    /// let mut bytes = Fnv1a::<u64>::new();
+    /// bytes.write(b"value = 1");
     ///
-    /// let fnv_hasher = Fnv1a::with_key(872u32);
+    /// assert_eq!(trimmed.finish(), bytes.finish());
     /// ```
-    pub fn with_key(key: T) -> Self {
-        Self { hash: key }
+    fn write_trimmed_end(&mut self, bytes: &[u8]) {
+        self.write(bytes.trim_ascii_end());
     }
-}
 
-macro_rules! fnv0_impl {
-    ($type: ty, $prime: expr, $from_byte: ident) => {
-        impl FnvHasher for Fnv0<$type> {
-            type Hash = $type;
+    /// Writes `bytes` into this Hasher without any leading or trailing bytes
+    /// which are in `trim`.
+    ///
+    /// Only the ends are stripped, so trim bytes within the rest are hashed.
+    /// Bytes which are entirely trim bytes write nothing, leaving the hash of
+    /// the empty input, and an empty `trim` writes `bytes` unchanged.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut trimmed = Fnv1a::<u64>::new();
+    /// trimmed.write_trimmed(b"[\"value\"]", b"[]\"");
+    ///
+    /// let mut bytes = Fnv1a::<u64>::new();
+    /// bytes.write(b"value");
+    ///
+    /// assert_eq!(trimmed.finish(), bytes.finish());
+    /// ```
+    fn write_trimmed(&mut self, bytes: &[u8], trim: &[u8]) {
+        let start = bytes
+            .iter()
+            .position(|byte| !trim.contains(byte))
+            .unwrap_or(bytes.len());
+        let end = bytes
+            .iter()
+            .rposition(|byte| !trim.contains(byte))
+            .map_or(start, |last| last + 1);
+
+        self.write(&bytes[start..end]);
+    }
 
-            fn finish(&self) -> Self::Hash {
-                self.hash
-            }
+    /// Writes each of `strings` into this Hasher followed by a nul byte, as C
+    /// hashes an array of nul-terminated strings.
+    ///
+    /// Nul bytes within an element are hashed as-is, so `["a\0b"]` and
+    /// `["a", "b"]` hash equally.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut array = Fnv1a::<u64>::new();
+    /// array.write_cstr_array(&[b"foo", b"bar"]);
+    ///
+    /// let mut bytes = Fnv1a::<u64>::new();
+    /// bytes.write(b"foo\0bar\0");
+    ///
+    /// assert_eq!(array.finish(), bytes.finish());
+    /// ```
+    fn write_cstr_array(&mut self, strings: &[&[u8]]) {
+        for string in strings {
+            self.write(string);
+            self.write(&[0]);
+        }
+    }
 
-            fn write(&mut self, bytes: &[u8]) {
-                let mut hash = self.hash;
+    /// Writes the UTF-8 encoding of each of `chars` into this Hasher, without
+    /// collecting them into a `String`.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut chars = Fnv1a::<u64>::new();
+    /// chars.write_chars("FooBar".chars().map(|c| c.to_ascii_lowercase()));
+    ///
+    /// let mut bytes = Fnv1a::<u64>::new();
+    /// bytes.write(b"foobar");
+    ///
+    /// assert_eq!(chars.finish(), bytes.finish());
+    /// ```
+    fn write_chars<I: IntoIterator<Item = char>>(&mut self, chars: I) {
+        let mut buffer = [0u8; 4];
+        for c in chars {
+            self.write(c.encode_utf8(&mut buffer).as_bytes());
+        }
+    }
 
-                for byte in bytes {
-                    hash = hash.wrapping_mul($prime);
-                    hash ^= ($from_byte)(*byte);
-                }
+    /// Writes the UTF-8 encoding of the NFC normalization of `s` into this
+    /// Hasher, so that canonically equivalent strings hash equally.
+    ///
+    /// NFC composes characters wherever Unicode defines a precomposed form,
+    /// so `"e\u{301}"`, an `e` followed by a combining acute accent, hashes
+    /// equally to `"\u{e9}"`, a precomposed `é`. Strings which are only
+    /// compatibly equivalent, such as `"ﬁ"` and `"fi"`, still hash
+    /// differently. The normalization is streamed into the hasher without
+    /// collecting a `String`.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut decomposed = Fnv1a::<u64>::new();
+    /// decomposed.write_nfc("caf\u{65}\u{301}");
+    ///
+    /// let mut bytes = Fnv1a::<u64>::new();
+    /// bytes.write("caf\u{e9}".as_bytes());
+    ///
+    /// assert_eq!(decomposed.finish(), bytes.finish());
+    /// ```
+    #[cfg(feature = "unicode-normalization")]
+    fn write_nfc(&mut self, s: &str) {
+        use unicode_normalization::UnicodeNormalization;
 
-                self.hash = hash;
+        self.write_chars(s.nfc());
+    }
+
+    /// Writes an instant into this Hasher, so that equal instants hash equally
+    /// whatever their offset from UTC or precision.
+    ///
+    /// The instant is written as 12 bytes, which will not change between
+    /// versions: the whole seconds since the Unix epoch, rounded towards
+    /// negative infinity, as an `i64`, then the nanoseconds past them, from 0
+    /// to 999,999,999, as a `u32`, both little-endian. Instants from before
+    /// 1970 to after the year 9999 are supported. See [`FnvTimestamp`] for
+    /// the supported types.
+    ///
+    /// ```
+    /// # #[cfg(feature = "std")] {
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut timestamp = Fnv1a::<u64>::new();
+    /// timestamp.write_timestamp(&(UNIX_EPOCH - Duration::from_millis(500)));
+    ///
+    /// let mut bytes = Fnv1a::<u64>::new();
+    /// bytes.write(&(-1i64).to_le_bytes());
+    /// bytes.write(&500_000_000u32.to_le_bytes());
+    ///
+    /// assert_eq!(timestamp.finish(), bytes.finish());
+    /// # }
+    /// ```
+    ///
+    /// [`FnvTimestamp`]: trait.FnvTimestamp.html
+    fn write_timestamp<T: FnvTimestamp + ?Sized>(&mut self, timestamp: &T) {
+        self.write(&timestamp::canonical_bytes(timestamp));
+    }
+
+    /// Writes a socket address into this Hasher, so that IPv4 and IPv6
+    /// addresses never hash equally.
+    ///
+    /// The address is written as a family byte, `4` or `6`, then its 4 or 16
+    /// octets, then the port as 2 big-endian bytes. The flow information and
+    /// scope ID of an IPv6 address are not written. An IPv4-mapped IPv6
+    /// address such as `[::ffff:192.0.2.1]:80` is written as IPv6, so it
+    /// does not collide with `192.0.2.1:80`. To hash them equally, convert the
+    /// IP address with `IpAddr::to_canonical` first.
+    ///
+    /// ```
+    /// use std::net::SocketAddr;
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let addr: SocketAddr = "192.0.2.1:80".parse().unwrap();
+    ///
+    /// let mut socket = Fnv1a::<u64>::new();
+    /// socket.write_socket_addr(&addr);
+    ///
+    /// let mut bytes = Fnv1a::<u64>::new();
+    /// bytes.write(&[4, 192, 0, 2, 1, 0, 80]);
+    ///
+    /// assert_eq!(socket.finish(), bytes.finish());
+    /// ```
+    fn write_socket_addr(&mut self, addr: &::std::net::SocketAddr) {
+        match *addr {
+            ::std::net::SocketAddr::V4(ref addr) => {
+                self.write(&[4]);
+                self.write(&addr.ip().octets());
+            }
+            ::std::net::SocketAddr::V6(ref addr) => {
+                self.write(&[6]);
+                self.write(&addr.ip().octets());
             }
         }
-    };
+        self.write(&addr.port().to_be_bytes());
+    }
+
+    /// Writes the bits of `value` into this Hasher as 4 little-endian bytes,
+    /// so that equal floats hash equally.
+    ///
+    /// Every NaN is written as the bits of `f32::NAN`, and `-0.0` as `+0.0`.
+    /// Infinities and all other values are written unchanged.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut positive = Fnv1a::<u64>::new();
+    /// positive.write_f32(0.0);
+    ///
+    /// let mut negative = Fnv1a::<u64>::new();
+    /// negative.write_f32(-0.0);
+    ///
+    /// assert_eq!(positive.finish(), negative.finish());
+    /// ```
+    fn write_f32(&mut self, value: f32) {
+        let value = if value.is_nan() {
+            f32::NAN
+        } else if value == 0.0 {
+            0.0
+        } else {
+            value
+        };
+
+        self.write(&value.to_bits().to_le_bytes());
+    }
+
+    /// Writes the bits of `value` into this Hasher as 8 little-endian bytes,
+    /// so that equal floats hash equally.
+    ///
+    /// Every NaN is written as the bits of `f64::NAN`, and `-0.0` as `+0.0`.
+    /// Infinities and all other values are written unchanged.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut quiet = Fnv1a::<u64>::new();
+    /// quiet.write_f64(f64::NAN);
+    ///
+    /// let mut signalling = Fnv1a::<u64>::new();
+    /// signalling.write_f64(f64::from_bits(0x7ff0_0000_0000_0001));
+    ///
+    /// assert_eq!(quiet.finish(), signalling.finish());
+    /// ```
+    fn write_f64(&mut self, value: f64) {
+        let value = if value.is_nan() {
+            f64::NAN
+        } else if value == 0.0 {
+            0.0
+        } else {
+            value
+        };
+
+        self.write(&value.to_bits().to_le_bytes());
+    }
+
+    /// Writes the in-memory bytes of a slice of plain-old-data values into
+    /// this Hasher.
+    ///
+    /// The bytes are those of `bytemuck::cast_slice`, so the hash depends on
+    /// the platform's endianness and on the layout of `T`. A `Pod` type has no
+    /// padding bytes, but wider integers and structs of them hash differently
+    /// on big-endian and little-endian platforms.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut fnv_hasher = Fnv1a::<u64>::new();
+    /// fnv_hasher.write_pod_slice(&[1u32, 2, 3]);
+    /// ```
+    #[cfg(feature = "bytemuck")]
+    fn write_pod_slice<T: bytemuck::Pod>(&mut self, slice: &[T]) {
+        self.write(bytemuck::cast_slice(slice));
+    }
+
+    /// Writes the bytes of a plain-old-data value into this Hasher.
+    ///
+    /// The bytes are those of `bytemuck::bytes_of`. `Pod` requires that `T`
+    /// has no implicit padding, so any padding is spelled out as fields which
+    /// are zeroed by `Zeroable::zeroed` or set explicitly, and equal values
+    /// always hash equally. A type which is not `Pod`, such as one with a
+    /// pointer or with implicit padding, is rejected at compile time. As with
+    /// `write_pod_slice`, the hash depends on the platform's endianness and on
+    /// the layout of `T`.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut fnv_hasher = Fnv1a::<u64>::new();
+    /// fnv_hasher.write_struct(&[1u16, 2, 3]);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// // A reference is not `Pod`
+    /// let mut fnv_hasher = Fnv1a::<u64>::new();
+    /// fnv_hasher.write_struct(&(&1u32, 2u32));
+    /// ```
+    #[cfg(feature = "bytemuck")]
+    fn write_struct<T: bytemuck::Zeroable + bytemuck::Pod>(&mut self, value: &T) {
+        self.write(bytemuck::bytes_of(value));
+    }
+
+    /// Writes the canonical form of a decimal into this Hasher, so that
+    /// numerically equal decimals hash equally.
+    ///
+    /// The decimal is normalized, stripping trailing zeros from its mantissa
+    /// and making `-0` positive, so `1.50` and `1.5` hash equally, as do every
+    /// zero. It is then written as 14 bytes, which will not change between
+    /// versions:
+    ///
+    /// 1. `0x00` if it is positive or zero, or `0x01` if it is negative,
+    /// 2. its scale, the number of digits after the decimal point, as one
+    ///    byte of 0 to 28, and
+    /// 3. the magnitude of its mantissa as 12 little-endian bytes.
+    ///
+    /// ```
+    /// extern crate lz_fnv;
+    /// extern crate rust_decimal;
+    ///
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    /// use rust_decimal::Decimal;
+    ///
+    /// # fn main() {
+    /// let mut trailing = Fnv1a::<u64>::new();
+    /// trailing.write_decimal(&Decimal::new(150, 2));
+    ///
+    /// let mut bytes = Fnv1a::<u64>::new();
+    /// bytes.write(&[0x00, 0x01, 15, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    ///
+    /// assert_eq!(trailing.finish(), bytes.finish());
+    /// # }
+    /// ```
+    #[cfg(feature = "rust_decimal")]
+    fn write_decimal(&mut self, d: &rust_decimal::Decimal) {
+        self.write(&decimal::canonical_bytes(d));
+    }
 }
 
-macro_rules! fnv1_impl {
-    ($type: ty, $offset: expr, $prime: expr, $from_byte: ident) => {
-        impl Default for Fnv1<$type> {
-            fn default() -> Self {
-                Self { hash: $offset }
-            }
-        }
+/// The former name of the `FnvHash` trait, which will be removed in a future
+/// release.
+///
+/// Code using this name, including implementations of the trait, compiles
+/// unchanged. Rust does not yet warn on uses of a deprecated re-export, so
+/// the deprecation is only shown in the documentation.
+#[deprecated(note = "renamed to `FnvHash`")]
+pub use FnvHash as FnvHasher;
 
-        impl Fnv1<$type> {
-            /// Creates a new `Fnv1<T>`.
-            pub fn new() -> Self {
-                Self::default()
-            }
-        }
+/// The FNV hash core, generic over the variant `V` and the hash width `T`.
+///
+/// The variant is one of the zero-sized markers `V0`, `V1` and `V1a`, and is
+/// usually named through the `Fnv0`, `Fnv1` and `Fnv1a` aliases. Code written
+/// over `Fnv<V, T>` with `V: Variant` works for all three. The width is
+/// bounded by `FnvWidth`, so naming a hasher over any type but `u32`, `u64`
+/// or `u128` is a compile error.
+pub struct Fnv<V: Variant, T: FnvWidth> {
+    hash: T,
+    variant: PhantomData<V>,
+}
 
-        impl FnvHasher for Fnv1<$type> {
-            type Hash = $type;
+/// The FNV-0 hash.
+///
+/// This is deprecated except for computing the FNV offset basis for FNV-1 and
+/// FNV-1a hashes.
+pub type Fnv0<T> = Fnv<V0, T>;
 
-            fn finish(&self) -> Self::Hash {
-                self.hash
-            }
+/// The FNV-1 hash.
+pub type Fnv1<T> = Fnv<V1, T>;
 
-            fn write(&mut self, bytes: &[u8]) {
-                let mut hash = self.hash;
+/// The FNV-1a hash.
+pub type Fnv1a<T> = Fnv<V1a, T>;
 
-                for byte in bytes {
-                    hash = hash.wrapping_mul($prime);
-                    hash ^= ($from_byte)(*byte);
-                }
+impl<V: Variant, T: FnvWidth> Clone for Fnv<V, T> {
+    fn clone(&self) -> Self {
+        Self::with_key(self.hash)
+    }
+}
 
-                self.hash = hash;
-            }
-        }
-    };
+impl<V: Variant, T: FnvWidth + fmt::Debug> fmt::Debug for Fnv<V, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(V::NAME).field("hash", &self.hash).finish()
+    }
 }
 
-macro_rules! fnv1a_impl {
-    ($type: ty, $offset: expr, $prime: expr, $from_byte: ident) => {
-        impl Default for Fnv1a<$type> {
-            fn default() -> Self {
-                Self { hash: $offset }
-            }
+impl<V: Variant, T: FnvWidth> Default for Fnv<V, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: Variant, T: FnvWidth> Fnv<V, T> {
+    /// Creates a new hasher, starting from zero for FNV-0 and from the offset
+    /// basis otherwise.
+    ///
+    /// ```
+    /// use lz_fnv::Fnv0;
+    ///
+    /// let fnv_hasher = Fnv0::<u32>::new();
+    /// ```
+    pub const fn new() -> Self {
+        Self::with_key(if V::USES_OFFSET_BASIS {
+            T::OFFSET_BASIS
+        } else {
+            T::ZERO
+        })
+    }
+
+    /// Reverses a prior `write` of exactly the specified bytes.
+    ///
+    /// Each byte's step is reversed, last byte first, by multiplying by the
+    /// modular inverse of the FNV prime and XORing the byte back out. After
+    /// `write(bytes)`, `rollback(bytes)` restores the prior state; rolling
+    /// back bytes which were not the last written produces a garbage state.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut fnv_hasher = Fnv1a::<u32>::new();
+    /// fnv_hasher.write(b"foo");
+    /// let before = fnv_hasher.finish();
+    ///
+    /// fnv_hasher.write(b"bar");
+    /// fnv_hasher.rollback(b"bar");
+    ///
+    /// assert_eq!(fnv_hasher.finish(), before);
+    /// ```
+    pub fn rollback(&mut self, bytes: &[u8]) {
+        let mut hash = self.hash;
+
+        for byte in bytes.iter().rev() {
+            hash = V::unstep(hash, *byte);
         }
 
-        impl Fnv1a<$type> {
-            /// Creates a new `Fnv1a<T>`.
-            pub fn new() -> Self {
-                Self::default()
-            }
+        self.hash = hash;
+    }
+
+    /// Creates a new hasher with the specified key.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv0, Fnv1, Fnv1a};
+    ///
+    /// let fnv0 = Fnv0::with_key(872u32);
+    /// let fnv1 = Fnv1::with_key(872u32);
+    /// let fnv1a = Fnv1a::with_key(872u32);
+    /// ```
+    pub const fn with_key(key: T) -> Self {
+        Self {
+            hash: key,
+            variant: PhantomData,
         }
+    }
 
-        impl FnvHasher for Fnv1a<$type> {
-            type Hash = $type;
+    /// Produces an independent hasher continuing from the current state.
+    ///
+    /// This is the same as `clone`, but states the intent: writes to either
+    /// hasher afterwards do not affect the other.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut prefix = Fnv1a::<u64>::new();
+    /// prefix.write(b"foo");
+    ///
+    /// let mut fork = prefix.fork();
+    /// fork.write(b"bar");
+    ///
+    /// assert_eq!(fork.finish(), 0x8594_4171_f739_67e8);
+    /// ```
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
 
-            fn finish(&self) -> Self::Hash {
-                self.hash
-            }
+    /// Returns the current hash.
+    ///
+    /// This is the same as `finish`, but is usable in const contexts.
+    ///
+    /// ```
+    /// use lz_fnv::Fnv1a;
+    ///
+    /// const HASHER: Fnv1a<u64> = Fnv1a::with_key(0xcbf2_9ce4_8422_2325);
+    /// const VALUE: u64 = HASHER.value();
+    ///
+    /// assert_eq!(VALUE, 0xcbf2_9ce4_8422_2325);
+    /// ```
+    pub const fn value(&self) -> T {
+        self.hash
+    }
 
-            fn write(&mut self, bytes: &[u8]) {
-                let mut hash = self.hash;
+    /// Formats the current hash into `buf` as lowercase hex, returning the
+    /// formatted digits.
+    ///
+    /// The hash is zero-padded to `T::BITS / 4` digits, one byte each, so
+    /// `buf` must be at least 8, 16 or 32 bytes long for a 32, 64 or 128-bit
+    /// hash; any further bytes are left untouched. Nothing is allocated, so
+    /// this is available without the `std` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FnvError::BufferTooSmall` if `buf` is shorter than the
+    /// number of digits, leaving it untouched.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvError, FnvHash};
+    ///
+    /// let mut fnv_hasher = Fnv1a::<u64>::new();
+    /// fnv_hasher.write(b"foobar");
+    ///
+    /// let mut buf = [0u8; 16];
+    /// assert_eq!(fnv_hasher.finish_hex_into(&mut buf), Ok("85944171f73967e8"));
+    ///
+    /// assert_eq!(
+    ///     fnv_hasher.finish_hex_into(&mut [0u8; 15]),
+    ///     Err(FnvError::BufferTooSmall { got: 15, needed: 16 })
+    /// );
+    /// ```
+    pub fn finish_hex_into<'a>(&self, buf: &'a mut [u8]) -> Result<&'a str, FnvError>
+    where
+        T: Into<u128>,
+    {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
 
-                for byte in bytes {
-                    hash ^= ($from_byte)(*byte);
-                    hash = hash.wrapping_mul($prime);
-                }
+        let needed = (T::BITS / 4) as usize;
+        if buf.len() < needed {
+            return Err(FnvError::BufferTooSmall {
+                got: buf.len(),
+                needed,
+            });
+        }
 
-                self.hash = hash;
-            }
+        let hash: u128 = self.hash.into();
+        let digits = &mut buf[..needed];
+        for (i, digit) in digits.iter_mut().rev().enumerate() {
+            *digit = DIGITS[(hash >> (4 * i) & 0xf) as usize];
         }
-    };
+
+        Ok(::std::str::from_utf8(digits).expect("hex digits are ASCII"))
+    }
 }
 
-macro_rules! fnv_hasher_impl {
-    ($type: ty) => {
-        impl ::std::hash::Hasher for $type {
-            fn finish(&self) -> u64 {
-                ::FnvHasher::finish(self)
-            }
+impl<T: FnvWidth> Fnv1a<T> {
+    /// Creates a new `Fnv1a<T>` which has already hashed the specified
+    /// domain-separation tag.
+    ///
+    /// The tag's length, as 8 little-endian bytes, is hashed before the
+    /// tag itself so that no tag is a prefix of another.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut users = Fnv1a::<u64>::with_domain(b"users");
+    /// users.write(b"42");
+    ///
+    /// let mut orders = Fnv1a::<u64>::with_domain(b"orders");
+    /// orders.write(b"42");
+    ///
+    /// assert_ne!(users.finish(), orders.finish());
+    /// ```
+    pub fn with_domain(tag: &[u8]) -> Self {
+        let mut hasher = Self::new();
+        hasher.write(&(tag.len() as u64).to_le_bytes());
+        hasher.write(tag);
+        hasher
+    }
 
-            fn write(&mut self, bytes: &[u8]) {
-                ::FnvHasher::write(self, bytes);
-            }
-        }
-    };
-}
-macro_rules! fnv_impl {
-    (u64, $offset: expr, $prime: expr, $from_byte: ident) => {
-        fnv0_impl!(u64, $prime, $from_byte);
-        fnv_hasher_impl!(Fnv0<u64>);
-
-        fnv1_impl!(u64, $offset, $prime, $from_byte);
-        fnv_hasher_impl!(Fnv1<u64>);
-
-        fnv1a_impl!(u64, $offset, $prime, $from_byte);
-        fnv_hasher_impl!(Fnv1a<u64>);
-    };
-    ($type: ty, $offset: expr, $prime: expr, $from_byte: ident) => {
-        fnv0_impl!($type, $prime, $from_byte);
-        fnv1_impl!($type, $offset, $prime, $from_byte);
-        fnv1a_impl!($type, $offset, $prime, $from_byte);
-    };
+    /// Undoes the writing of the specified trailing bytes.
+    ///
+    /// This is deprecated in favour of `rollback`, which it calls and which
+    /// is available on every hasher.
+    #[deprecated(note = "use `rollback`, which is available on every hasher")]
+    pub fn rewind(&mut self, bytes: &[u8]) {
+        self.rollback(bytes);
+    }
 }
 
-fn u32_from_byte(byte: u8) -> u32 {
-    byte.into()
+impl<V: Variant, T: FnvWidth> FnvHash for Fnv<V, T> {
+    type Hash = T;
+
+    fn finish(&self) -> Self::Hash {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.hash = V::advance(self.hash, bytes);
+    }
 }
 
-fn u64_from_byte(byte: u8) -> u64 {
-    byte.into()
+impl<V: Variant> ::std::hash::Hasher for Fnv<V, u64> {
+    fn finish(&self) -> u64 {
+        FnvHash::finish(self)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        FnvHash::write(self, bytes);
+    }
 }
 
-fn u128_from_byte(byte: u8) -> u128 {
-    byte.into()
+impl Fnv1a<u64> {
+    /// Completes a round of hashing, XOR-folding the 64-bit hash down to 32
+    /// bits.
+    ///
+    /// This is the fold recommended by the FNV specification for producing a
+    /// 32-bit hash from the 64-bit computation, `(hash >> 32) ^ (hash &
+    /// 0xffff_ffff)`. It is distinct from, and not equal to, `Fnv1a<u32>`.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut fnv_hasher = Fnv1a::<u64>::new();
+    /// fnv_hasher.write(b"foobar");
+    ///
+    /// assert_eq!(fnv_hasher.finish_folded_32(), 0x72ad_2699);
+    /// ```
+    pub fn finish_folded_32(&self) -> u32 {
+        ((self.hash >> 32) ^ (self.hash & 0xffff_ffff)) as u32
+    }
+
+    /// Completes a round of hashing, reducing the 64-bit hash to the 52 bits
+    /// produced by the `fnv-plus` JavaScript package.
+    ///
+    /// This nonstandard reduction keeps the low 52 bits and XORs the top 12
+    /// bits into the lowest; see `fnv1a_52`.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut fnv_hasher = Fnv1a::<u64>::new();
+    /// fnv_hasher.write(b"foobar");
+    ///
+    /// assert_eq!(fnv_hasher.finish_52(), 0x4_4171_f739_6fb1);
+    /// ```
+    pub fn finish_52(&self) -> u64 {
+        (self.hash & ((1 << 52) - 1)) ^ (self.hash >> 52)
+    }
 }
 
-fnv_impl!(u32, 0x811c_9dc5, 0x100_0193, u32_from_byte);
-fnv_impl!(u64, 0xcbf2_9ce4_8422_2325, 0x100_0000_01B3, u64_from_byte);
-fnv_impl!(
-    u128,
-    0x6C62_272E_07BB_0142_62B8_2175_6295_C58D,
-    0x0000_0000_0100_0000_0000_0000_0000_013B,
-    u128_from_byte
-);
+#[cfg(feature = "std")]
+mod abbrev;
+mod any;
+#[cfg(feature = "std")]
+mod avalanche;
+mod batch;
+#[cfg(feature = "std")]
+mod builder;
+#[macro_use]
+mod callsite;
+mod checksum;
+#[cfg(feature = "std")]
+pub mod checksum_file;
+mod chunks;
+#[cfg(feature = "cobs")]
+mod cobs;
+#[cfg(feature = "std")]
+mod compare;
+mod coords;
+mod counting;
+#[cfg(feature = "rust_decimal")]
+mod decimal;
+#[cfg(feature = "std")]
+mod dir;
+#[cfg(feature = "rayon")]
+mod dir_par;
+mod dyn_hasher;
+#[cfg(feature = "std")]
+mod env;
+mod error;
+mod ext;
+#[cfg(feature = "std")]
+mod feature_hash;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "std")]
+mod file;
+#[cfg(feature = "std")]
+mod file_cache;
+#[cfg(feature = "std")]
+mod fingerprint;
+mod flat_map;
+mod fnv_plus;
+mod folded;
+#[cfg(feature = "std")]
+mod hash_map;
+#[macro_use]
+mod include;
+#[cfg(feature = "std")]
+mod instrumented;
+#[cfg(feature = "std")]
+mod interner;
+mod jitter;
+#[cfg(feature = "serde_json")]
+mod json;
+mod key_bytes;
+#[cfg(feature = "std")]
+mod kmer;
+#[macro_use]
+mod oneshot;
+mod page;
+mod pair;
+mod params;
+#[cfg(feature = "std")]
+mod partition;
+#[cfg(feature = "std")]
+mod proquint;
+mod self_test;
+#[cfg(feature = "std")]
+mod series;
+#[cfg(feature = "std")]
+mod shuffle;
+#[cfg(any(all(test, feature = "std"), feature = "testing"))]
+#[macro_use]
+mod snapshot;
+#[cfg(feature = "std")]
+mod sparse;
+#[cfg(test)]
+mod spec_vectors;
+#[cfg(feature = "rusqlite")]
+mod sqlite_hash;
+#[cfg(test)]
+mod stability;
+mod std_hasher;
+mod step;
+#[cfg(feature = "futures")]
+mod stream;
+mod summary;
+#[cfg(feature = "tar")]
+mod tar_hash;
+#[cfg(any(all(test, feature = "std"), feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "std")]
+mod thread_local;
+mod timestamp;
+#[cfg(feature = "tracing")]
+mod tracing_hash;
+mod unordered;
+mod variant;
+mod visit;
+#[cfg(feature = "std")]
+mod walk;
+mod width;
+mod widths;
+mod words;
+#[cfg(all(unix, feature = "xattr"))]
+mod xattr_hash;
+
+#[cfg(feature = "std")]
+pub use abbrev::{abbreviate, AbbrevTable, Resolution};
+pub use any::AnyFnv64;
+#[cfg(feature = "std")]
+pub use avalanche::avalanche_score;
+pub use batch::{hash_each_u32, hash_each_u64};
+#[cfg(feature = "std")]
+pub use batch::{hash_each_u32_vec, hash_each_u64_vec};
+#[cfg(feature = "std")]
+pub use builder::{Algorithm, BuildError, Endian, HasherBuilder, Width};
+pub use callsite::callsite_id;
+pub use checksum::{checksum, Checksum, DualChecksum};
+pub use chunks::{hash_ring, hash_try_chunks, write_try_chunks};
+#[cfg(feature = "cobs")]
+pub use cobs::{decode_frame, encode_frame, max_frame_len, FrameError};
+#[cfg(feature = "std")]
+pub use compare::compare_report;
+pub use coords::{hash_coords2, hash_coords3};
+pub use counting::CountingHasher;
+#[cfg(feature = "rust_decimal")]
+pub use decimal::hash_decimal;
+#[cfg(feature = "std")]
+pub use dir::hash_dir;
+#[cfg(feature = "rayon")]
+pub use dir_par::hash_dir_files;
+pub use dyn_hasher::DynFnvHasher;
+#[cfg(feature = "std")]
+pub use env::{
+    env_fingerprint, env_fingerprint_from, env_fingerprint_sorted, env_fingerprint_sorted_from,
+};
+pub use error::FnvError;
+pub use ext::FnvExt;
+#[cfg(feature = "std")]
+pub use feature_hash::{
+    feature_index, feature_index_signed, hash_features, FeatureHashError, FeatureVector,
+};
+#[cfg(feature = "ffi")]
+pub use ffi::hash_cstr;
+#[cfg(feature = "std")]
+pub use file::{hash_file, hash_file_with_progress, quick_file_signature, verify_reader};
+#[cfg(feature = "std")]
+pub use file_cache::FileHashCache;
+#[cfg(feature = "std")]
+pub use fingerprint::{fingerprint_file, fingerprint_name, DEFAULT_FINGERPRINT_LEN};
+pub use flat_map::{FlatMapFullError, FnvFlatMap};
+pub use fnv_plus::fnv1a_52;
+pub use folded::fnv1a_24;
+#[cfg(feature = "std")]
+pub use hash_map::{fnv_hashmap_with_seed, FnvBuildHasher, FnvHashMap, FnvHashSet};
+#[cfg(feature = "std")]
+pub use instrumented::{
+    HashStatsReport, InstrumentedFnvBuildHasher, InstrumentedFnvHasher, HISTOGRAM_BUCKETS,
+};
+#[cfg(feature = "std")]
+pub use interner::{FnvInterner, Symbol};
+pub use jitter::{jitter_fraction, ttl_with_jitter};
+#[cfg(feature = "serde_json")]
+pub use json::hash_json_value;
+pub use key_bytes::FnvKeyBytes;
+#[cfg(feature = "std")]
+pub use kmer::{hash_kmer, kmer_hashes, KmerError, KmerHashes};
+pub use page::{page_checksum, page_checksum_64, verify_page, verify_page_64, PageError};
+pub use pair::{hash_pair, hash_salted};
+pub use params::{
+    const_offset_basis_u128, const_offset_basis_u32, const_offset_basis_u64, derive_offset_basis,
+    validate_prime, PrimeError,
+};
+#[cfg(feature = "std")]
+pub use partition::{
+    partition_by_hash, partition_for, partition_indices_by_hash, PartitionedWriter,
+};
+#[cfg(feature = "std")]
+pub use proquint::{Proquint, ProquintError};
+pub use self_test::{self_test, SelfTestFailure};
+#[cfg(feature = "std")]
+pub use series::{series_id, SeriesIdError};
+#[cfg(feature = "std")]
+pub use shuffle::{shuffle_by_hash, stable_rank};
+#[cfg(any(all(test, feature = "std"), feature = "testing"))]
+pub use snapshot::{check_fnv_snapshot, display_fnv_hash, UPDATE_SNAPSHOTS_VAR};
+#[cfg(feature = "std")]
+pub use sparse::hash_file_sparse;
+#[cfg(feature = "rusqlite")]
+pub use sqlite_hash::register_fnv_functions;
+pub use std_hasher::StdFnvHasher;
+pub use step::{
+    fnv1_128_advance, fnv1_128_step, fnv1_128_unstep, fnv1_32_advance, fnv1_32_step,
+    fnv1_32_unstep, fnv1_64_advance, fnv1_64_step, fnv1_64_unstep, fnv1a_128_advance,
+    fnv1a_128_step, fnv1a_128_unstep, fnv1a_32_advance, fnv1a_32_step, fnv1a_32_unstep,
+    fnv1a_64_advance, fnv1a_64_step, fnv1a_64_unstep, prime_inverse_u128, prime_inverse_u32,
+    prime_inverse_u64,
+};
+pub use step::{
+    fnv1_step_u128, fnv1_step_u32, fnv1_step_u64, fnv1a_step_u128, fnv1a_step_u32, fnv1a_step_u64,
+};
+#[cfg(feature = "futures")]
+pub use stream::{hash_byte_stream, HashedStream};
+pub use summary::summarize;
+#[cfg(feature = "tar")]
+pub use tar_hash::{hash_tar_entries, TarHashOptions};
+#[cfg(feature = "std")]
+pub use thread_local::fnv1a_64_tl;
+pub use timestamp::{hash_timestamp, FnvTimestamp};
+#[cfg(feature = "tracing")]
+pub use tracing_hash::{event_fingerprint, FnvEventHasher};
+pub use unordered::{hash_unordered, IncrementalSetHash};
+pub use variant::{V1a, Variant, V0, V1};
+pub use visit::{hash_visit, visit_with, write_leaf, write_node, write_node_header, FnvVisit};
+#[cfg(feature = "rayon")]
+pub use walk::DirHashOptions;
+pub use width::FnvWidth;
+pub use widths::{u128, u32, u64};
+pub use words::Fnv1aWords;
+#[cfg(all(unix, feature = "xattr"))]
+pub use xattr_hash::{
+    read_xattr_hash, store_xattr_hash, verify_xattr_hash, VerifyOutcome, XATTR_NAME,
+};
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "std")]
     use std::iter;
-    use {Fnv0, Fnv1, Fnv1a, FnvHasher};
+    #[cfg(feature = "std")]
+    use FnvError;
+    use {Fnv0, Fnv1, Fnv1a, FnvHash};
 
     macro_rules! fnv0_tests {
         ($($name: ident: $size: ty, $input: expr, $expected_hash: expr,)*) => {
@@ -263,6 +1084,7 @@ mod tests {
         };
     }
 
+    #[cfg(feature = "std")]
     macro_rules! fnv1_tests {
         ($($name: ident: $size: ty, $input: expr, $expected_hash: expr,)*) => {
             $(
@@ -279,6 +1101,7 @@ mod tests {
             )*
         };
     }
+    #[cfg(feature = "std")]
     macro_rules! fnv1a_tests {
         ($($name: ident: $size: ty, $input: expr, $expected_hash: expr,)*) => {
             $(
@@ -296,12 +1119,616 @@ mod tests {
         };
     }
 
+    #[cfg(feature = "std")]
+    #[allow(clippy::manual_repeat_n)]
     fn repeat(slice: &[u8], times: usize) -> Vec<u8> {
         iter::repeat(slice).take(times).flatten().cloned().collect()
     }
 
+    // The test vectors build C strings with `CString`
+    #[cfg(feature = "std")]
     include!("fnv_test_cases.rs");
 
+    fn varint_hash(value: u64) -> u64 {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write_varint(value);
+        fnv1a.finish()
+    }
+
+    fn bytes_hash(bytes: &[u8]) -> u64 {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write(bytes);
+        fnv1a.finish()
+    }
+
+    #[test]
+    fn write_capped_enforces_budget() {
+        let mut remaining = 5;
+        let mut fnv1a = Fnv1a::<u64>::new();
+
+        assert_eq!(fnv1a.write_capped(b"ab", &mut remaining), 2);
+        assert_eq!(fnv1a.write_capped(b"cde", &mut remaining), 3);
+        assert_eq!(fnv1a.write_capped(b"fgh", &mut remaining), 0);
+        assert_eq!(fnv1a.write_capped(b"", &mut remaining), 0);
+
+        assert_eq!(remaining, 0);
+        assert_eq!(fnv1a.finish(), bytes_hash(b"abcde"));
+    }
+
+    #[test]
+    fn write_capped_splits_chunk() {
+        let mut remaining = 4;
+        let mut fnv1a = Fnv1a::<u64>::new();
+
+        assert_eq!(fnv1a.write_capped(b"foo", &mut remaining), 3);
+        assert_eq!(fnv1a.write_capped(b"bar", &mut remaining), 1);
+
+        assert_eq!(fnv1a.finish(), bytes_hash(b"foob"));
+    }
+
+    #[test]
+    fn write_range_matches_slice() {
+        let bytes = b"chongo was here!\n";
+
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write_range(bytes, 7..11);
+
+        assert_eq!(fnv1a.finish(), bytes_hash(&bytes[7..11]));
+    }
+
+    #[test]
+    fn write_range_empty() {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write_range(b"foobar", 3..3);
+        fnv1a.write_range(b"", 0..0);
+
+        assert_eq!(fnv1a.finish(), bytes_hash(b""));
+    }
+
+    #[test]
+    #[should_panic(expected = "write_range start 4 is greater than end 2")]
+    fn write_range_inverted() {
+        #[allow(clippy::reversed_empty_ranges)]
+        Fnv1a::<u64>::new().write_range(b"foobar", 4..2);
+    }
+
+    #[test]
+    #[should_panic(expected = "write_range end 7 is out of range for 6 bytes")]
+    fn write_range_out_of_bounds() {
+        Fnv1a::<u64>::new().write_range(b"foobar", 2..7);
+    }
+
+    #[test]
+    fn write_in_place_matches_write() {
+        let mut buf = *b"foobar";
+        let mut in_place = Fnv1a::<u64>::new();
+        in_place.write_in_place(&buf);
+        buf.reverse();
+        in_place.write_in_place(&buf[..0]);
+        in_place.write_in_place(&buf);
+
+        assert_eq!(in_place.finish(), bytes_hash(b"foobarraboof"));
+    }
+
+    #[test]
+    fn write_trimmed_end_ignores_trailing_whitespace() {
+        let mut padded = Fnv1a::<u64>::new();
+        padded.write_trimmed_end(b"value   ");
+        assert_eq!(padded.finish(), bytes_hash(b"value"));
+
+        let mut leading = Fnv1a::<u64>::new();
+        leading.write_trimmed_end(b"  value \x0c\n");
+        assert_eq!(leading.finish(), bytes_hash(b"  value"));
+
+        let mut blank = Fnv1a::<u64>::new();
+        blank.write_trimmed_end(b"   ");
+        blank.write_trimmed_end(b"");
+        assert_eq!(blank.finish(), bytes_hash(b""));
+    }
+
+    #[test]
+    fn write_trimmed_strips_both_ends() {
+        let mut quoted = Fnv1a::<u64>::new();
+        quoted.write_trimmed(b"'value'", b"'");
+        assert_eq!(quoted.finish(), bytes_hash(b"value"));
+
+        let mut inner = Fnv1a::<u64>::new();
+        inner.write_trimmed(b"(('it's'))", b"()'");
+        assert_eq!(inner.finish(), bytes_hash(b"it's"));
+
+        let mut untrimmed = Fnv1a::<u64>::new();
+        untrimmed.write_trimmed(b" value ", b"");
+        assert_eq!(untrimmed.finish(), bytes_hash(b" value "));
+    }
+
+    #[test]
+    fn write_trimmed_of_only_trim_bytes_is_empty() {
+        let mut hasher = Fnv1a::<u64>::new();
+        hasher.write_trimmed(b"''''", b"'");
+        hasher.write_trimmed(b"'", b"'");
+        hasher.write_trimmed(b"", b"'");
+
+        assert_eq!(hasher.finish(), bytes_hash(b""));
+    }
+
+    #[test]
+    fn write_cstr_array_matches_c() {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write_cstr_array(&[b"foo", b"bar"]);
+        assert_eq!(fnv1a.finish(), 0x5863_d945_8c10_38de);
+
+        let mut argv = Fnv1a::<u64>::new();
+        argv.write_cstr_array(&[b"ls", b"-la", b""]);
+        assert_eq!(argv.finish(), 0xadfe_1633_8160_c968);
+
+        let mut fnv1a_32 = Fnv1a::<u32>::new();
+        fnv1a_32.write_cstr_array(&[b"a", b"b"]);
+        assert_eq!(fnv1a_32.finish(), 0x8197_7b96);
+    }
+
+    #[test]
+    fn write_cstr_array_embedded_nul() {
+        let mut embedded = Fnv1a::<u64>::new();
+        embedded.write_cstr_array(&[b"a\0b"]);
+
+        let mut split = Fnv1a::<u64>::new();
+        split.write_cstr_array(&[b"a", b"b"]);
+
+        assert_eq!(embedded.finish(), split.finish());
+    }
+
+    #[test]
+    fn write_cstr_array_empty() {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write_cstr_array(&[]);
+
+        assert_eq!(fnv1a.finish(), bytes_hash(b""));
+    }
+
+    #[test]
+    fn write_chars_matches_utf8() {
+        for s in &["foobar", "", "caf\u{e9} \u{1f980}", "\u{fffd}\u{fffd}"] {
+            let mut fnv1a = Fnv1a::<u64>::new();
+            fnv1a.write_chars(s.chars());
+
+            assert_eq!(fnv1a.finish(), bytes_hash(s.as_bytes()));
+        }
+
+        let mut replacement = Fnv1a::<u64>::new();
+        replacement.write_chars(Some(char::REPLACEMENT_CHARACTER));
+        assert_eq!(replacement.finish(), bytes_hash(&[0xef, 0xbf, 0xbd]));
+    }
+
+    fn socket_addr_hash(addr: &str) -> u64 {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write_socket_addr(&addr.parse().unwrap());
+        fnv1a.finish()
+    }
+
+    #[test]
+    fn write_socket_addr_distinguishes_families() {
+        let addrs = [
+            "192.0.2.1:80",
+            "192.0.2.1:8080",
+            "192.0.2.2:80",
+            "[::ffff:192.0.2.1]:80",
+            "[::1]:80",
+            "[2001:db8::1]:443",
+            "0.0.0.0:0",
+            "[::]:0",
+        ];
+
+        for (i, a) in addrs.iter().enumerate() {
+            for b in &addrs[i + 1..] {
+                assert_ne!(socket_addr_hash(a), socket_addr_hash(b), "{} {}", a, b);
+            }
+        }
+
+        assert_eq!(
+            socket_addr_hash("[2001:db8::1%7]:443"),
+            socket_addr_hash("[2001:db8::1]:443")
+        );
+        assert_eq!(
+            socket_addr_hash("0.0.0.0:0"),
+            bytes_hash(&[4, 0, 0, 0, 0, 0, 0])
+        );
+        assert_eq!(socket_addr_hash("192.0.2.1:80"), 0x8a6d_af0d_3339_a3be);
+        assert_eq!(socket_addr_hash("[2001:db8::1]:443"), 0x3174_5f3d_25fa_d0a8);
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn write_nfc_equates_composed_and_decomposed() {
+        fn nfc_hash(s: &str) -> u64 {
+            let mut fnv1a = Fnv1a::<u64>::new();
+            fnv1a.write_nfc(s);
+            fnv1a.finish()
+        }
+
+        assert_eq!(nfc_hash("\u{e9}"), nfc_hash("e\u{301}"));
+        assert_eq!(nfc_hash("e\u{301}"), bytes_hash("\u{e9}".as_bytes()));
+        assert_eq!(nfc_hash("\u{1e69}"), nfc_hash("s\u{323}\u{307}"));
+        assert_eq!(nfc_hash("\u{1e69}"), nfc_hash("s\u{307}\u{323}"));
+        assert_eq!(nfc_hash("foobar"), bytes_hash(b"foobar"));
+        assert_eq!(nfc_hash(""), bytes_hash(b""));
+        assert_ne!(nfc_hash("\u{fb01}"), nfc_hash("fi"));
+        assert_ne!(nfc_hash("\u{e9}"), nfc_hash("e"));
+    }
+
+    fn f32_hash(value: f32) -> u64 {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write_f32(value);
+        fnv1a.finish()
+    }
+
+    fn f64_hash(value: f64) -> u64 {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write_f64(value);
+        fnv1a.finish()
+    }
+
+    #[test]
+    fn write_float_canonicalizes_zero() {
+        assert_eq!(f32_hash(0.0), f32_hash(-0.0));
+        assert_eq!(f64_hash(0.0), f64_hash(-0.0));
+        assert_eq!(f64_hash(-0.0), bytes_hash(&[0; 8]));
+    }
+
+    #[test]
+    fn write_float_canonicalizes_nan() {
+        let nans_32 = [0x7fc0_0000, 0xffc0_0000, 0x7f80_0001, 0x7fff_ffff];
+        for &bits in &nans_32 {
+            assert_eq!(f32_hash(f32::from_bits(bits)), f32_hash(f32::NAN));
+        }
+
+        let nans_64 = [
+            0xfff8_0000_0000_0000,
+            0x7ff0_0000_0000_0001,
+            0x7ff8_dead_beef_0000,
+        ];
+        for &bits in &nans_64 {
+            assert_eq!(f64_hash(f64::from_bits(bits)), f64_hash(f64::NAN));
+        }
+    }
+
+    #[test]
+    fn write_float_keeps_other_values() {
+        assert_ne!(f32_hash(f32::INFINITY), f32_hash(f32::NEG_INFINITY));
+        assert_ne!(f64_hash(f64::INFINITY), f64_hash(f64::NAN));
+        assert_eq!(f32_hash(1.5), bytes_hash(&1.5f32.to_bits().to_le_bytes()));
+        assert_eq!(
+            f64_hash(-2.25),
+            bytes_hash(&(-2.25f64).to_bits().to_le_bytes())
+        );
+    }
+
+    #[test]
+    fn write_varint_zero_is_single_byte() {
+        assert_eq!(varint_hash(0), bytes_hash(&[0x00]));
+    }
+
+    #[test]
+    fn write_varint_matches_encoding() {
+        assert_eq!(varint_hash(1), bytes_hash(&[0x01]));
+        assert_eq!(varint_hash(127), bytes_hash(&[0x7F]));
+        assert_eq!(varint_hash(128), bytes_hash(&[0x80, 0x01]));
+        assert_eq!(varint_hash(300), bytes_hash(&[0xAC, 0x02]));
+        assert_eq!(varint_hash(16_384), bytes_hash(&[0x80, 0x80, 0x01]));
+    }
+
+    #[test]
+    fn write_varint_ten_bytes() {
+        assert_eq!(
+            varint_hash(u64::MAX),
+            bytes_hash(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01])
+        );
+        assert_eq!(
+            varint_hash(1 << 63),
+            bytes_hash(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01])
+        );
+    }
+
+    #[test]
+    fn finish_folded_32_foobar() {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write(b"foobar");
+
+        assert_eq!(fnv1a.finish(), 0x8594_4171_f739_67e8);
+        assert_eq!(fnv1a.finish_folded_32(), 0x8594_4171 ^ 0xf739_67e8);
+        assert_eq!(fnv1a.finish_folded_32(), 0x72ad_2699);
+    }
+
+    #[test]
+    fn finish_folded_32_differs_from_native_32() {
+        let mut fnv1a_64 = Fnv1a::<u64>::new();
+        fnv1a_64.write(b"foobar");
+
+        let mut fnv1a_32 = Fnv1a::<u32>::new();
+        fnv1a_32.write(b"foobar");
+
+        assert_ne!(fnv1a_64.finish_folded_32(), fnv1a_32.finish());
+    }
+
+    #[test]
+    fn with_domain_separates_tags() {
+        let mut auth = Fnv1a::<u64>::with_domain(b"auth");
+        auth.write(b"message");
+
+        let mut cache = Fnv1a::<u64>::with_domain(b"cache");
+        cache.write(b"message");
+
+        assert_ne!(auth.finish(), cache.finish());
+    }
+
+    #[test]
+    fn with_domain_frames_tag() {
+        let mut short = Fnv1a::<u32>::with_domain(b"ab");
+        short.write(b"c");
+
+        let mut long = Fnv1a::<u32>::with_domain(b"a");
+        long.write(b"bc");
+
+        assert_ne!(short.finish(), long.finish());
+    }
+
+    #[test]
+    fn with_domain_prefixes_length_and_tag() {
+        let mut domain = Fnv1a::<u128>::with_domain(b"cache");
+        domain.write(b"message");
+
+        let mut manual = Fnv1a::<u128>::new();
+        manual.write(&[5, 0, 0, 0, 0, 0, 0, 0]);
+        manual.write(b"cachemessage");
+
+        assert_eq!(domain.finish(), manual.finish());
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn write_pod_slice_matches_native_bytes() {
+        let values = [0x0102_0304u32, 0xA0B0_C0D0, 0, u32::MAX];
+
+        let mut pod = Fnv1a::<u64>::new();
+        pod.write_pod_slice(&values);
+
+        let mut bytes = Fnv1a::<u64>::new();
+        for value in &values {
+            bytes.write(&value.to_ne_bytes());
+        }
+
+        assert_eq!(pod.finish(), bytes.finish());
+    }
+
+    #[cfg(all(feature = "bytemuck", target_endian = "little"))]
+    #[test]
+    fn write_pod_slice_little_endian() {
+        let mut pod = Fnv1a::<u64>::new();
+        pod.write_pod_slice(&[0x6f66u16, 0x626f, 0x7261]);
+
+        assert_eq!(pod.finish(), 0x8594_4171_f739_67e8);
+    }
+
+    #[cfg(feature = "bytemuck")]
+    #[derive(Clone, Copy)]
+    #[repr(C)]
+    struct Padded {
+        tag: u8,
+        _padding: [u8; 3],
+        value: u32,
+    }
+
+    #[cfg(feature = "bytemuck")]
+    unsafe impl ::bytemuck::Zeroable for Padded {}
+
+    #[cfg(feature = "bytemuck")]
+    unsafe impl ::bytemuck::Pod for Padded {}
+
+    #[cfg(feature = "bytemuck")]
+    #[test]
+    fn write_struct_hashes_equal_padded_structs_equally() {
+        let mut first: Padded = ::bytemuck::Zeroable::zeroed();
+        first.tag = 7;
+        first.value = 0x0102_0304;
+        let second = Padded {
+            tag: 7,
+            _padding: [0; 3],
+            value: 0x0102_0304,
+        };
+
+        let mut a = Fnv1a::<u64>::new();
+        a.write_struct(&first);
+        let mut b = Fnv1a::<u64>::new();
+        b.write_struct(&second);
+
+        assert_eq!(a.finish(), b.finish());
+
+        let mut bytes = Fnv1a::<u64>::new();
+        bytes.write(&[7, 0, 0, 0]);
+        bytes.write(&0x0102_0304u32.to_ne_bytes());
+        assert_eq!(a.finish(), bytes.finish());
+    }
+
+    macro_rules! rewind_tests {
+        ($($name: ident: $size: ty,)*) => {
+            $(
+                #[test]
+                #[allow(deprecated)]
+                fn $name() {
+                    let mut fnv1a = Fnv1a::<$size>::new();
+                    fnv1a.write(b"chongo was here");
+                    let before = fnv1a.clone();
+
+                    fnv1a.write(b"!\n\x00\xff");
+                    assert_ne!(fnv1a.finish(), before.finish());
+
+                    fnv1a.rewind(b"!\n\x00\xff");
+                    assert_eq!(fnv1a.finish(), before.finish());
+
+                    fnv1a.rewind(b"chongo was here");
+                    assert_eq!(fnv1a.finish(), Fnv1a::<$size>::new().finish());
+                }
+            )*
+        };
+    }
+
+    rewind_tests! {
+        rewind_32: u32,
+        rewind_64: u64,
+        rewind_128: u128,
+    }
+
+    macro_rules! rollback_tests {
+        ($($name: ident: $hasher: ty,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    let mut hasher = <$hasher>::new();
+                    hasher.write(b"shared prefix");
+                    let before = hasher.finish();
+
+                    for suffix in &[&b""[..], b"a", b"suffix", b"\x00\x00\xff"] {
+                        hasher.write(suffix);
+                        hasher.rollback(suffix);
+
+                        assert_eq!(hasher.finish(), before);
+                    }
+                }
+            )*
+        };
+    }
+
+    rollback_tests! {
+        rollback_fnv0_32: Fnv0<u32>,
+        rollback_fnv1_32: Fnv1<u32>,
+        rollback_fnv1a_32: Fnv1a<u32>,
+        rollback_fnv0_64: Fnv0<u64>,
+        rollback_fnv1_64: Fnv1<u64>,
+        rollback_fnv1a_64: Fnv1a<u64>,
+        rollback_fnv0_128: Fnv0<u128>,
+        rollback_fnv1_128: Fnv1<u128>,
+        rollback_fnv1a_128: Fnv1a<u128>,
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn rewind_partial() {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write(b"foobar");
+        fnv1a.rewind(b"bar");
+
+        let mut expected = Fnv1a::<u64>::new();
+        expected.write(b"foo");
+
+        assert_eq!(fnv1a.finish(), expected.finish());
+    }
+
+    #[test]
+    fn fork_continues_independently() {
+        let mut fnv1 = Fnv1::<u32>::new();
+        fnv1.write(b"shared ");
+
+        let mut fork = fnv1.fork();
+        fork.write(b"fork");
+        fnv1.write(b"original");
+
+        let mut expected_fork = Fnv1::<u32>::new();
+        expected_fork.write(b"shared fork");
+        let mut expected_original = Fnv1::<u32>::new();
+        expected_original.write(b"shared original");
+
+        assert_eq!(fork.finish(), expected_fork.finish());
+        assert_eq!(fnv1.finish(), expected_original.finish());
+    }
+
+    const KEYED_32: Fnv1<u32> = Fnv1::with_key(0x811c_9dc5);
+    const KEYED_128: Fnv0<u128> = Fnv0::with_key(872);
+    const VALUE_32: u32 = KEYED_32.value();
+    const VALUE_128: u128 = KEYED_128.value();
+
+    #[test]
+    fn value_is_const() {
+        assert_eq!(VALUE_32, 0x811c_9dc5);
+        assert_eq!(VALUE_128, 872);
+    }
+
+    #[test]
+    fn value_equals_finish() {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write(b"foobar");
+
+        assert_eq!(fnv1a.value(), fnv1a.finish());
+    }
+
+    #[test]
+    fn finish_hex_into_exact_buffer() {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write(b"foobar");
+        let mut buf = [0u8; 16];
+        assert_eq!(fnv1a.finish_hex_into(&mut buf), Ok("85944171f73967e8"));
+
+        let mut buf = [b'x'; 10];
+        assert_eq!(Fnv0::<u32>::new().finish_hex_into(&mut buf), Ok("00000000"));
+        assert_eq!(&buf[8..], b"xx");
+
+        let mut buf = [0u8; 32];
+        assert_eq!(
+            Fnv1::<u128>::new().finish_hex_into(&mut buf),
+            Ok("6c62272e07bb014262b821756295c58d")
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn finish_hex_into_undersized_buffer() {
+        let mut buf = [b'x'; 31];
+
+        assert_eq!(
+            Fnv1a::<u128>::new().finish_hex_into(&mut buf),
+            Err(FnvError::BufferTooSmall {
+                got: 31,
+                needed: 32
+            })
+        );
+        assert_eq!(buf, [b'x'; 31]);
+        assert_eq!(
+            Fnv1a::<u32>::new().finish_hex_into(&mut []),
+            Err(FnvError::BufferTooSmall { got: 0, needed: 8 })
+        );
+        assert_eq!(
+            FnvError::BufferTooSmall { got: 0, needed: 8 }.to_string(),
+            "a buffer of 0 bytes was given but 8 are required"
+        );
+    }
+
+    #[test]
+    fn deprecated_trait_name() {
+        #[allow(deprecated)]
+        use FnvHasher;
+
+        struct Length(usize);
+
+        #[allow(deprecated)]
+        impl FnvHasher for Length {
+            type Hash = usize;
+
+            fn finish(&self) -> usize {
+                self.0
+            }
+
+            fn write(&mut self, bytes: &[u8]) {
+                self.0 += bytes.len();
+            }
+        }
+
+        fn finish_foobar<H: FnvHasher>(mut hasher: H) -> H::Hash {
+            hasher.write(b"foobar");
+            FnvHash::finish(&hasher)
+        }
+
+        assert_eq!(finish_foobar(Length(1)), 7);
+        assert_eq!(finish_foobar(Fnv1a::<u64>::new()), 0x8594_4171_f739_67e8);
+    }
+
     fnv0_tests! {
         fnv0_offset_calculation_128_bit: u128, b"chongo <Landon Curt Noll> /\\../\\", 0x6C62_272E_07BB_0142_62B8_2175_6295_C58D,
     }
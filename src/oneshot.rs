@@ -0,0 +1,116 @@
+//! Generation of one-shot hash functions for further widths.
+
+/// Defines `const fn` one-shot FNV-0, FNV-1 and FNV-1a functions for a width
+/// given its prime and offset basis.
+///
+/// The invocation names an optional visibility, the unsigned integer type
+/// holding the hash, the prime and the offset basis, then the three function
+/// names in the order FNV-0, FNV-1, FNV-1a. Each function takes `&[u8]` and
+/// returns the hash of it, so that a crate adding a width can offer the same
+/// shape of API as the built-in ones. The type must be a primitive unsigned
+/// integer, whose arithmetic wraps modulo `2^bits`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate lz_fnv;
+///
+/// use lz_fnv::FnvExt;
+///
+/// fnv_oneshot!(pub u64, 0x100_0000_01b3, 0xcbf2_9ce4_8422_2325 => fnv0_64, fnv1_64, fnv1a_64);
+///
+/// // FNV defines no 16-bit parameters, but any may be given
+/// fnv_oneshot!(u16, 0x0101, 0x1cd9 => fnv0_16, fnv1_16, fnv1a_16);
+///
+/// # fn main() {
+/// const FOOBAR: u64 = fnv1a_64(b"foobar");
+///
+/// assert_eq!(FOOBAR, b"foobar".fnv1a_64());
+/// assert_eq!(fnv0_16(b""), 0);
+/// # }
+/// ```
+#[macro_export]
+macro_rules! fnv_oneshot {
+    (
+        $vis: vis $type: ty,
+        $prime: expr,
+        $offset_basis: expr => $fnv0: ident,
+        $fnv1: ident,
+        $fnv1a: ident
+    ) => {
+        #[doc = concat!("Returns the FNV-0 hash of `bytes` as a `", stringify!($type), "`.")]
+        $vis const fn $fnv0(bytes: &[u8]) -> $type {
+            let mut hash: $type = 0;
+            let mut i = 0;
+
+            while i < bytes.len() {
+                hash = hash.wrapping_mul($prime) ^ bytes[i] as $type;
+                i += 1;
+            }
+
+            hash
+        }
+
+        #[doc = concat!("Returns the FNV-1 hash of `bytes` as a `", stringify!($type), "`.")]
+        $vis const fn $fnv1(bytes: &[u8]) -> $type {
+            let mut hash: $type = $offset_basis;
+            let mut i = 0;
+
+            while i < bytes.len() {
+                hash = hash.wrapping_mul($prime) ^ bytes[i] as $type;
+                i += 1;
+            }
+
+            hash
+        }
+
+        #[doc = concat!("Returns the FNV-1a hash of `bytes` as a `", stringify!($type), "`.")]
+        $vis const fn $fnv1a(bytes: &[u8]) -> $type {
+            let mut hash: $type = $offset_basis;
+            let mut i = 0;
+
+            while i < bytes.len() {
+                hash = (hash ^ bytes[i] as $type).wrapping_mul($prime);
+                i += 1;
+            }
+
+            hash
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use FnvExt;
+
+    fnv_oneshot!(u32, 0x0100_0193, 0x811c_9dc5 => fnv0_32, fnv1_32, fnv1a_32);
+    fnv_oneshot!(pub(crate) u64, 0x100_0000_01b3, 0xcbf2_9ce4_8422_2325 => fnv0_64, fnv1_64, fnv1a_64);
+    fnv_oneshot!(
+        u128,
+        0x0000_0000_0100_0000_0000_0000_0000_013b,
+        0x6c62_272e_07bb_0142_62b8_2175_6295_c58d => fnv0_128, fnv1_128, fnv1a_128
+    );
+
+    const INPUTS: [&[u8]; 5] = [b"", b"a", b"foobar", b"chongo was here!\n", &[0xff; 300]];
+
+    #[test]
+    fn matches_built_in_one_shots() {
+        for input in &INPUTS {
+            assert_eq!(fnv0_32(input), input.fnv0_32());
+            assert_eq!(fnv1_32(input), input.fnv1_32());
+            assert_eq!(fnv1a_32(input), input.fnv1a_32());
+            assert_eq!(fnv0_64(input), input.fnv0_64());
+            assert_eq!(fnv1_64(input), input.fnv1_64());
+            assert_eq!(fnv1a_64(input), input.fnv1a_64());
+            assert_eq!(fnv0_128(input), input.fnv0_128());
+            assert_eq!(fnv1_128(input), input.fnv1_128());
+            assert_eq!(fnv1a_128(input), input.fnv1a_128());
+        }
+    }
+
+    #[test]
+    fn usable_in_const_context() {
+        const FOOBAR: u64 = fnv1a_64(b"foobar");
+
+        assert_eq!(FOOBAR, 0x8594_4171_f739_67e8);
+    }
+}
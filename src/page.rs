@@ -0,0 +1,250 @@
+//! Checksums of fixed-size pages which embed their own checksum.
+//!
+//! A page's checksum is FNV-1a over the whole page with the checksum field,
+//! `page[offset..offset + 4]` for the 32-bit checksum or
+//! `page[offset..offset + 8]` for the 64-bit checksum, replaced by zero
+//! bytes. The field itself stores the checksum as little-endian bytes.
+//!
+//! The offset must lie within the page and be a multiple of the field's
+//! width.
+
+use std::error::Error;
+use std::fmt;
+
+use {Fnv1a, FnvHash};
+
+/// An error locating the checksum field within a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageError {
+    /// The checksum field extends past the end of the page.
+    OffsetOutOfRange {
+        /// The offset of the checksum field.
+        offset: usize,
+        /// The length of the page.
+        page_len: usize,
+    },
+    /// The checksum field is not aligned to its width.
+    UnalignedOffset {
+        /// The offset of the checksum field.
+        offset: usize,
+        /// The required alignment.
+        alignment: usize,
+    },
+}
+
+impl fmt::Display for PageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PageError::OffsetOutOfRange { offset, page_len } => write!(
+                f,
+                "checksum offset {} is out of range for a page of {} bytes",
+                offset, page_len
+            ),
+            PageError::UnalignedOffset { offset, alignment } => write!(
+                f,
+                "checksum offset {} is not a multiple of {}",
+                offset, alignment
+            ),
+        }
+    }
+}
+
+impl Error for PageError {}
+
+fn check_field(page: &[u8], offset: usize, width: usize) -> Result<(), PageError> {
+    if !offset.is_multiple_of(width) {
+        return Err(PageError::UnalignedOffset {
+            offset,
+            alignment: width,
+        });
+    }
+
+    match offset.checked_add(width) {
+        Some(end) if end <= page.len() => Ok(()),
+        _ => Err(PageError::OffsetOutOfRange {
+            offset,
+            page_len: page.len(),
+        }),
+    }
+}
+
+fn hash_around<H: FnvHash>(
+    mut hasher: H,
+    page: &[u8],
+    offset: usize,
+    zeros: &[u8],
+) -> Result<H::Hash, PageError> {
+    check_field(page, offset, zeros.len())?;
+
+    hasher.write(&page[..offset]);
+    hasher.write(zeros);
+    hasher.write(&page[offset + zeros.len()..]);
+
+    Ok(hasher.finish())
+}
+
+/// Computes the FNV-1a 32 checksum of a page, treating the 4 bytes at
+/// `checksum_offset` as zeros.
+///
+/// ```
+/// use lz_fnv::{page_checksum, verify_page};
+///
+/// let mut page = [0xA5u8; 64];
+/// let checksum = page_checksum(&page, 8).unwrap();
+/// page[8..12].copy_from_slice(&checksum.to_le_bytes());
+///
+/// assert_eq!(verify_page(&page, 8), Ok(true));
+/// ```
+pub fn page_checksum(page: &[u8], checksum_offset: usize) -> Result<u32, PageError> {
+    hash_around(Fnv1a::<u32>::new(), page, checksum_offset, &[0; 4])
+}
+
+/// Verifies the little-endian FNV-1a 32 checksum stored at `checksum_offset`.
+pub fn verify_page(page: &[u8], checksum_offset: usize) -> Result<bool, PageError> {
+    let checksum = page_checksum(page, checksum_offset)?;
+    let stored = &page[checksum_offset..checksum_offset + 4];
+
+    Ok(stored == checksum.to_le_bytes())
+}
+
+/// Computes the FNV-1a 64 checksum of a page, treating the 8 bytes at
+/// `checksum_offset` as zeros.
+pub fn page_checksum_64(page: &[u8], checksum_offset: usize) -> Result<u64, PageError> {
+    hash_around(Fnv1a::<u64>::new(), page, checksum_offset, &[0; 8])
+}
+
+/// Verifies the little-endian FNV-1a 64 checksum stored at `checksum_offset`.
+pub fn verify_page_64(page: &[u8], checksum_offset: usize) -> Result<bool, PageError> {
+    let checksum = page_checksum_64(page, checksum_offset)?;
+    let stored = &page[checksum_offset..checksum_offset + 8];
+
+    Ok(stored == checksum.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use {page_checksum, page_checksum_64, verify_page, verify_page_64, Fnv1a, FnvHash, PageError};
+
+    const PAGE_SIZE: usize = 256;
+
+    fn page() -> [u8; PAGE_SIZE] {
+        let mut page = [0; PAGE_SIZE];
+        for (i, byte) in page.iter_mut().enumerate() {
+            *byte = (i * 7 + 3) as u8;
+        }
+        page
+    }
+
+    fn sealed_page(offset: usize) -> [u8; PAGE_SIZE] {
+        let mut page = page();
+        let checksum = page_checksum(&page, offset).unwrap();
+        page[offset..offset + 4].copy_from_slice(&checksum.to_le_bytes());
+        page
+    }
+
+    fn sealed_page_64(offset: usize) -> [u8; PAGE_SIZE] {
+        let mut page = page();
+        let checksum = page_checksum_64(&page, offset).unwrap();
+        page[offset..offset + 8].copy_from_slice(&checksum.to_le_bytes());
+        page
+    }
+
+    #[test]
+    fn checksum_treats_field_as_zeros() {
+        let mut page = page();
+        page[16..20].copy_from_slice(&[0; 4]);
+
+        let mut hasher = Fnv1a::<u32>::new();
+        hasher.write(&page);
+
+        page[16..20].copy_from_slice(&[1, 2, 3, 4]);
+
+        assert_eq!(page_checksum(&page, 16), Ok(hasher.finish()));
+    }
+
+    #[test]
+    fn round_trip() {
+        for &offset in &[0, 4, 128, PAGE_SIZE - 4] {
+            assert_eq!(verify_page(&sealed_page(offset), offset), Ok(true));
+        }
+        for &offset in &[0, 8, 128, PAGE_SIZE - 8] {
+            assert_eq!(verify_page_64(&sealed_page_64(offset), offset), Ok(true));
+        }
+    }
+
+    #[test]
+    fn unsealed_page_fails() {
+        assert_eq!(verify_page(&page(), 0), Ok(false));
+        assert_eq!(verify_page_64(&page(), 0), Ok(false));
+    }
+
+    #[test]
+    fn detects_single_bit_flips() {
+        let sealed = sealed_page(32);
+        let sealed_64 = sealed_page_64(32);
+
+        for bit in 0..PAGE_SIZE * 8 {
+            let mut page = sealed;
+            page[bit / 8] ^= 1 << (bit % 8);
+            assert_eq!(verify_page(&page, 32), Ok(false), "bit {}", bit);
+
+            let mut page = sealed_64;
+            page[bit / 8] ^= 1 << (bit % 8);
+            assert_eq!(verify_page_64(&page, 32), Ok(false), "bit {}", bit);
+        }
+    }
+
+    #[test]
+    fn offset_out_of_range() {
+        let page = page();
+        let error = PageError::OffsetOutOfRange {
+            offset: PAGE_SIZE,
+            page_len: PAGE_SIZE,
+        };
+
+        assert_eq!(page_checksum(&page, PAGE_SIZE), Err(error));
+        assert_eq!(verify_page(&page, PAGE_SIZE), Err(error));
+        assert_eq!(page_checksum_64(&page, PAGE_SIZE), Err(error));
+        assert_eq!(
+            page_checksum(&page[..2], 0),
+            Err(PageError::OffsetOutOfRange {
+                offset: 0,
+                page_len: 2
+            })
+        );
+        assert!(page_checksum(&page, usize::MAX - 3).is_err());
+    }
+
+    #[test]
+    fn offset_unaligned() {
+        let page = page();
+
+        assert_eq!(
+            page_checksum(&page, 6),
+            Err(PageError::UnalignedOffset {
+                offset: 6,
+                alignment: 4
+            })
+        );
+        assert_eq!(
+            verify_page_64(&page, 12),
+            Err(PageError::UnalignedOffset {
+                offset: 12,
+                alignment: 8
+            })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn error_messages() {
+        assert_eq!(
+            PageError::UnalignedOffset {
+                offset: 6,
+                alignment: 4
+            }
+            .to_string(),
+            "checksum offset 6 is not a multiple of 4"
+        );
+    }
+}
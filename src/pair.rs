@@ -0,0 +1,91 @@
+//! Hashing pairs of byte strings.
+
+use {Fnv1a, FnvHash};
+
+/// Computes FNV-1a 64 over `a`, then `sep`, then `b`, without building the
+/// concatenated buffer.
+///
+/// The separator distinguishes pairs such as `("ab", "c")` and `("a", "bc")`,
+/// provided it never occurs in `a`. Use [`series_id`]-style length framing
+/// where no such byte exists.
+///
+/// [`series_id`]: fn.series_id.html
+///
+/// ```
+/// use lz_fnv::{hash_pair, Fnv1a, FnvHash};
+///
+/// let mut fnv_hasher = Fnv1a::<u64>::new();
+/// fnv_hasher.write(b"key=value");
+///
+/// assert_eq!(hash_pair(b"key", b'=', b"value"), fnv_hasher.finish());
+/// ```
+pub fn hash_pair(a: &[u8], sep: u8, b: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(a);
+    hasher.write(&[sep]);
+    hasher.write(b);
+    hasher.finish()
+}
+
+/// Computes FNV-1a 64 over `salt`, then `data`, without building the
+/// concatenated buffer.
+///
+/// Rotating the salt changes the hash of every input, such as for rate
+/// limiter keys, and an empty salt gives the plain hash of `data`. The salt
+/// is not separated from the data, so a salt of varying length can make
+/// `("ab", "c")` and `("a", "bc")` collide; use salts of a fixed length.
+///
+/// ```
+/// use lz_fnv::{hash_salted, FnvExt};
+///
+/// assert_ne!(hash_salted(b"2024-06", b"user-42"), hash_salted(b"2024-07", b"user-42"));
+/// assert_eq!(hash_salted(b"", b"user-42"), b"user-42".fnv1a_64());
+/// ```
+pub fn hash_salted(salt: &[u8], data: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(salt);
+    hasher.write(data);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use {hash_pair, hash_salted, Fnv1a, FnvExt, FnvHash};
+
+    #[test]
+    fn separator_distinguishes_split() {
+        assert_ne!(hash_pair(b"ab", 0, b"c"), hash_pair(b"a", 0, b"bc"));
+        assert_ne!(hash_pair(b"", b':', b"abc"), hash_pair(b"abc", b':', b""));
+    }
+
+    #[test]
+    fn equals_concatenation() {
+        let mut hasher = Fnv1a::<u64>::new();
+        hasher.write(b"ab\0c");
+
+        assert_eq!(hash_pair(b"ab", 0, b"c"), hasher.finish());
+    }
+
+    #[test]
+    fn separator_is_hashed() {
+        assert_ne!(hash_pair(b"a", b':', b"b"), hash_pair(b"a", b'=', b"b"));
+    }
+
+    #[test]
+    fn salts_change_the_hash() {
+        let salts: [&[u8]; 4] = [b"", b"\0", b"salt-1", b"salt-2"];
+
+        for (i, a) in salts.iter().enumerate() {
+            for b in &salts[i + 1..] {
+                assert_ne!(hash_salted(a, b"user-42"), hash_salted(b, b"user-42"));
+            }
+        }
+    }
+
+    #[test]
+    fn salted_equals_concatenation() {
+        assert_eq!(hash_salted(b"", b"foobar"), 0x8594_4171_f739_67e8);
+        assert_eq!(hash_salted(b"foo", b"bar"), b"foobar".fnv1a_64());
+        assert_eq!(hash_salted(b"foobar", b""), b"foobar".fnv1a_64());
+    }
+}
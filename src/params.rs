@@ -0,0 +1,237 @@
+//! Deriving and validating FNV parameters per the specification.
+
+use std::error::Error;
+use std::fmt;
+
+use step::{fnv1_128_advance, fnv1_32_advance, fnv1_64_advance};
+use FnvWidth;
+
+/// The signature hashed with FNV-0 to produce each offset basis.
+const SIGNATURE: &[u8; 32] = b"chongo <Landon Curt Noll> /\\../\\";
+
+/// Derives the FNV offset basis for the specified prime.
+///
+/// The specification defines the offset basis as the FNV-0 hash, computed
+/// with the prime, of the 32-byte signature `chongo <Landon Curt Noll>
+/// /\../\`.
+///
+/// ```
+/// use lz_fnv::derive_offset_basis;
+///
+/// assert_eq!(derive_offset_basis(0x100_0000_01b3u64), 0xcbf2_9ce4_8422_2325);
+/// ```
+pub fn derive_offset_basis<W: FnvWidth>(prime: W) -> W {
+    SIGNATURE.iter().fold(W::ZERO, |hash, &byte| {
+        hash.wrapping_mul(prime).xor_byte(byte)
+    })
+}
+
+/// Derives the FNV-1 and FNV-1a 32 offset basis from the signature at
+/// compile time.
+///
+/// ```
+/// use lz_fnv::const_offset_basis_u32;
+///
+/// const OFFSET_BASIS: u32 = const_offset_basis_u32();
+///
+/// assert_eq!(OFFSET_BASIS, 0x811c_9dc5);
+/// ```
+pub const fn const_offset_basis_u32() -> u32 {
+    fnv1_32_advance(0, SIGNATURE)
+}
+
+/// Derives the FNV-1 and FNV-1a 64 offset basis from the signature at
+/// compile time.
+///
+/// ```
+/// use lz_fnv::const_offset_basis_u64;
+///
+/// const OFFSET_BASIS: u64 = const_offset_basis_u64();
+///
+/// assert_eq!(OFFSET_BASIS, 0xcbf2_9ce4_8422_2325);
+/// ```
+pub const fn const_offset_basis_u64() -> u64 {
+    fnv1_64_advance(0, SIGNATURE)
+}
+
+/// Derives the FNV-1 and FNV-1a 128 offset basis from the signature at
+/// compile time.
+pub const fn const_offset_basis_u128() -> u128 {
+    fnv1_128_advance(0, SIGNATURE)
+}
+
+// Guards the offset bases the hashers start from.
+const _: () = assert!(const_offset_basis_u32() == 0x811c_9dc5);
+const _: () = assert!(const_offset_basis_u64() == 0xcbf2_9ce4_8422_2325);
+const _: () = assert!(const_offset_basis_u128() == 0x6C62_272E_07BB_0142_62B8_2175_6295_C58D);
+const _: () = assert!(const_offset_basis_u32() == <u32 as FnvWidth>::OFFSET_BASIS);
+const _: () = assert!(const_offset_basis_u64() == <u64 as FnvWidth>::OFFSET_BASIS);
+const _: () = assert!(const_offset_basis_u128() == <u128 as FnvWidth>::OFFSET_BASIS);
+
+/// An error validating an FNV prime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimeError {
+    /// The width is not a power of two from 32 to 128 bits.
+    UnsupportedWidth(u32),
+    /// The prime is not of the form `256^t + 2^8 + b` with `0 <= b < 256`.
+    NotOfForm,
+    /// The low byte `b` of the prime is zero or does not have 4 or 5 bits set.
+    InvalidLowByte(u8),
+    /// The prime modulo `2^40 - 2^24 - 1` is not greater than
+    /// `2^24 + 2^8 + 2^7`.
+    ResidueTooSmall,
+}
+
+impl fmt::Display for PrimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PrimeError::UnsupportedWidth(bits) => write!(f, "unsupported FNV width {}", bits),
+            PrimeError::NotOfForm => write!(f, "prime is not of the form 256^t + 2^8 + b"),
+            PrimeError::InvalidLowByte(byte) => {
+                write!(
+                    f,
+                    "low byte {:#04x} must be nonzero with 4 or 5 bits set",
+                    byte
+                )
+            }
+            PrimeError::ResidueTooSmall => {
+                write!(f, "prime modulo 2^40 - 2^24 - 1 is too small")
+            }
+        }
+    }
+}
+
+impl Error for PrimeError {}
+
+/// Checks that a prime meets the structural conditions the specification
+/// places on FNV primes of the specified width.
+///
+/// For a width of `2^s` bits, with `s >= 5`, the prime must be
+/// `256^t + 2^8 + b` where `t = (5 + 2^s) / 12` rounded down, `0 < b < 2^8`,
+/// `b` has 4 or 5 bits set, and the prime modulo `2^40 - 2^24 - 1` is greater
+/// than `2^24 + 2^8 + 2^7`. Widths up to 128 bits are supported.
+///
+/// Primality itself is not checked.
+///
+/// ```
+/// use lz_fnv::{validate_prime, PrimeError};
+///
+/// assert_eq!(validate_prime(64, 0x100_0000_01b3), Ok(()));
+/// assert_eq!(validate_prime(64, 0x100_0000_01b0), Err(PrimeError::InvalidLowByte(0xb0)));
+/// ```
+pub fn validate_prime(width_bits: u32, prime: u128) -> Result<(), PrimeError> {
+    if !width_bits.is_power_of_two() || !(32..=128).contains(&width_bits) {
+        return Err(PrimeError::UnsupportedWidth(width_bits));
+    }
+
+    let t = (5 + width_bits) / 12;
+    let base = (1u128 << (8 * t)) + (1 << 8);
+    let low = match prime.checked_sub(base) {
+        Some(low) if low < 0x100 => low as u8,
+        _ => return Err(PrimeError::NotOfForm),
+    };
+
+    let weight = low.count_ones();
+    if low == 0 || (weight != 4 && weight != 5) {
+        return Err(PrimeError::InvalidLowByte(low));
+    }
+
+    let modulus = (1u128 << 40) - (1 << 24) - 1;
+    if prime % modulus <= (1 << 24) + (1 << 8) + (1 << 7) {
+        return Err(PrimeError::ResidueTooSmall);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use step::{PRIME_128, PRIME_32, PRIME_64};
+    use {derive_offset_basis, validate_prime, Fnv1, Fnv1a, FnvHash, PrimeError};
+
+    #[test]
+    fn derives_built_in_offset_bases() {
+        assert_eq!(derive_offset_basis(PRIME_32), Fnv1::<u32>::new().finish());
+        assert_eq!(derive_offset_basis(PRIME_64), Fnv1::<u64>::new().finish());
+        assert_eq!(derive_offset_basis(PRIME_128), Fnv1::<u128>::new().finish());
+
+        assert_eq!(derive_offset_basis(PRIME_32), Fnv1a::<u32>::new().finish());
+        assert_eq!(derive_offset_basis(PRIME_64), Fnv1a::<u64>::new().finish());
+        assert_eq!(
+            derive_offset_basis(PRIME_128),
+            Fnv1a::<u128>::new().finish()
+        );
+    }
+
+    #[test]
+    fn built_in_primes_are_valid() {
+        assert_eq!(validate_prime(32, u128::from(PRIME_32)), Ok(()));
+        assert_eq!(validate_prime(64, u128::from(PRIME_64)), Ok(()));
+        assert_eq!(validate_prime(128, PRIME_128), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unsupported_widths() {
+        for &bits in &[0, 16, 48, 256] {
+            assert_eq!(
+                validate_prime(bits, u128::from(PRIME_64)),
+                Err(PrimeError::UnsupportedWidth(bits))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_wrong_form() {
+        assert_eq!(
+            validate_prime(64, u128::from(PRIME_32)),
+            Err(PrimeError::NotOfForm)
+        );
+        assert_eq!(
+            validate_prime(32, u128::from(PRIME_64)),
+            Err(PrimeError::NotOfForm)
+        );
+        assert_eq!(
+            validate_prime(64, 0x100_0000_00b3),
+            Err(PrimeError::NotOfForm)
+        );
+        assert_eq!(validate_prime(64, 0), Err(PrimeError::NotOfForm));
+    }
+
+    #[test]
+    fn rejects_invalid_low_bytes() {
+        assert_eq!(
+            validate_prime(64, 0x100_0000_0100),
+            Err(PrimeError::InvalidLowByte(0))
+        );
+        assert_eq!(
+            validate_prime(64, 0x100_0000_0107),
+            Err(PrimeError::InvalidLowByte(0x07))
+        );
+        assert_eq!(
+            validate_prime(64, 0x100_0000_01bf),
+            Err(PrimeError::InvalidLowByte(0xbf))
+        );
+    }
+
+    #[test]
+    fn rejects_small_residues() {
+        assert_eq!(
+            validate_prime(32, 0x100_010f),
+            Err(PrimeError::ResidueTooSmall)
+        );
+        assert_eq!(validate_prime(32, 0x100_0193), Ok(()));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn error_messages() {
+        assert_eq!(
+            PrimeError::UnsupportedWidth(48).to_string(),
+            "unsupported FNV width 48"
+        );
+        assert_eq!(
+            PrimeError::InvalidLowByte(0x07).to_string(),
+            "low byte 0x07 must be nonzero with 4 or 5 bits set"
+        );
+    }
+}
@@ -0,0 +1,443 @@
+//! Hash partitioning of keyed records across several outputs or groups.
+
+use std::io::{self, Write};
+
+use {Fnv1a, FnvHash};
+
+/// Reduces a 64-bit hash into the range `0..n`.
+///
+/// This is the fixed-point multiply reduction `(hash * n) >> 64`, which maps
+/// the hash onto the partitions using its high bits. The high bits of an FNV
+/// hash are better mixed than the low bits a modulo would select, and every
+/// partition receives either `floor(2^64 / n)` or `ceil(2^64 / n)` of the
+/// possible hash values, so the bias is at most one part in `2^64 / n`.
+fn reduce(hash: u64, n: usize) -> usize {
+    ((u128::from(hash) * n as u128) >> 64) as usize
+}
+
+/// Returns the partition in `0..n` for the specified key.
+///
+/// The key is hashed with FNV-1a 64 and reduced with a fixed-point multiply,
+/// `(hash * n) >> 64`. Both steps are fixed, so a key always maps to the same
+/// partition for a given `n`, across runs and platforms.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+///
+/// ```
+/// use lz_fnv::partition_for;
+///
+/// let partition = partition_for(b"customer-42", 8);
+///
+/// assert!(partition < 8);
+/// assert_eq!(partition, partition_for(b"customer-42", 8));
+/// ```
+pub fn partition_for(key: &[u8], n: usize) -> usize {
+    partition_with(Fnv1a::<u64>::new(), key, n)
+}
+
+fn partition_with<H: FnvHash<Hash = u64>>(mut hasher: H, key: &[u8], n: usize) -> usize {
+    assert!(n > 0, "the number of partitions must be greater than zero");
+
+    hasher.write(key);
+    reduce(hasher.finish(), n)
+}
+
+/// Splits `items` into `n` groups by the FNV-1a 64 hash of each item's key.
+///
+/// Each item is placed in group [`partition_for`]`(key(&item), n)`, so items
+/// with equal keys always land in the same group, and the groups agree with
+/// [`PartitionedWriter`]. Items keep their relative order within a group.
+/// Exactly `n` groups are returned, some of which may be empty.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+///
+/// ```
+/// use lz_fnv::{partition_by_hash, partition_for};
+///
+/// let users = vec!["ada", "grace", "ada", "edsger"];
+///
+/// let groups = partition_by_hash(users, 4, |user| user.as_bytes());
+///
+/// assert_eq!(groups.len(), 4);
+/// assert_eq!(groups[partition_for(b"ada", 4)].iter().filter(|&&u| u == "ada").count(), 2);
+/// ```
+///
+/// [`partition_for`]: fn.partition_for.html
+/// [`PartitionedWriter`]: struct.PartitionedWriter.html
+pub fn partition_by_hash<I, F>(items: I, n: usize, key: F) -> Vec<Vec<I::Item>>
+where
+    I: IntoIterator,
+    F: Fn(&I::Item) -> &[u8],
+{
+    assert!(n > 0, "the number of partitions must be greater than zero");
+
+    let mut groups: Vec<Vec<I::Item>> = (0..n).map(|_| Vec::new()).collect();
+    for item in items {
+        let partition = partition_for(key(&item), n);
+        groups[partition].push(item);
+    }
+
+    groups
+}
+
+/// Returns the group in `0..n` of each of `items`, as [`partition_by_hash`]
+/// would place it, without moving the items.
+///
+/// The assignment at index `i` is [`partition_for`]`(key(&items[i]), n)`.
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+///
+/// ```
+/// use lz_fnv::{partition_for, partition_indices_by_hash};
+///
+/// let users = ["ada", "grace"];
+///
+/// let assignments = partition_indices_by_hash(&users, 4, |user| user.as_bytes());
+///
+/// assert_eq!(assignments, [partition_for(b"ada", 4), partition_for(b"grace", 4)]);
+/// ```
+///
+/// [`partition_by_hash`]: fn.partition_by_hash.html
+/// [`partition_for`]: fn.partition_for.html
+pub fn partition_indices_by_hash<T, F>(items: &[T], n: usize, key: F) -> Vec<usize>
+where
+    F: Fn(&T) -> &[u8],
+{
+    assert!(n > 0, "the number of partitions must be greater than zero");
+
+    items
+        .iter()
+        .map(|item| partition_for(key(item), n))
+        .collect()
+}
+
+/// A writer which fans records out across several outputs by key hash.
+///
+/// Each record is written in full to the output selected by hashing its key,
+/// so records sharing a key always land in the same output. With the default
+/// FNV-1a 64 hasher the selection matches [`partition_for`].
+///
+/// [`partition_for`]: fn.partition_for.html
+#[derive(Debug)]
+pub struct PartitionedWriter<W, H = Fnv1a<u64>> {
+    writers: Vec<W>,
+    hasher: H,
+}
+
+impl<W: Write> PartitionedWriter<W> {
+    /// Creates a new `PartitionedWriter` which partitions by FNV-1a 64.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `writers` is empty.
+    ///
+    /// ```
+    /// use lz_fnv::PartitionedWriter;
+    ///
+    /// let writer = PartitionedWriter::new(vec![Vec::new(), Vec::new()]);
+    /// ```
+    pub fn new(writers: Vec<W>) -> Self {
+        Self::with_hasher(writers, Fnv1a::<u64>::new())
+    }
+}
+
+impl<W: Write, H: FnvHash<Hash = u64> + Clone> PartitionedWriter<W, H> {
+    /// Creates a new `PartitionedWriter` which partitions using the specified
+    /// hasher.
+    ///
+    /// Every key is hashed by a clone of `hasher`, so a keyed hasher may be
+    /// supplied to vary the partitioning.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `writers` is empty.
+    ///
+    /// ```
+    /// use lz_fnv::{Fnv1, PartitionedWriter};
+    ///
+    /// let writer = PartitionedWriter::with_hasher(vec![Vec::new()], Fnv1::<u64>::new());
+    /// ```
+    pub fn with_hasher(writers: Vec<W>, hasher: H) -> Self {
+        assert!(
+            !writers.is_empty(),
+            "a PartitionedWriter requires at least one writer"
+        );
+
+        Self { writers, hasher }
+    }
+
+    /// Writes a record to the output selected by its key.
+    ///
+    /// The whole record is written, returning the index of the output it was
+    /// written to.
+    ///
+    /// ```
+    /// use lz_fnv::{partition_for, PartitionedWriter};
+    ///
+    /// let mut writer = PartitionedWriter::new(vec![Vec::new(), Vec::new()]);
+    ///
+    /// let partition = writer.write_record(b"key", b"record").unwrap();
+    ///
+    /// assert_eq!(partition, partition_for(b"key", 2));
+    /// ```
+    pub fn write_record(&mut self, key: &[u8], record: &[u8]) -> io::Result<usize> {
+        let partition = partition_with(self.hasher.clone(), key, self.writers.len());
+
+        self.writers[partition].write_all(record)?;
+
+        Ok(partition)
+    }
+
+    /// Flushes every output.
+    pub fn flush(&mut self) -> io::Result<()> {
+        for writer in &mut self.writers {
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets a reference to the underlying writers.
+    pub fn get_ref(&self) -> &[W] {
+        &self.writers
+    }
+
+    /// Consumes this `PartitionedWriter`, returning the underlying writers.
+    pub fn into_inner(self) -> Vec<W> {
+        self.writers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use {
+        partition_by_hash, partition_for, partition_indices_by_hash, Fnv1, FnvHash,
+        PartitionedWriter,
+    };
+
+    struct FailingWriter;
+
+    impl Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("failing writer"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn key(index: u64) -> [u8; 8] {
+        // A fixed LCG keeps the "random" keys reproducible
+        index
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407)
+            .to_le_bytes()
+    }
+
+    #[test]
+    fn equal_keys_land_together() {
+        let mut writer = PartitionedWriter::new(vec![Vec::new(); 7]);
+
+        for _ in 0..3 {
+            writer.write_record(b"alpha", b"a").unwrap();
+            writer.write_record(b"beta", b"b").unwrap();
+        }
+
+        let writers = writer.into_inner();
+
+        assert_eq!(
+            writers[partition_for(b"alpha", 7)]
+                .iter()
+                .filter(|&&b| b == b'a')
+                .count(),
+            3
+        );
+        assert_eq!(
+            writers[partition_for(b"beta", 7)]
+                .iter()
+                .filter(|&&b| b == b'b')
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn matches_partition_for() {
+        let mut writer = PartitionedWriter::new(vec![Vec::new(); 5]);
+
+        for i in 0..100 {
+            let key = key(i);
+            assert_eq!(
+                writer.write_record(&key, &key).unwrap(),
+                partition_for(&key, 5)
+            );
+        }
+    }
+
+    #[test]
+    fn partition_for_is_stable() {
+        assert_eq!(partition_for(b"", 16), 12);
+        assert_eq!(partition_for(b"foobar", 16), 8);
+        assert_eq!(partition_for(b"foobar", 1), 0);
+    }
+
+    #[test]
+    fn distribution_is_roughly_even() {
+        const PARTITIONS: usize = 8;
+        const KEYS: u64 = 80_000;
+
+        let mut counts = [0usize; PARTITIONS];
+        for i in 0..KEYS {
+            counts[partition_for(&key(i), PARTITIONS)] += 1;
+        }
+
+        let expected = KEYS as usize / PARTITIONS;
+        for &count in &counts {
+            assert!(
+                count > expected * 9 / 10 && count < expected * 11 / 10,
+                "{:?}",
+                counts
+            );
+        }
+    }
+
+    #[test]
+    fn records_are_written_whole() {
+        let mut writer = PartitionedWriter::new(vec![Vec::new()]);
+
+        writer.write_record(b"key", b"first,").unwrap();
+        writer.write_record(b"other", b"second").unwrap();
+
+        assert_eq!(writer.into_inner(), vec![b"first,second".to_vec()]);
+    }
+
+    #[test]
+    fn custom_hasher_selects_partition() {
+        let mut writer = PartitionedWriter::with_hasher(vec![Vec::new(); 4], Fnv1::<u64>::new());
+
+        let mut hasher = Fnv1::<u64>::new();
+        hasher.write(b"key");
+        let expected = ((u128::from(hasher.finish()) * 4) >> 64) as usize;
+
+        assert_eq!(writer.write_record(b"key", b"record").unwrap(), expected);
+    }
+
+    #[test]
+    fn writer_errors_surface() {
+        let mut writer = PartitionedWriter::new(vec![FailingWriter, FailingWriter]);
+
+        let error = writer.write_record(b"key", b"record").unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::Other);
+        assert_eq!(error.to_string(), "failing writer");
+    }
+
+    #[test]
+    #[should_panic]
+    fn zero_partitions_panics() {
+        partition_for(b"key", 0);
+    }
+
+    #[test]
+    fn partition_by_hash_matches_partition_for() {
+        let keys: Vec<_> = (0..1_000).map(key).collect();
+
+        let groups = partition_by_hash(keys.clone(), 6, |key| &key[..]);
+        let assignments = partition_indices_by_hash(&keys, 6, |key| &key[..]);
+
+        assert_eq!(groups.len(), 6);
+        assert_eq!(groups.iter().map(Vec::len).sum::<usize>(), keys.len());
+        for (partition, group) in groups.iter().enumerate() {
+            for key in group {
+                assert_eq!(partition_for(key, 6), partition);
+            }
+        }
+        for (key, &partition) in keys.iter().zip(&assignments) {
+            assert_eq!(partition_for(key, 6), partition);
+        }
+    }
+
+    #[test]
+    fn partition_by_hash_keeps_equal_keys_and_order_together() {
+        let records = vec![
+            ("alpha", 1),
+            ("beta", 2),
+            ("alpha", 3),
+            ("gamma", 4),
+            ("alpha", 5),
+        ];
+
+        let groups = partition_by_hash(records, 3, |record| record.0.as_bytes());
+
+        let alpha: Vec<_> = groups[partition_for(b"alpha", 3)]
+            .iter()
+            .filter(|record| record.0 == "alpha")
+            .map(|record| record.1)
+            .collect();
+        assert_eq!(alpha, [1, 3, 5]);
+    }
+
+    #[test]
+    fn partition_by_hash_is_stable() {
+        let groups = partition_by_hash(vec![&b""[..], b"foobar"], 16, |key| key);
+
+        assert_eq!(groups[12], [&b""[..]]);
+        assert_eq!(groups[8], [&b"foobar"[..]]);
+        assert_eq!(
+            partition_indices_by_hash(&[&b""[..], b"foobar"], 16, |key| key),
+            [12, 8]
+        );
+    }
+
+    #[test]
+    fn partition_by_hash_is_roughly_even() {
+        const PARTITIONS: usize = 8;
+        const KEYS: u64 = 80_000;
+
+        let groups = partition_by_hash((0..KEYS).map(key), PARTITIONS, |key| &key[..]);
+
+        let expected = KEYS as usize / PARTITIONS;
+        for group in &groups {
+            assert!(
+                group.len() > expected * 9 / 10 && group.len() < expected * 11 / 10,
+                "{:?}",
+                groups.iter().map(Vec::len).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn partition_by_hash_single_partition() {
+        let keys: Vec<_> = (0..10).map(key).collect();
+
+        assert_eq!(
+            partition_by_hash(keys.clone(), 1, |key| &key[..]),
+            vec![keys.clone()]
+        );
+        assert_eq!(partition_indices_by_hash(&keys, 1, |key| &key[..]), [0; 10]);
+        assert_eq!(
+            partition_by_hash(Vec::<[u8; 8]>::new(), 3, |key| &key[..]),
+            [Vec::<[u8; 8]>::new(), Vec::new(), Vec::new()]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "the number of partitions must be greater than zero")]
+    fn partition_by_hash_zero_partitions_panics() {
+        partition_by_hash(vec![key(0)], 0, |key| &key[..]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the number of partitions must be greater than zero")]
+    fn partition_indices_by_hash_zero_partitions_panics() {
+        partition_indices_by_hash(&[key(0)], 0, |key| &key[..]);
+    }
+}
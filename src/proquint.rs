@@ -0,0 +1,321 @@
+//! Proquint encoding of hashes, for reading them aloud.
+//!
+//! Each 16-bit group of a value is written as a five letter word of
+//! alternating consonants and vowels, most significant group first, with
+//! dashes between the words. See <https://arxiv.org/abs/0901.4016>.
+
+use std::error::Error;
+use std::fmt;
+
+const CONSONANTS: &[u8; 16] = b"bdfghjklmnprstvz";
+const VOWELS: &[u8; 4] = b"aiou";
+
+/// An error decoding a proquint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProquintError {
+    /// The proquint has the wrong number of dash-separated words.
+    WrongWordCount {
+        /// The number of words for the type being decoded.
+        expected: usize,
+        /// The number of words found.
+        found: usize,
+    },
+    /// A word is not five characters long.
+    WrongWordLength {
+        /// The index of the word.
+        word: usize,
+        /// The number of characters in the word.
+        len: usize,
+    },
+    /// A character is not a proquint consonant or vowel as its position
+    /// requires.
+    InvalidCharacter {
+        /// The index of the word.
+        word: usize,
+        /// The index of the character within the word.
+        position: usize,
+        /// The character found.
+        found: char,
+    },
+}
+
+impl fmt::Display for ProquintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProquintError::WrongWordCount { expected, found } => {
+                write!(f, "expected {} proquint words, found {}", expected, found)
+            }
+            ProquintError::WrongWordLength { word, len } => write!(
+                f,
+                "proquint word {} has {} characters, expected 5",
+                word, len
+            ),
+            ProquintError::InvalidCharacter {
+                word,
+                position,
+                found,
+            } => write!(
+                f,
+                "invalid {} {:?} at position {} of proquint word {}",
+                if position % 2 == 0 {
+                    "consonant"
+                } else {
+                    "vowel"
+                },
+                found,
+                position,
+                word
+            ),
+        }
+    }
+}
+
+impl Error for ProquintError {}
+
+fn encode_word(word: u16, out: &mut String) {
+    let word = word as usize;
+
+    out.push(CONSONANTS[word >> 12] as char);
+    out.push(VOWELS[(word >> 10) & 0x3] as char);
+    out.push(CONSONANTS[(word >> 6) & 0xf] as char);
+    out.push(VOWELS[(word >> 4) & 0x3] as char);
+    out.push(CONSONANTS[word & 0xf] as char);
+}
+
+fn decode_word(index: usize, string: &str) -> Result<u16, ProquintError> {
+    let len = string.chars().count();
+    if len != 5 {
+        return Err(ProquintError::WrongWordLength { word: index, len });
+    }
+
+    string
+        .chars()
+        .enumerate()
+        .try_fold(0u16, |word, (position, found)| {
+            let (alphabet, bits): (&[u8], _) = if position % 2 == 0 {
+                (CONSONANTS, 4)
+            } else {
+                (VOWELS, 2)
+            };
+
+            alphabet
+                .iter()
+                .position(|&letter| letter as char == found)
+                .map(|value| (word << bits) | value as u16)
+                .ok_or(ProquintError::InvalidCharacter {
+                    word: index,
+                    position,
+                    found,
+                })
+        })
+}
+
+fn decode_words(string: &str, out: &mut [u16]) -> Result<(), ProquintError> {
+    let found = string.split('-').count();
+    if found != out.len() {
+        return Err(ProquintError::WrongWordCount {
+            expected: out.len(),
+            found,
+        });
+    }
+
+    for (index, (word, string)) in out.iter_mut().zip(string.split('-')).enumerate() {
+        *word = decode_word(index, string)?;
+    }
+
+    Ok(())
+}
+
+/// A hash which can be encoded as a proquint.
+///
+/// ```
+/// use lz_fnv::Proquint;
+///
+/// assert_eq!(0x7f00_0001u32.to_proquint(), "lusab-babad");
+/// assert_eq!(u32::from_proquint("lusab-babad"), Ok(0x7f00_0001));
+/// ```
+pub trait Proquint: Sized {
+    /// Encodes this value as a proquint.
+    fn to_proquint(&self) -> String;
+
+    /// Decodes a value from a proquint.
+    ///
+    /// The proquint must have exactly one five letter word for each 16 bits
+    /// of the value, separated by single dashes, in lowercase.
+    fn from_proquint(string: &str) -> Result<Self, ProquintError>;
+}
+
+macro_rules! proquint_impl {
+    ($type: ty, $words: expr) => {
+        impl Proquint for $type {
+            fn to_proquint(&self) -> String {
+                let mut out = String::with_capacity($words * 6 - 1);
+
+                for word in (0..$words).rev() {
+                    encode_word((*self >> (16 * word)) as u16, &mut out);
+                    if word > 0 {
+                        out.push('-');
+                    }
+                }
+
+                out
+            }
+
+            fn from_proquint(string: &str) -> Result<Self, ProquintError> {
+                let mut words = [0u16; $words];
+                decode_words(string, &mut words)?;
+
+                Ok(words
+                    .iter()
+                    .fold(0, |value, &word| (value << 16) | <$type>::from(word)))
+            }
+        }
+    };
+}
+
+proquint_impl!(u32, 2);
+proquint_impl!(u64, 4);
+proquint_impl!(u128, 8);
+
+#[cfg(test)]
+mod tests {
+    use {Proquint, ProquintError};
+
+    #[test]
+    fn reference_examples() {
+        let examples = [
+            ([127, 0, 0, 1], "lusab-babad"),
+            ([63, 84, 220, 193], "gutih-tugad"),
+            ([63, 118, 7, 35], "gutuk-bisog"),
+            ([140, 98, 193, 141], "mudof-sakat"),
+            ([64, 255, 6, 200], "haguz-biram"),
+            ([128, 30, 52, 45], "mabiv-gibot"),
+            ([147, 67, 119, 2], "natag-lisaf"),
+            ([212, 58, 253, 68], "tibup-zujah"),
+            ([216, 35, 68, 215], "tobog-higil"),
+            ([216, 68, 232, 21], "todah-vobij"),
+            ([198, 81, 129, 136], "sinid-makam"),
+            ([12, 110, 110, 204], "budov-kuras"),
+        ];
+
+        for &(octets, proquint) in &examples {
+            let value = u32::from_be_bytes(octets);
+
+            assert_eq!(value.to_proquint(), proquint);
+            assert_eq!(u32::from_proquint(proquint), Ok(value));
+        }
+    }
+
+    #[test]
+    fn wider_values() {
+        assert_eq!(0u64.to_proquint(), "babab-babab-babab-babab");
+        assert_eq!(u64::MAX.to_proquint(), "zuzuz-zuzuz-zuzuz-zuzuz");
+        assert_eq!(
+            0x7f00_0001_0000_0000_0000_0000_0000_ffffu128.to_proquint(),
+            "lusab-babad-babab-babab-babab-babab-babab-zuzuz"
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut state = 0x0123_4567_89ab_cdefu64;
+        for _ in 0..1_000 {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+
+            let value_32 = (state >> 32) as u32;
+            let value_128 = (u128::from(state) << 64) | u128::from(state.rotate_left(17));
+
+            assert_eq!(u32::from_proquint(&value_32.to_proquint()), Ok(value_32));
+            assert_eq!(u64::from_proquint(&state.to_proquint()), Ok(state));
+            assert_eq!(u128::from_proquint(&value_128.to_proquint()), Ok(value_128));
+        }
+    }
+
+    #[test]
+    fn wrong_word_count() {
+        assert_eq!(
+            u64::from_proquint("lusab-babad"),
+            Err(ProquintError::WrongWordCount {
+                expected: 4,
+                found: 2
+            })
+        );
+        assert_eq!(
+            u32::from_proquint(""),
+            Err(ProquintError::WrongWordCount {
+                expected: 2,
+                found: 1
+            })
+        );
+        assert_eq!(
+            u32::from_proquint("lusab-babad-babab"),
+            Err(ProquintError::WrongWordCount {
+                expected: 2,
+                found: 3
+            })
+        );
+    }
+
+    #[test]
+    fn wrong_word_length() {
+        assert_eq!(
+            u32::from_proquint("lusab-baba"),
+            Err(ProquintError::WrongWordLength { word: 1, len: 4 })
+        );
+        assert_eq!(
+            u32::from_proquint("lusab-"),
+            Err(ProquintError::WrongWordLength { word: 1, len: 0 })
+        );
+    }
+
+    #[test]
+    fn invalid_characters() {
+        assert_eq!(
+            u32::from_proquint("lusab-cabad"),
+            Err(ProquintError::InvalidCharacter {
+                word: 1,
+                position: 0,
+                found: 'c'
+            })
+        );
+        assert_eq!(
+            u32::from_proquint("lesab-babad"),
+            Err(ProquintError::InvalidCharacter {
+                word: 0,
+                position: 1,
+                found: 'e'
+            })
+        );
+        assert_eq!(
+            u32::from_proquint("LUSAB-babad"),
+            Err(ProquintError::InvalidCharacter {
+                word: 0,
+                position: 0,
+                found: 'L'
+            })
+        );
+    }
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(
+            ProquintError::InvalidCharacter {
+                word: 1,
+                position: 3,
+                found: 'e'
+            }
+            .to_string(),
+            "invalid vowel 'e' at position 3 of proquint word 1"
+        );
+        assert_eq!(
+            ProquintError::WrongWordCount {
+                expected: 4,
+                found: 2
+            }
+            .to_string(),
+            "expected 4 proquint words, found 2"
+        );
+    }
+}
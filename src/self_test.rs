@@ -0,0 +1,280 @@
+//! A known-answer self-test of the hashers, for checks at startup.
+
+use std::fmt;
+use std::hint::black_box;
+
+use {Fnv, FnvHash, FnvWidth, V1a, Variant, V0, V1};
+
+const INPUTS: [&[u8]; 3] = [
+    b"",
+    b"a",
+    b"The quick brown fox jumps over the lazy dog. The quick brown fox jumps over the lazy dog.",
+];
+
+/// A variant and width of the hashers with its known answers for `INPUTS`.
+#[derive(Clone, Copy)]
+struct Check {
+    algorithm: &'static str,
+    hash: fn(&[u8]) -> u128,
+    expected: [u128; 3],
+}
+
+fn live_hash<V: Variant, T: FnvWidth + Into<u128>>(bytes: &[u8]) -> u128 {
+    let mut hasher = Fnv::<V, T>::new();
+    hasher.write(bytes);
+    hasher.finish().into()
+}
+
+const CHECKS: [Check; 9] = [
+    Check {
+        algorithm: "FNV-0 32",
+        hash: live_hash::<V0, u32>,
+        expected: [0x0000_0000, 0x0000_0061, 0xb42c_cde6],
+    },
+    Check {
+        algorithm: "FNV-0 64",
+        hash: live_hash::<V0, u64>,
+        expected: [
+            0x0000_0000_0000_0000,
+            0x0000_0000_0000_0061,
+            0x75c1_649a_9913_80c6,
+        ],
+    },
+    Check {
+        algorithm: "FNV-0 128",
+        hash: live_hash::<V0, u128>,
+        expected: [
+            0x0000_0000_0000_0000_0000_0000_0000_0000,
+            0x0000_0000_0000_0000_0000_0000_0000_0061,
+            0x49b1_57ef_d122_26b6_1851_2a76_16f5_1d2e,
+        ],
+    },
+    Check {
+        algorithm: "FNV-1 32",
+        hash: live_hash::<V1, u32>,
+        expected: [0x811c_9dc5, 0x050c_5d7e, 0x2ebe_8221],
+    },
+    Check {
+        algorithm: "FNV-1 64",
+        hash: live_hash::<V1, u64>,
+        expected: [
+            0xcbf2_9ce4_8422_2325,
+            0xaf63_bd4c_8601_b7be,
+            0x1a2b_9a7a_fde6_45c1,
+        ],
+    },
+    Check {
+        algorithm: "FNV-1 128",
+        hash: live_hash::<V1, u128>,
+        expected: [
+            0x6c62_272e_07bb_0142_62b8_2175_6295_c58d,
+            0xd228_cb69_101a_8caf_7891_2b70_4e4a_141e,
+            0xfa50_3f4e_0c6d_29de_463f_ea1f_bfcd_d379,
+        ],
+    },
+    Check {
+        algorithm: "FNV-1a 32",
+        hash: live_hash::<V1a, u32>,
+        expected: [0x811c_9dc5, 0xe40c_292c, 0x1766_8e0d],
+    },
+    Check {
+        algorithm: "FNV-1a 64",
+        hash: live_hash::<V1a, u64>,
+        expected: [
+            0xcbf2_9ce4_8422_2325,
+            0xaf63_dc4c_8601_ec8c,
+            0x69e0_b833_692c_f96d,
+        ],
+    },
+    Check {
+        algorithm: "FNV-1a 128",
+        hash: live_hash::<V1a, u128>,
+        expected: [
+            0x6c62_272e_07bb_0142_62b8_2175_6295_c58d,
+            0xd228_cb69_6f1a_8caf_7891_2b70_4e4a_8964,
+            0xda36_eb92_4f65_518d_0655_08a2_4963_fc65,
+        ],
+    },
+];
+
+/// A known answer which [`self_test`] found to be wrong.
+///
+/// [`self_test`]: fn.self_test.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestFailure {
+    /// The variant and width which failed, such as `"FNV-1a 64"`.
+    pub algorithm: &'static str,
+    /// The length in bytes of the input which failed: 0, 1 or 89.
+    pub input_len: usize,
+    /// The known answer, zero-extended to 128 bits.
+    pub expected: u128,
+    /// The hash computed, zero-extended to 128 bits.
+    pub actual: u128,
+}
+
+impl fmt::Display for SelfTestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} self-test failed for the {} byte input: expected {:#x}, computed {:#x}",
+            self.algorithm, self.input_len, self.expected, self.actual
+        )
+    }
+}
+
+impl ::std::error::Error for SelfTestFailure {}
+
+fn run(checks: &[Check]) -> Result<(), SelfTestFailure> {
+    for check in checks {
+        for (input, &expected) in INPUTS.iter().zip(&check.expected) {
+            // The inputs, hashers and answers are all constants, so without
+            // black_box the compiler folds every check away and the test
+            // passes without hashing anything
+            let hash = black_box(check.hash);
+            let input = black_box(*input);
+            let expected = black_box(expected);
+            let actual = hash(input);
+            if actual != expected {
+                return Err(SelfTestFailure {
+                    algorithm: check.algorithm,
+                    input_len: input.len(),
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs known-answer vectors through every variant and width of the hashers,
+/// returning the first which fails.
+///
+/// This suits an integrity check at startup, catching a miscompilation or
+/// corrupted code or constants. Each of FNV-0, FNV-1 and FNV-1a at 32, 64
+/// and 128 bits hashes an empty input, a 1 byte input and an 89 byte input,
+/// through the same `FnvHash::write` used by every hasher, and compares the
+/// results with answers compiled into the binary. The whole test is fewer
+/// than a thousand byte steps and never allocates, so it is available
+/// without the `std` feature.
+///
+/// This is a best-effort guard rather than a guarantee. The inputs, hashers
+/// and answers pass through `black_box` so that the hashing happens at run
+/// time rather than being folded away by the compiler, but a fault which
+/// affects only other inputs, or which also corrupts this test, goes
+/// unnoticed.
+///
+/// ```
+/// assert_eq!(lz_fnv::self_test(), Ok(()));
+/// ```
+pub fn self_test() -> Result<(), SelfTestFailure> {
+    run(&CHECKS)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use super::{run, Check};
+    use super::{CHECKS, INPUTS};
+    #[cfg(feature = "std")]
+    use SelfTestFailure;
+    use {self_test, Fnv0, Fnv1, Fnv1a, FnvExt, FnvHash};
+
+    /// FNV-1a 64 with one bit of its prime flipped, as by a corrupted
+    /// constant.
+    #[cfg(feature = "std")]
+    fn perturbed_fnv1a_64(bytes: &[u8]) -> u128 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        for &byte in bytes {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(0x100_0000_01b3 ^ 0x10);
+        }
+        u128::from(hash)
+    }
+
+    #[test]
+    fn passes() {
+        assert_eq!(self_test(), Ok(()));
+    }
+
+    #[test]
+    fn known_answers_match_the_hashers() {
+        for (i, input) in INPUTS.iter().enumerate() {
+            assert_eq!(CHECKS[0].expected[i], u128::from(input.fnv0_32()));
+            assert_eq!(CHECKS[4].expected[i], u128::from(input.fnv1_64()));
+            assert_eq!(CHECKS[8].expected[i], input.fnv1a_128());
+
+            let mut fnv0 = Fnv0::<u128>::new();
+            fnv0.write(input);
+            assert_eq!(CHECKS[2].expected[i], fnv0.finish());
+            let mut fnv1 = Fnv1::<u32>::new();
+            fnv1.write(input);
+            assert_eq!(CHECKS[3].expected[i], u128::from(fnv1.finish()));
+            let mut fnv1a = Fnv1a::<u64>::new();
+            fnv1a.write(input);
+            assert_eq!(CHECKS[7].expected[i], u128::from(fnv1a.finish()));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn covers_every_variant_and_width() {
+        let algorithms: Vec<_> = CHECKS.iter().map(|check| check.algorithm).collect();
+
+        assert_eq!(
+            algorithms,
+            [
+                "FNV-0 32",
+                "FNV-0 64",
+                "FNV-0 128",
+                "FNV-1 32",
+                "FNV-1 64",
+                "FNV-1 128",
+                "FNV-1a 32",
+                "FNV-1a 64",
+                "FNV-1a 128"
+            ]
+        );
+        assert_eq!(
+            INPUTS.iter().map(|input| input.len()).collect::<Vec<_>>(),
+            [0, 1, 89]
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn reports_a_perturbed_constant() {
+        let mut checks = CHECKS;
+        checks[7].hash = perturbed_fnv1a_64;
+
+        let failure = run(&checks).unwrap_err();
+
+        // The empty input never multiplies by the prime, so the 1 byte input
+        // is the first to fail
+        assert_eq!(
+            failure,
+            SelfTestFailure {
+                algorithm: "FNV-1a 64",
+                input_len: 1,
+                expected: 0xaf63_dc4c_8601_ec8c,
+                actual: perturbed_fnv1a_64(b"a"),
+            }
+        );
+        assert!(failure.to_string().starts_with(
+            "FNV-1a 64 self-test failed for the 1 byte input: expected 0xaf63dc4c8601ec8c"
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn reports_a_perturbed_answer() {
+        let mut checks: Vec<Check> = CHECKS.to_vec();
+        checks[1].expected[2] ^= 1;
+
+        let failure = run(&checks).unwrap_err();
+
+        assert_eq!(failure.algorithm, "FNV-0 64");
+        assert_eq!(failure.input_len, 89);
+        assert_eq!(failure.actual, failure.expected ^ 1);
+    }
+}
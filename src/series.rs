@@ -0,0 +1,154 @@
+//! Metric series identifiers.
+
+use std::error::Error;
+use std::fmt;
+
+use {Fnv1a, FnvHash};
+
+/// An error computing a series identifier.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeriesIdError {
+    /// The same label key was given more than once.
+    DuplicateLabel(String),
+}
+
+impl fmt::Display for SeriesIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SeriesIdError::DuplicateLabel(ref key) => write!(f, "duplicate label key {:?}", key),
+        }
+    }
+}
+
+impl Error for SeriesIdError {}
+
+fn write_framed<H: FnvHash>(hasher: &mut H, bytes: &[u8]) {
+    hasher.write(&(bytes.len() as u64).to_le_bytes());
+    hasher.write(bytes);
+}
+
+/// Computes the identifier of a metric series from its name and labels.
+///
+/// The labels are sorted by the bytes of their keys, then FNV-1a 64 is
+/// computed over the name followed by each key and value in turn. Every one
+/// of these strings is framed as its length in bytes, as 8 little-endian
+/// bytes, followed by its UTF-8 bytes. This encoding is frozen.
+///
+/// Returns an error if a label key appears more than once.
+///
+/// ```
+/// use lz_fnv::series_id;
+///
+/// let a = series_id("http_requests_total", &[("method", "GET"), ("code", "200")]);
+/// let b = series_id("http_requests_total", &[("code", "200"), ("method", "GET")]);
+///
+/// assert_eq!(a, b);
+/// ```
+pub fn series_id(name: &str, labels: &[(&str, &str)]) -> Result<u64, SeriesIdError> {
+    let mut sorted: Vec<_> = labels.iter().collect();
+    sorted.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+
+    if let Some(pair) = sorted.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(SeriesIdError::DuplicateLabel(pair[0].0.to_owned()));
+    }
+
+    let mut hasher = Fnv1a::<u64>::new();
+    write_framed(&mut hasher, name.as_bytes());
+    for &&(key, value) in &sorted {
+        write_framed(&mut hasher, key.as_bytes());
+        write_framed(&mut hasher, value.as_bytes());
+    }
+
+    Ok(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use {series_id, SeriesIdError};
+
+    #[test]
+    fn label_order_independence() {
+        let labels = [
+            ("job", "api"),
+            ("instance", "10.0.0.1:9090"),
+            ("env", "prod"),
+        ];
+        let reversed = [
+            ("env", "prod"),
+            ("instance", "10.0.0.1:9090"),
+            ("job", "api"),
+        ];
+
+        assert_eq!(series_id("up", &labels), series_id("up", &reversed));
+    }
+
+    #[test]
+    fn sensitive_to_changes() {
+        let expected = series_id("up", &[("job", "api"), ("env", "prod")]).unwrap();
+
+        assert_ne!(
+            series_id("up2", &[("job", "api"), ("env", "prod")]).unwrap(),
+            expected
+        );
+        assert_ne!(
+            series_id("up", &[("job", "apj"), ("env", "prod")]).unwrap(),
+            expected
+        );
+        assert_ne!(
+            series_id("up", &[("jpb", "api"), ("env", "prod")]).unwrap(),
+            expected
+        );
+        assert_ne!(series_id("up", &[("job", "api")]).unwrap(), expected);
+        assert_ne!(
+            series_id("up", &[("job", "api"), ("env", "prod"), ("a", "")]).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn framing_is_unambiguous() {
+        assert_ne!(
+            series_id("up", &[("ab", "c")]),
+            series_id("up", &[("a", "bc")])
+        );
+        assert_ne!(
+            series_id("upa", &[("b", "c")]),
+            series_id("up", &[("ab", "c")])
+        );
+    }
+
+    #[test]
+    fn duplicate_keys_are_rejected() {
+        assert_eq!(
+            series_id("up", &[("job", "api"), ("env", "prod"), ("job", "web")]),
+            Err(SeriesIdError::DuplicateLabel("job".to_owned()))
+        );
+        assert_eq!(
+            SeriesIdError::DuplicateLabel("job".to_owned()).to_string(),
+            "duplicate label key \"job\""
+        );
+    }
+
+    #[test]
+    fn pinned_vectors() {
+        assert_eq!(series_id("up", &[]), Ok(0x9c88_fbd3_28cc_4172));
+        assert_eq!(
+            series_id(
+                "http_requests_total",
+                &[
+                    ("method", "GET"),
+                    ("code", "200"),
+                    ("handler", "/api/v1/query")
+                ]
+            ),
+            Ok(0xe45c_1826_fe7c_9412)
+        );
+        assert_eq!(
+            series_id(
+                "node_cpu_seconds_total",
+                &[("cpu", "0"), ("mode", "idle"), ("instance", "node-1:9100")]
+            ),
+            Ok(0x42cd_4ee3_63dc_f35e)
+        );
+    }
+}
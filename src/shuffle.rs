@@ -0,0 +1,105 @@
+//! Deterministic hash-based ordering.
+
+use {Fnv1a, FnvHash};
+
+/// Returns the rank of `key` in the order given by `seed`.
+///
+/// This is FNV-1a 64 over the seed, as 8 little-endian bytes, followed by
+/// the key. It is fixed, so the same key and seed give the same rank on every
+/// run and platform.
+///
+/// ```
+/// use lz_fnv::stable_rank;
+///
+/// assert_eq!(stable_rank(b"alpha", 1), stable_rank(b"alpha", 1));
+/// assert_ne!(stable_rank(b"alpha", 1), stable_rank(b"alpha", 2));
+/// ```
+pub fn stable_rank(key: &[u8], seed: u64) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(&seed.to_le_bytes());
+    hasher.write(key);
+    hasher.finish()
+}
+
+/// Shuffles `items` into the order given by `seed`, sorting them by the
+/// [`stable_rank`] of their keys.
+///
+/// The order depends only on the keys and the seed, so a failure reproduces
+/// given the seed. The sort is stable: items with equal ranks, such as those
+/// with identical keys, keep their relative order.
+///
+/// ```
+/// use lz_fnv::shuffle_by_hash;
+///
+/// let mut first = ["a", "b", "c", "d", "e"];
+/// let mut second = first;
+///
+/// shuffle_by_hash(&mut first, 42, |item| item.as_bytes());
+/// shuffle_by_hash(&mut second, 42, |item| item.as_bytes());
+///
+/// assert_eq!(first, second);
+/// ```
+///
+/// [`stable_rank`]: fn.stable_rank.html
+pub fn shuffle_by_hash<T>(items: &mut [T], seed: u64, key: impl Fn(&T) -> &[u8]) {
+    items.sort_by_cached_key(|item| stable_rank(key(item), seed));
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use {shuffle_by_hash, stable_rank};
+
+    fn shuffled(seed: u64) -> Vec<&'static str> {
+        let mut items = vec!["alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta"];
+        shuffle_by_hash(&mut items, seed, |item| item.as_bytes());
+        items
+    }
+
+    #[test]
+    fn pinned_permutation() {
+        assert_eq!(stable_rank(b"alpha", 0), 0xd069_a0cb_aa7c_aa4b);
+        assert_eq!(
+            shuffled(0),
+            ["delta", "beta", "gamma", "eta", "zeta", "epsilon", "alpha"]
+        );
+    }
+
+    #[test]
+    fn seeds_change_permutation() {
+        assert_ne!(shuffled(1), shuffled(2));
+        assert_eq!(shuffled(1), shuffled(1));
+    }
+
+    #[test]
+    fn identical_keys_keep_order() {
+        let mut items = vec![("b", 0), ("a", 1), ("b", 2), ("a", 3), ("b", 4)];
+        shuffle_by_hash(&mut items, 9, |item| item.0.as_bytes());
+
+        let b_positions: Vec<_> = items
+            .iter()
+            .filter(|item| item.0 == "b")
+            .map(|item| item.1)
+            .collect();
+        let a_positions: Vec<_> = items
+            .iter()
+            .filter(|item| item.0 == "a")
+            .map(|item| item.1)
+            .collect();
+        assert_eq!(b_positions, [0, 2, 4]);
+        assert_eq!(a_positions, [1, 3]);
+    }
+
+    proptest! {
+        #[test]
+        fn shuffle_is_permutation(items in prop::collection::vec(any::<Vec<u8>>(), 0..64), seed: u64) {
+            let mut shuffled = items.clone();
+            shuffle_by_hash(&mut shuffled, seed, |item| item);
+
+            let mut sorted = items;
+            sorted.sort();
+            shuffled.sort();
+            prop_assert_eq!(shuffled, sorted);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Snapshot assertions which record only the FNV-1a 64 hash of a value.
+
+use std::env;
+use std::fmt::{self, Write};
+
+use {Fnv1a, FnvHash};
+
+/// The environment variable which, when set to anything but `0` or the
+/// empty string, makes snapshot mismatches print rather than panic.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "LZ_FNV_UPDATE_SNAPSHOTS";
+
+struct HashingWriter(Fnv1a<u64>);
+
+impl Write for HashingWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Returns the FNV-1a 64 hash of the `Display` output of a value.
+///
+/// The output is hashed as it is written, without being collected into a
+/// `String`, and hashes equally to the bytes of `value.to_string()`.
+pub fn display_fnv_hash<T: fmt::Display + ?Sized>(value: &T) -> u64 {
+    let mut writer = HashingWriter(Fnv1a::<u64>::new());
+    write!(writer, "{}", value).expect("a Display implementation returned an error");
+    writer.0.finish()
+}
+
+fn mismatch(hash: u64, expected_hex: &str, context: &str) -> Option<String> {
+    let actual = format!("{:016x}", hash);
+    let expected = expected_hex.trim();
+    let expected = expected.strip_prefix("0x").unwrap_or(expected);
+
+    if expected.eq_ignore_ascii_case(&actual) {
+        return None;
+    }
+
+    Some(format!(
+        "FNV snapshot mismatch for {}\n  expected: {}\n    actual: \"{}\"\n\
+         set {}=1 to print mismatches instead of failing",
+        context, expected_hex, actual, UPDATE_SNAPSHOTS_VAR
+    ))
+}
+
+fn update_mode() -> bool {
+    match env::var_os(UPDATE_SNAPSHOTS_VAR) {
+        Some(value) => !value.is_empty() && value != "0",
+        None => false,
+    }
+}
+
+fn check(hash: u64, expected_hex: &str, context: &str, update: bool) {
+    if let Some(message) = mismatch(hash, expected_hex, context) {
+        if update {
+            eprintln!("{}", message);
+        } else {
+            panic!("{}", message);
+        }
+    }
+}
+
+/// Checks a hash against the 16 hex digits recorded for a snapshot, as used
+/// by [`assert_fnv_snapshot!`].
+///
+/// The recorded digits may have a `0x` prefix and either case. On a mismatch
+/// this panics with a message naming `context` and giving the actual hash as
+/// a quoted string ready to paste into the test. If the
+/// [`UPDATE_SNAPSHOTS_VAR`] environment variable is set the message is
+/// printed to standard error instead, so every mismatch in a run can be
+/// collected at once.
+///
+/// [`assert_fnv_snapshot!`]: macro.assert_fnv_snapshot.html
+/// [`UPDATE_SNAPSHOTS_VAR`]: constant.UPDATE_SNAPSHOTS_VAR.html
+pub fn check_fnv_snapshot(hash: u64, expected_hex: &str, context: &str) {
+    check(hash, expected_hex, context, update_mode());
+}
+
+/// Asserts that the FNV-1a 64 hash of some bytes matches a recorded hex
+/// snapshot.
+///
+/// The value may be anything implementing `AsRef<[u8]>`. This detects any
+/// change to a large golden output without storing it, but cannot show what
+/// changed. On a mismatch the panic message gives the expression, its
+/// location and the actual hash to record; see [`check_fnv_snapshot`] for the
+/// update mode which prints instead.
+///
+/// This is available with the `testing` feature.
+///
+/// ```
+/// #[macro_use]
+/// extern crate lz_fnv;
+///
+/// # fn main() {
+/// let rendered = String::from("foobar");
+///
+/// assert_fnv_snapshot!(rendered, "85944171f73967e8");
+/// assert_fnv_snapshot!(b"foobar", "0x85944171F73967E8");
+/// # }
+/// ```
+///
+/// [`check_fnv_snapshot`]: fn.check_fnv_snapshot.html
+#[macro_export]
+macro_rules! assert_fnv_snapshot {
+    ($value: expr, $expected: expr) => {{
+        let mut hasher = $crate::Fnv1a::<u64>::new();
+        $crate::FnvHash::write(&mut hasher, ::std::convert::AsRef::<[u8]>::as_ref(&$value));
+        $crate::check_fnv_snapshot(
+            $crate::FnvHash::finish(&hasher),
+            ::std::convert::AsRef::<str>::as_ref(&$expected),
+            concat!("`", stringify!($value), "` at ", file!(), ":", line!()),
+        );
+    }};
+}
+
+/// Asserts that the FNV-1a 64 hash of a value's `Display` output matches a
+/// recorded hex snapshot.
+///
+/// This is [`assert_fnv_snapshot!`] of `value.to_string()`, hashed without
+/// allocating the string.
+///
+/// This is available with the `testing` feature.
+///
+/// ```
+/// #[macro_use]
+/// extern crate lz_fnv;
+///
+/// # fn main() {
+/// assert_fnv_snapshot_display!(format_args!("foo{}", "bar"), "85944171f73967e8");
+/// # }
+/// ```
+///
+/// [`assert_fnv_snapshot!`]: macro.assert_fnv_snapshot.html
+#[macro_export]
+macro_rules! assert_fnv_snapshot_display {
+    ($value: expr, $expected: expr) => {{
+        $crate::check_fnv_snapshot(
+            $crate::display_fnv_hash(&$value),
+            ::std::convert::AsRef::<str>::as_ref(&$expected),
+            concat!("`", stringify!($value), "` at ", file!(), ":", line!()),
+        );
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use super::{check, mismatch};
+    use display_fnv_hash;
+
+    fn panic_message<F: FnOnce() + panic::UnwindSafe>(f: F) -> String {
+        let payload = panic::catch_unwind(f).unwrap_err();
+        match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => payload.downcast::<&str>().unwrap().to_string(),
+        }
+    }
+
+    #[test]
+    fn matching_snapshots_pass() {
+        assert_fnv_snapshot!(b"foobar", "85944171f73967e8");
+        assert_fnv_snapshot!(b"foobar".to_vec(), "85944171F73967E8");
+        assert_fnv_snapshot!("", String::from("0xcbf29ce484222325"));
+    }
+
+    #[test]
+    fn mismatching_snapshots_panic() {
+        let message = panic_message(|| assert_fnv_snapshot!(b"foobaz", "85944171f73967e8"));
+
+        assert!(
+            message.contains("`b\"foobaz\"` at src/snapshot.rs:"),
+            "{}",
+            message
+        );
+        assert!(message.contains("\"85943971f7395a50\""), "{}", message);
+    }
+
+    #[test]
+    fn message_format() {
+        assert_eq!(
+            mismatch(0x8594_4171_f739_67e8, "0x85944171f73967e8", "x"),
+            None
+        );
+        assert_eq!(
+            mismatch(
+                0x8594_4171_f739_67e8,
+                "0000000000000000",
+                "`output` at a.rs:1"
+            )
+            .unwrap(),
+            "FNV snapshot mismatch for `output` at a.rs:1\n  \
+             expected: 0000000000000000\n    \
+             actual: \"85944171f73967e8\"\n\
+             set LZ_FNV_UPDATE_SNAPSHOTS=1 to print mismatches instead of failing"
+        );
+    }
+
+    #[test]
+    fn update_mode_does_not_panic() {
+        check(0, "85944171f73967e8", "`value`", true);
+
+        let message = panic_message(|| check(0, "85944171f73967e8", "`value`", false));
+        assert!(message.starts_with("FNV snapshot mismatch for `value`\n"));
+    }
+
+    #[test]
+    fn display_snapshots() {
+        assert_eq!(display_fnv_hash("foobar"), 0x8594_4171_f739_67e8);
+        assert_eq!(display_fnv_hash(&12_345), display_fnv_hash("12345"));
+
+        assert_fnv_snapshot_display!(format_args!("{}{}", "foo", "bar"), "85944171f73967e8");
+        assert_fnv_snapshot_display!(12_345, format!("{:016x}", display_fnv_hash("12345")));
+
+        let message = panic_message(|| assert_fnv_snapshot_display!(1.5, "85944171f73967e8"));
+        assert!(message.contains("`1.5` at "), "{}", message);
+    }
+}
@@ -0,0 +1,209 @@
+//! Hashing of sparse files without reading their holes.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use hash_file;
+
+/// Hashes the contents of the file at `path` with FNV-1a 64, skipping over
+/// holes in sparse files.
+///
+/// The result is identical to [`hash_file`]: holes read as zero bytes and are
+/// hashed as such. Each FNV-1a step over a zero byte only multiplies the
+/// state by the prime, so a hole of `n` bytes is hashed in `O(log n)` time by
+/// multiplying by the prime raised to the power `n`, without reading it.
+///
+/// Holes are found with `SEEK_DATA` and `SEEK_HOLE` on Linux, Android and
+/// FreeBSD. Elsewhere, and on filesystems which do not support them, the
+/// whole file is read.
+///
+/// [`hash_file`]: fn.hash_file.html
+///
+/// ```no_run
+/// use lz_fnv::{hash_file, hash_file_sparse};
+///
+/// assert_eq!(hash_file_sparse("disk.img").unwrap(), hash_file("disk.img").unwrap());
+/// ```
+pub fn hash_file_sparse<P: AsRef<Path>>(path: P) -> io::Result<u64> {
+    let path = path.as_ref();
+
+    match imp::hash_sparse(&File::open(path)?)? {
+        Some(hash) => Ok(hash),
+        None => hash_file(path),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+mod imp {
+    use std::fs::File;
+    use std::io::{self, Read, Seek, SeekFrom};
+    use std::os::unix::io::AsRawFd;
+
+    use libc;
+
+    use file::write_reader;
+    use step::PRIME_64;
+    use {Fnv1a, FnvHash};
+
+    /// Returns the prime raised to the power `exponent`, modulo `2^64`.
+    fn prime_power(mut exponent: u64) -> u64 {
+        let (mut power, mut base) = (1u64, PRIME_64);
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                power = power.wrapping_mul(base);
+            }
+            base = base.wrapping_mul(base);
+            exponent >>= 1;
+        }
+
+        power
+    }
+
+    /// Seeks with `whence` from `offset`, returning `None` if there is no
+    /// further data or hole.
+    fn seek(file: &File, offset: u64, whence: libc::c_int) -> io::Result<Option<u64>> {
+        let result = unsafe { libc::lseek(file.as_raw_fd(), offset as libc::off_t, whence) };
+
+        if result >= 0 {
+            return Ok(Some(result as u64));
+        }
+
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() == Some(libc::ENXIO) {
+            Ok(None)
+        } else {
+            Err(error)
+        }
+    }
+
+    /// Hashes `file` extent by extent, or returns `None` if the filesystem
+    /// cannot report holes.
+    pub(super) fn hash_sparse(file: &File) -> io::Result<Option<u64>> {
+        let len = file.metadata()?.len();
+        let mut hasher = Fnv1a::<u64>::new();
+        let mut offset = 0;
+
+        while offset < len {
+            let data = match seek(file, offset, libc::SEEK_DATA) {
+                Ok(data) => data.unwrap_or(len).min(len),
+                Err(ref e) if offset == 0 && e.raw_os_error() == Some(libc::EINVAL) => {
+                    return Ok(None)
+                }
+                Err(e) => return Err(e),
+            };
+
+            let hash = hasher.finish().wrapping_mul(prime_power(data - offset));
+            hasher = Fnv1a::with_key(hash);
+            if data == len {
+                break;
+            }
+
+            let hole = seek(file, data, libc::SEEK_HOLE)?.unwrap_or(len).min(len);
+
+            let mut reader = file;
+            reader.seek(SeekFrom::Start(data))?;
+            write_reader(&mut hasher, &mut reader.take(hole - data))?;
+
+            offset = hole;
+        }
+
+        Ok(Some(hasher.finish()))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::prime_power;
+        use step::PRIME_64;
+        use {Fnv1a, FnvHash};
+
+        #[test]
+        fn prime_power_hashes_zeros() {
+            for &n in &[0, 1, 2, 7, 4096, 100_000] {
+                let mut hasher = Fnv1a::<u64>::with_key(0x1234_5678);
+                hasher.write(&vec![0; n]);
+
+                assert_eq!(
+                    0x1234_5678u64.wrapping_mul(prime_power(n as u64)),
+                    hasher.finish()
+                );
+            }
+
+            assert_eq!(prime_power(1), PRIME_64);
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+mod imp {
+    use std::fs::File;
+    use std::io;
+
+    pub(super) fn hash_sparse(_file: &File) -> io::Result<Option<u64>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, OpenOptions};
+    use std::io::{Seek, SeekFrom, Write};
+    use tempfile::tempdir;
+
+    use {hash_file, hash_file_sparse};
+
+    #[test]
+    fn sparse_matches_dense() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sparse.img");
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .unwrap();
+        file.set_len(8 * 1024 * 1024).unwrap();
+        for &offset in &[0, 1024 * 1024 + 17, 3 * 1024 * 1024, 8 * 1024 * 1024 - 3] {
+            file.seek(SeekFrom::Start(offset)).unwrap();
+            file.write_all(b"data").unwrap();
+        }
+        drop(file);
+
+        assert_eq!(hash_file_sparse(&path).unwrap(), hash_file(&path).unwrap());
+    }
+
+    #[test]
+    fn trailing_and_leading_holes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("holes.img");
+
+        let mut file = fs::File::create(&path).unwrap();
+        file.seek(SeekFrom::Start(2 * 1024 * 1024)).unwrap();
+        file.write_all(b"middle").unwrap();
+        file.set_len(6 * 1024 * 1024).unwrap();
+        drop(file);
+
+        assert_eq!(hash_file_sparse(&path).unwrap(), hash_file(&path).unwrap());
+    }
+
+    #[test]
+    fn dense_and_empty_files() {
+        let dir = tempdir().unwrap();
+        let dense = dir.path().join("dense");
+        let empty = dir.path().join("empty");
+        fs::write(&dense, b"foobar").unwrap();
+        fs::write(&empty, b"").unwrap();
+
+        assert_eq!(hash_file_sparse(&dense).unwrap(), 0x8594_4171_f739_67e8);
+        assert_eq!(hash_file_sparse(&empty).unwrap(), 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    fn missing_file() {
+        let dir = tempdir().unwrap();
+
+        assert!(hash_file_sparse(dir.path().join("missing")).is_err());
+    }
+}
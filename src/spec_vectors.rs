@@ -0,0 +1,111 @@
+// The test vectors published with the FNV specification, draft-eastlake-fnv,
+// for FNV-1 and FNV-1a at every width this crate supports. The full
+// test_fnv.c suite for the 32 and 64-bit widths is in fnv_test_cases.rs.
+
+use std::fmt::Debug;
+use {Fnv0, Fnv1, Fnv1a, FnvHash};
+
+pub(crate) const FNV1_32: &[(&[u8], u32)] = &[
+    (b"", 0x811c_9dc5),
+    (b"a", 0x050c_5d7e),
+    (b"foobar", 0x31f0_b262),
+];
+
+pub(crate) const FNV1A_32: &[(&[u8], u32)] = &[
+    (b"", 0x811c_9dc5),
+    (b"a", 0xe40c_292c),
+    (b"foobar", 0xbf9c_f968),
+];
+
+pub(crate) const FNV1_64: &[(&[u8], u64)] = &[
+    (b"", 0xcbf2_9ce4_8422_2325),
+    (b"a", 0xaf63_bd4c_8601_b7be),
+    (b"foobar", 0x340d_8765_a4dd_a9c2),
+];
+
+pub(crate) const FNV1A_64: &[(&[u8], u64)] = &[
+    (b"", 0xcbf2_9ce4_8422_2325),
+    (b"a", 0xaf63_dc4c_8601_ec8c),
+    (b"foobar", 0x8594_4171_f739_67e8),
+];
+
+pub(crate) const FNV1_128: &[(&[u8], u128)] = &[
+    (b"", 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d),
+    (b"a", 0xd228_cb69_101a_8caf_7891_2b70_4e4a_141e),
+    (b"foobar", 0x7896_bfea_9c3c_64bf_6dc5_8353_d2c2_93aa),
+];
+
+pub(crate) const FNV1A_128: &[(&[u8], u128)] = &[
+    (b"", 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d),
+    (b"a", 0xd228_cb69_6f1a_8caf_7891_2b70_4e4a_8964),
+    (b"foobar", 0x343e_1662_793c_64bf_6f0d_3597_ba44_6f18),
+];
+
+fn check<H>(new: fn() -> H, vectors: &[(&[u8], H::Hash)])
+where
+    H: FnvHash,
+    H::Hash: PartialEq + Debug,
+{
+    for &(input, ref expected) in vectors {
+        let mut hasher = new();
+        hasher.write(input);
+
+        assert_eq!(&hasher.finish(), expected, "input {:?}", input);
+    }
+}
+
+#[test]
+fn fnv1_32() {
+    check(Fnv1::<u32>::new, FNV1_32);
+}
+
+#[test]
+fn fnv1a_32() {
+    check(Fnv1a::<u32>::new, FNV1A_32);
+}
+
+#[test]
+fn fnv1_64() {
+    check(Fnv1::<u64>::new, FNV1_64);
+}
+
+#[test]
+fn fnv1a_64() {
+    check(Fnv1a::<u64>::new, FNV1A_64);
+}
+
+#[test]
+fn fnv1_128() {
+    check(Fnv1::<u128>::new, FNV1_128);
+}
+
+#[test]
+fn fnv1a_128() {
+    check(Fnv1a::<u128>::new, FNV1A_128);
+}
+
+#[test]
+fn fnv0_zero_length() {
+    check(Fnv0::<u32>::new, &[(b"", 0)]);
+    check(Fnv0::<u64>::new, &[(b"", 0)]);
+    check(Fnv0::<u128>::new, &[(b"", 0)]);
+}
+
+#[test]
+fn zero_length_is_offset_basis() {
+    for &(input, expected) in FNV1_32.iter().chain(FNV1A_32) {
+        if input.is_empty() {
+            assert_eq!(expected, Fnv1::<u32>::new().finish());
+        }
+    }
+    for &(input, expected) in FNV1_64.iter().chain(FNV1A_64) {
+        if input.is_empty() {
+            assert_eq!(expected, Fnv1a::<u64>::new().finish());
+        }
+    }
+    for &(input, expected) in FNV1_128.iter().chain(FNV1A_128) {
+        if input.is_empty() {
+            assert_eq!(expected, Fnv1a::<u128>::new().finish());
+        }
+    }
+}
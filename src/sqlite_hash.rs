@@ -0,0 +1,173 @@
+//! FNV-1a SQL functions for SQLite connections.
+
+use rusqlite::functions::{Context, FunctionFlags};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, Error, Result};
+
+use FnvExt;
+
+/// Returns the bytes of the single argument, or `None` if it is NULL.
+fn argument<'a>(ctx: &'a Context) -> Result<Option<&'a [u8]>> {
+    match ctx.get_raw(0) {
+        ValueRef::Null => Ok(None),
+        ValueRef::Text(bytes) | ValueRef::Blob(bytes) => Ok(Some(bytes)),
+        value => Err(Error::InvalidFunctionParameterType(0, value.data_type())),
+    }
+}
+
+/// Registers the FNV-1a SQL functions on a connection.
+///
+/// Each function takes one TEXT or BLOB argument, hashing the UTF-8 bytes of
+/// text and the bytes of a blob, and returns NULL for a NULL argument. Other
+/// argument types are errors. The functions are:
+///
+/// * `fnv1a32(value)`, the FNV-1a 32 hash as a non-negative INTEGER,
+/// * `fnv1a64(value)`, the FNV-1a 64 hash as an INTEGER. SQLite integers are
+///   signed 64-bit, so the hash's bits are reinterpreted as an `i64`, and
+///   hashes of `1 << 63` or more are negative. Read the column as an `i64`
+///   and cast it with `as u64` to recover the hash, and
+/// * `fnv1a64_hex(value)`, the FNV-1a 64 hash as 16 lowercase hex digits of
+///   TEXT.
+///
+/// The functions are registered as deterministic, so they may be used in
+/// index expressions, but such an index can only be used by connections on
+/// which the functions are registered.
+///
+/// ```
+/// extern crate lz_fnv;
+/// extern crate rusqlite;
+///
+/// use lz_fnv::register_fnv_functions;
+/// use rusqlite::Connection;
+///
+/// # fn main() {
+/// let conn = Connection::open_in_memory().unwrap();
+/// register_fnv_functions(&conn).unwrap();
+///
+/// let hex: String = conn
+///     .query_row("SELECT fnv1a64_hex('foobar')", [], |row| row.get(0))
+///     .unwrap();
+///
+/// assert_eq!(hex, "85944171f73967e8");
+/// # }
+/// ```
+pub fn register_fnv_functions(conn: &Connection) -> Result<()> {
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+
+    conn.create_scalar_function("fnv1a32", 1, flags, |ctx| {
+        Ok(argument(ctx)?.map(|bytes| i64::from(bytes.fnv1a_32())))
+    })?;
+    conn.create_scalar_function("fnv1a64", 1, flags, |ctx| {
+        Ok(argument(ctx)?.map(|bytes| bytes.fnv1a_64() as i64))
+    })?;
+    conn.create_scalar_function("fnv1a64_hex", 1, flags, |ctx| {
+        Ok(argument(ctx)?.map(|bytes| format!("{:016x}", bytes.fnv1a_64())))
+    })?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::types::Type;
+    use rusqlite::{Connection, Error};
+
+    use {register_fnv_functions, Fnv1a, FnvHash};
+
+    fn connection() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        register_fnv_functions(&conn).unwrap();
+        conn
+    }
+
+    fn query<T: rusqlite::types::FromSql>(conn: &Connection, sql: &str) -> T {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn text_and_blobs_match_rust() {
+        let conn = connection();
+
+        let mut fnv1a_32 = Fnv1a::<u32>::new();
+        fnv1a_32.write(b"foobar");
+        let mut fnv1a_64 = Fnv1a::<u64>::new();
+        fnv1a_64.write(b"foobar");
+
+        for value in &["'foobar'", "X'666f6f626172'"] {
+            let sql = format!("SELECT fnv1a32({})", value);
+            assert_eq!(query::<i64>(&conn, &sql), i64::from(fnv1a_32.finish()));
+
+            let sql = format!("SELECT fnv1a64({})", value);
+            assert_eq!(query::<i64>(&conn, &sql) as u64, fnv1a_64.finish());
+
+            let sql = format!("SELECT fnv1a64_hex({})", value);
+            assert_eq!(query::<String>(&conn, &sql), "85944171f73967e8");
+        }
+    }
+
+    #[test]
+    fn large_hashes_are_negative() {
+        let conn = connection();
+
+        assert_eq!(query::<i64>(&conn, "SELECT fnv1a32('foobar')"), 0xbf9c_f968);
+        assert_eq!(
+            query::<i64>(&conn, "SELECT fnv1a64('foobar')"),
+            0x8594_4171_f739_67e8_u64 as i64
+        );
+        assert!(query::<i64>(&conn, "SELECT fnv1a64('foobar')") < 0);
+        assert_eq!(
+            query::<String>(&conn, "SELECT fnv1a64_hex('')"),
+            "cbf29ce484222325"
+        );
+    }
+
+    #[test]
+    fn null_propagates() {
+        let conn = connection();
+
+        for function in &["fnv1a32", "fnv1a64", "fnv1a64_hex"] {
+            let sql = format!("SELECT {}(NULL)", function);
+            assert_eq!(query::<Option<i64>>(&conn, &sql), None);
+        }
+    }
+
+    #[test]
+    fn numbers_are_rejected() {
+        let conn = connection();
+        let result: Result<i64, Error> = conn.query_row("SELECT fnv1a64(42)", [], |row| row.get(0));
+
+        match result {
+            Err(Error::SqliteFailure(_, Some(message))) => {
+                assert!(message.contains(&Type::Integer.to_string()), "{}", message)
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn usable_in_index_expressions() {
+        let conn = connection();
+        conn.execute_batch(
+            "CREATE TABLE documents (body BLOB);
+             CREATE INDEX documents_hash ON documents (fnv1a64(body));
+             INSERT INTO documents VALUES ('foo'), ('bar'), (X'666f6f626172');",
+        )
+        .unwrap();
+
+        let sql = "SELECT body FROM documents WHERE fnv1a64(body) = fnv1a64('foobar')";
+
+        let plan: String = conn
+            .query_row(&format!("EXPLAIN QUERY PLAN {}", sql), [], |row| row.get(3))
+            .unwrap();
+        assert!(plan.contains("USING INDEX documents_hash"), "{}", plan);
+
+        let mut statement = conn.prepare(sql).unwrap();
+        let bodies: Vec<Vec<u8>> = statement
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|body| body.unwrap())
+            .collect();
+
+        assert_eq!(bodies, [b"foobar".to_vec()]);
+    }
+}
@@ -0,0 +1,144 @@
+// Pinned outputs of every hasher at every width over a fixed corpus.
+//
+// Downstream users persist these hashes, so they must never change between
+// versions of the crate. A failure here means a change in output, not a
+// test to be updated.
+
+use std::fmt::Debug;
+use {Fnv0, Fnv1, Fnv1a, FnvHash};
+
+const CORPUS: [&[u8]; 7] = [
+    b"",
+    b"a",
+    b"foobar",
+    b"lz_fnv",
+    b"The quick brown fox jumps over the lazy dog",
+    b"\x00\xff\x00\xff",
+    b"0123456789012345678901234567890123456789012345678901234567890123456789012345678901234567890123456789",
+];
+
+const FNV0_32: [u32; 7] = [
+    0x0000_0000,
+    0x0000_0061,
+    0xb74b_b5ef,
+    0x96b0_c9b9,
+    0x8907_4759,
+    0xdc77_ee68,
+    0x2924_14a8,
+];
+
+const FNV0_64: [u64; 7] = [
+    0x0000_0000_0000_0000,
+    0x0000_0000_0000_0061,
+    0x0b91_ae3f_7ccd_c5ef,
+    0x0c0d_f0e6_c65f_c399,
+    0x1f72_24b4_c8a7_bbb9,
+    0x0362_9a00_02e0_4528,
+    0xb6aa_65b8_124f_cea8,
+];
+
+const FNV0_128: [u128; 7] = [
+    0x0000_0000_0000_0000_0000_0000_0000_0000,
+    0x0000_0000_0000_0000_0000_0000_0000_0061,
+    0x9438_ff4b_ea00_0000_0001_20ab_5188_d04f,
+    0xd3d4_ed4d_2300_0000_0001_3001_272f_a611,
+    0x5170_9821_3d50_cd2f_edca_83d3_2b0c_6e31,
+    0x0000_0273_8a00_0000_0000_0000_0182_1598,
+    0x5a53_f993_911b_263c_127a_32a0_689f_5608,
+];
+
+const FNV1_32: [u32; 7] = [
+    0x811c_9dc5,
+    0x050c_5d7e,
+    0x31f0_b262,
+    0xad27_e9f0,
+    0xe9c8_6c6e,
+    0x4815_d77d,
+    0xd515_d83d,
+];
+
+const FNV1_64: [u64; 7] = [
+    0xcbf2_9ce4_8422_2325,
+    0xaf63_bd4c_8601_b7be,
+    0x340d_8765_a4dd_a9c2,
+    0x8464_0c0c_3ef2_6f70,
+    0xa8b2_f311_7de3_7ace,
+    0x4a3d_347f_9b55_bf1d,
+    0x2ce1_1b56_a069_7e9d,
+];
+
+const FNV1_128: [u128; 7] = [
+    0x6c62_272e_07bb_0142_62b8_2175_6295_c58d,
+    0xd228_cb69_101a_8caf_7891_2b70_4e4a_141e,
+    0x7896_bfea_9c3c_64bf_6dc5_8353_d2c2_93aa,
+    0x3273_4fde_a33c_64bf_6dc5_71fb_92c8_f960,
+    0x185a_db69_3e7c_9784_4ecf_a949_7cb5_29b6,
+    0x66ad_399c_0475_7277_b806_e89d_2f32_15d5,
+    0x4467_c66c_006e_7214_18d4_c7e2_1fa3_6505,
+];
+
+const FNV1A_32: [u32; 7] = [
+    0x811c_9dc5,
+    0xe40c_292c,
+    0xbf9c_f968,
+    0x835e_be7a,
+    0x048f_ff90,
+    0xf681_f0cd,
+    0x93ff_86dd,
+];
+
+const FNV1A_64: [u64; 7] = [
+    0xcbf2_9ce4_8422_2325,
+    0xaf63_dc4c_8601_ec8c,
+    0x8594_4171_f739_67e8,
+    0x611d_91f6_de10_f65a,
+    0xf3f9_b7f5_e7e4_7110,
+    0xd6b3_707b_f4b8_56ed,
+    0x96eb_a2c6_d627_6bbd,
+];
+
+const FNV1A_128: [u128; 7] = [
+    0x6c62_272e_07bb_0142_62b8_2175_6295_c58d,
+    0xd228_cb69_6f1a_8caf_7891_2b70_4e4a_8964,
+    0x343e_1662_793c_64bf_6f0d_3597_ba44_6f18,
+    0x1173_9443_743c_64bf_6ee9_8b0d_543f_0852,
+    0x68cc_e4cd_885e_a042_39f0_2af3_0e29_7870,
+    0x66ad_3d06_e075_7277_b806_e89d_305d_dc45,
+    0xcd2d_863c_86be_525f_774f_8cfa_9580_9fe5,
+];
+
+fn check<H>(new: fn() -> H, expected: &[H::Hash])
+where
+    H: FnvHash,
+    H::Hash: PartialEq + Debug,
+{
+    for (input, expected) in CORPUS.iter().zip(expected) {
+        let mut hasher = new();
+        hasher.write(input);
+
+        assert_eq!(&hasher.finish(), expected, "input {:?}", input);
+    }
+}
+
+macro_rules! stability_tests {
+    ($($name: ident: $hasher: ty, $expected: ident,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                check(<$hasher>::new, &$expected);
+            }
+        )*
+    };
+}
+
+stability_tests! {
+    fnv0_32: Fnv0<u32>, FNV0_32,
+    fnv0_64: Fnv0<u64>, FNV0_64,
+    fnv0_128: Fnv0<u128>, FNV0_128,
+    fnv1_32: Fnv1<u32>, FNV1_32,
+    fnv1_64: Fnv1<u64>, FNV1_64,
+    fnv1_128: Fnv1<u128>, FNV1_128,
+    fnv1a_32: Fnv1a<u32>, FNV1A_32,
+    fnv1a_64: Fnv1a<u64>, FNV1A_64,
+    fnv1a_128: Fnv1a<u128>, FNV1A_128,
+}
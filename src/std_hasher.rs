@@ -0,0 +1,197 @@
+//! Adapting the 32-bit and 128-bit hashers to `std::hash::Hasher`.
+
+#[cfg(feature = "std")]
+use std::hash::BuildHasher;
+use std::hash::Hasher;
+
+#[cfg(feature = "std")]
+use FnvBuildHasher;
+use {Fnv, Fnv0, Fnv1, Fnv1a, FnvHash, Variant};
+
+/// An adapter implementing `std::hash::Hasher` for the FNV hashers whose
+/// hash is not 64 bits wide.
+///
+/// `finish` zero-extends a 32-bit hash and XOR-folds a 128-bit hash, `(hash
+/// >> 64) ^ (hash & 0xffff_ffff_ffff_ffff)`, into the `u64` which `Hasher`
+/// requires. The 64-bit hashers implement `Hasher` directly.
+#[derive(Debug, Clone, Default)]
+pub struct StdFnvHasher<H> {
+    inner: H,
+}
+
+impl<H> StdFnvHasher<H> {
+    /// Creates a new `StdFnvHasher` wrapping the specified hasher.
+    pub fn new(inner: H) -> Self {
+        Self { inner }
+    }
+
+    /// Gets a reference to the wrapped hasher.
+    pub fn get_ref(&self) -> &H {
+        &self.inner
+    }
+
+    /// Consumes this `StdFnvHasher`, returning the wrapped hasher.
+    pub fn into_inner(self) -> H {
+        self.inner
+    }
+}
+
+macro_rules! std_fnv_hasher_impl {
+    ($type: ty, $hash: ident => $fold: expr) => {
+        impl Hasher for StdFnvHasher<$type> {
+            fn finish(&self) -> u64 {
+                let $hash = self.inner.finish();
+                $fold
+            }
+
+            fn write(&mut self, bytes: &[u8]) {
+                self.inner.write(bytes);
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl BuildHasher for FnvBuildHasher<$type> {
+            type Hasher = StdFnvHasher<$type>;
+
+            fn build_hasher(&self) -> Self::Hasher {
+                StdFnvHasher::new(self.build_fnv_hasher())
+            }
+        }
+
+        impl $type {
+            /// Converts this hasher into an implementation of
+            /// `std::hash::Hasher`.
+            ///
+            /// See [`StdFnvHasher`](struct.StdFnvHasher.html) for how the hash is
+            /// reduced to a `u64`.
+            pub fn into_std_hasher(self) -> StdFnvHasher<Self> {
+                StdFnvHasher::new(self)
+            }
+        }
+    };
+}
+
+std_fnv_hasher_impl!(Fnv0<u32>, hash => u64::from(hash));
+std_fnv_hasher_impl!(Fnv1<u32>, hash => u64::from(hash));
+std_fnv_hasher_impl!(Fnv1a<u32>, hash => u64::from(hash));
+std_fnv_hasher_impl!(Fnv0<u128>, hash => ((hash >> 64) ^ hash) as u64);
+std_fnv_hasher_impl!(Fnv1<u128>, hash => ((hash >> 64) ^ hash) as u64);
+std_fnv_hasher_impl!(Fnv1a<u128>, hash => ((hash >> 64) ^ hash) as u64);
+
+impl<V: Variant> Fnv<V, u32> {
+    /// Recovers the native 32-bit hash from the `u64` returned by
+    /// `Hasher::finish` on a [`StdFnvHasher`](struct.StdFnvHasher.html)
+    /// wrapping a 32-bit hasher.
+    ///
+    /// `finish` zero-extends the 32-bit hash, so this returns the low 32 bits
+    /// of `std_finish`. The high 32 bits are always zero for a value from
+    /// `finish`, and are otherwise discarded.
+    ///
+    /// ```
+    /// use std::hash::Hasher;
+    /// use lz_fnv::{Fnv1a, FnvHash};
+    ///
+    /// let mut native = Fnv1a::<u32>::new();
+    /// FnvHash::write(&mut native, b"foobar");
+    ///
+    /// let mut hasher = Fnv1a::<u32>::new().into_std_hasher();
+    /// hasher.write(b"foobar");
+    ///
+    /// assert_eq!(Fnv1a::<u32>::native_from_std(hasher.finish()), native.finish());
+    /// ```
+    pub fn native_from_std(std_finish: u64) -> u32 {
+        std_finish as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use std::hash::BuildHasherDefault;
+    use std::hash::Hasher;
+    #[cfg(feature = "std")]
+    use StdFnvHasher;
+    use {Fnv0, Fnv1, Fnv1a, FnvHash};
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn boxed_u32_zero_extends() {
+        let mut hasher: Box<dyn Hasher> = Box::new(Fnv1a::<u32>::new().into_std_hasher());
+
+        hasher.write(b"foobar");
+
+        assert_eq!(hasher.finish(), 0xbf9c_f968);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn boxed_u128_folds() {
+        let mut native = Fnv1::<u128>::new();
+        native.write(b"foobar");
+        let native = native.finish();
+
+        let mut hasher: Box<dyn Hasher> = Box::new(Fnv1::<u128>::new().into_std_hasher());
+        hasher.write(b"foobar");
+
+        assert_eq!(hasher.finish(), ((native >> 64) as u64) ^ (native as u64));
+    }
+
+    #[test]
+    fn native_from_std_round_trips() {
+        for input in &[&b""[..], b"a", b"foobar", &[0xff; 100]] {
+            let mut native = Fnv0::<u32>::new();
+            FnvHash::write(&mut native, input);
+            let mut hasher = Fnv0::<u32>::new().into_std_hasher();
+            Hasher::write(&mut hasher, input);
+            assert_eq!(
+                Fnv0::<u32>::native_from_std(hasher.finish()),
+                FnvHash::finish(&native)
+            );
+
+            let mut native = Fnv1::<u32>::new();
+            FnvHash::write(&mut native, input);
+            let mut hasher = Fnv1::<u32>::new().into_std_hasher();
+            Hasher::write(&mut hasher, input);
+            assert_eq!(
+                Fnv1::<u32>::native_from_std(hasher.finish()),
+                FnvHash::finish(&native)
+            );
+
+            let mut native = Fnv1a::<u32>::new();
+            FnvHash::write(&mut native, input);
+            let mut hasher = Fnv1a::<u32>::new().into_std_hasher();
+            Hasher::write(&mut hasher, input);
+            assert_eq!(
+                Fnv1a::<u32>::native_from_std(hasher.finish()),
+                FnvHash::finish(&native)
+            );
+        }
+
+        assert_eq!(Fnv1a::<u32>::native_from_std(0xbf9c_f968), 0xbf9c_f968);
+    }
+
+    #[test]
+    fn into_inner_keeps_state() {
+        let mut hasher = Fnv1a::<u32>::new().into_std_hasher();
+        Hasher::write(&mut hasher, b"foo");
+
+        let mut inner = hasher.into_inner();
+        FnvHash::write(&mut inner, b"bar");
+
+        assert_eq!(FnvHash::finish(&inner), 0xbf9c_f968);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn usable_as_build_hasher() {
+        let mut map = ::std::collections::HashMap::<
+            &str,
+            u32,
+            BuildHasherDefault<StdFnvHasher<Fnv1a<u32>>>,
+        >::default();
+
+        map.insert("foobar", 1);
+
+        assert_eq!(map.get("foobar"), Some(&1));
+    }
+}
@@ -0,0 +1,302 @@
+//! The single-step FNV primitives.
+//!
+//! These operate on the raw accumulator held by the hashers, so hashing can
+//! be driven byte by byte without constructing a hasher. FNV-0 shares the
+//! FNV-1 step, differing only in its zero starting state. Each step is also
+//! available under a name ending in its type, such as `fnv1a_step_u64` for
+//! `fnv1a_64_step`.
+//!
+//! The FNV primes are odd and so invertible modulo `2^width`, which makes
+//! every step invertible. The unstep functions multiply by the inverse of the
+//! prime to undo a step; unstepping a byte which was not the last stepped
+//! produces garbage.
+
+macro_rules! step_impl {
+    (
+        $type: ty,
+        $prime: expr,
+        $inverse: expr,
+        $fnv1_step: ident,
+        $fnv1a_step: ident,
+        $fnv1_advance: ident,
+        $fnv1a_advance: ident,
+        $fnv1_unstep: ident,
+        $fnv1a_unstep: ident,
+        $width: expr
+    ) => {
+        #[doc = concat!("Performs one FNV-1 ", $width, " step: multiplies `state` by the prime, then XORs in `byte`.")]
+        #[inline]
+        pub const fn $fnv1_step(state: $type, byte: u8) -> $type {
+            state.wrapping_mul($prime) ^ byte as $type
+        }
+
+        #[doc = concat!("Performs one FNV-1a ", $width, " step: XORs in `byte`, then multiplies by the prime.")]
+        #[inline]
+        pub const fn $fnv1a_step(state: $type, byte: u8) -> $type {
+            (state ^ byte as $type).wrapping_mul($prime)
+        }
+
+        #[doc = concat!("Performs an FNV-1 ", $width, " step for each of `bytes`, returning the new state.")]
+        #[inline]
+        pub const fn $fnv1_advance(state: $type, bytes: &[u8]) -> $type {
+            let mut state = state;
+            let mut i = 0;
+
+            while i < bytes.len() {
+                state = $fnv1_step(state, bytes[i]);
+                i += 1;
+            }
+
+            state
+        }
+
+        #[doc = concat!("Performs an FNV-1a ", $width, " step for each of `bytes`, returning the new state.")]
+        #[inline]
+        pub const fn $fnv1a_advance(state: $type, bytes: &[u8]) -> $type {
+            let mut state = state;
+            let mut i = 0;
+
+            while i < bytes.len() {
+                state = $fnv1a_step(state, bytes[i]);
+                i += 1;
+            }
+
+            state
+        }
+
+        #[doc = concat!("Undoes one FNV-1 ", $width, " step: XORs out `byte`, then multiplies by the inverse of the prime.")]
+        #[inline]
+        pub const fn $fnv1_unstep(state: $type, byte: u8) -> $type {
+            (state ^ byte as $type).wrapping_mul($inverse)
+        }
+
+        #[doc = concat!("Undoes one FNV-1a ", $width, " step: multiplies `state` by the inverse of the prime, then XORs out `byte`.")]
+        #[inline]
+        pub const fn $fnv1a_unstep(state: $type, byte: u8) -> $type {
+            state.wrapping_mul($inverse) ^ byte as $type
+        }
+    };
+}
+
+pub(crate) const PRIME_32: u32 = 0x100_0193;
+pub(crate) const PRIME_64: u64 = 0x100_0000_01B3;
+pub(crate) const PRIME_128: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013B;
+
+pub(crate) const PRIME_INVERSE_32: u32 = 0x359c_449b;
+pub(crate) const PRIME_INVERSE_64: u64 = 0xce96_5057_aff6_957b;
+pub(crate) const PRIME_INVERSE_128: u128 = 0xb104_1ad2_562f_f2ff_2ff2_ff2f_f2ff_2ff3;
+
+/// Returns the inverse of the FNV 32 prime modulo `2^32`.
+///
+/// Multiplying by it undoes a multiplication by the prime, as the unstep
+/// functions do. It equals `<u32 as FnvWidth>::PRIME_INVERSE`.
+///
+/// ```
+/// use lz_fnv::prime_inverse_u32;
+///
+/// assert_eq!(0x0100_0193u32.wrapping_mul(prime_inverse_u32()), 1);
+/// ```
+pub const fn prime_inverse_u32() -> u32 {
+    PRIME_INVERSE_32
+}
+
+/// Returns the inverse of the FNV 64 prime modulo `2^64`.
+///
+/// Multiplying by it undoes a multiplication by the prime, as the unstep
+/// functions do. It equals `<u64 as FnvWidth>::PRIME_INVERSE`.
+///
+/// ```
+/// use lz_fnv::prime_inverse_u64;
+///
+/// assert_eq!(0x100_0000_01b3u64.wrapping_mul(prime_inverse_u64()), 1);
+/// ```
+pub const fn prime_inverse_u64() -> u64 {
+    PRIME_INVERSE_64
+}
+
+/// Returns the inverse of the FNV 128 prime modulo `2^128`.
+///
+/// Multiplying by it undoes a multiplication by the prime, as the unstep
+/// functions do. It equals `<u128 as FnvWidth>::PRIME_INVERSE`.
+pub const fn prime_inverse_u128() -> u128 {
+    PRIME_INVERSE_128
+}
+
+macro_rules! step_alias {
+    ($($alias: ident => $step: ident: $type: ty,)*) => {
+        $(
+            #[doc = concat!("Performs one step as [`", stringify!($step), "`], under a name ending in its type.")]
+            #[doc = ""]
+            #[doc = concat!("[`", stringify!($step), "`]: fn.", stringify!($step), ".html")]
+            #[inline]
+            pub const fn $alias(state: $type, byte: u8) -> $type {
+                $step(state, byte)
+            }
+        )*
+    };
+}
+
+step_impl!(
+    u32,
+    PRIME_32,
+    PRIME_INVERSE_32,
+    fnv1_32_step,
+    fnv1a_32_step,
+    fnv1_32_advance,
+    fnv1a_32_advance,
+    fnv1_32_unstep,
+    fnv1a_32_unstep,
+    "32"
+);
+step_impl!(
+    u64,
+    PRIME_64,
+    PRIME_INVERSE_64,
+    fnv1_64_step,
+    fnv1a_64_step,
+    fnv1_64_advance,
+    fnv1a_64_advance,
+    fnv1_64_unstep,
+    fnv1a_64_unstep,
+    "64"
+);
+step_impl!(
+    u128,
+    PRIME_128,
+    PRIME_INVERSE_128,
+    fnv1_128_step,
+    fnv1a_128_step,
+    fnv1_128_advance,
+    fnv1a_128_advance,
+    fnv1_128_unstep,
+    fnv1a_128_unstep,
+    "128"
+);
+
+step_alias! {
+    fnv1_step_u32 => fnv1_32_step: u32,
+    fnv1a_step_u32 => fnv1a_32_step: u32,
+    fnv1_step_u64 => fnv1_64_step: u64,
+    fnv1a_step_u64 => fnv1a_64_step: u64,
+    fnv1_step_u128 => fnv1_128_step: u128,
+    fnv1a_step_u128 => fnv1a_128_step: u128,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Fnv0, Fnv1, Fnv1a, FnvHash, FnvWidth};
+
+    const INPUTS: [&[u8]; 4] = [b"", b"a", b"foobar", b"chongo was here!\n"];
+
+    const FOOBAR_64: u64 = fnv1a_64_advance(0xcbf2_9ce4_8422_2325, b"foobar");
+    const A_32: u32 = fnv1_32_step(0x811c_9dc5, b'a');
+
+    macro_rules! step_tests {
+        ($($name: ident: $hasher: ty, $step: ident, $advance: ident,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    for input in &INPUTS {
+                        let mut hasher = <$hasher>::new();
+                        let initial = hasher.finish();
+                        hasher.write(input);
+
+                        let folded = input.iter().fold(initial, |state, &byte| $step(state, byte));
+
+                        assert_eq!(folded, hasher.finish());
+                        assert_eq!($advance(initial, input), hasher.finish());
+                    }
+                }
+            )*
+        };
+    }
+
+    step_tests! {
+        fnv0_32_steps: Fnv0<u32>, fnv1_32_step, fnv1_32_advance,
+        fnv1_32_steps: Fnv1<u32>, fnv1_32_step, fnv1_32_advance,
+        fnv1a_32_steps: Fnv1a<u32>, fnv1a_32_step, fnv1a_32_advance,
+        fnv0_64_steps: Fnv0<u64>, fnv1_64_step, fnv1_64_advance,
+        fnv1_64_steps: Fnv1<u64>, fnv1_64_step, fnv1_64_advance,
+        fnv1a_64_steps: Fnv1a<u64>, fnv1a_64_step, fnv1a_64_advance,
+        fnv0_128_steps: Fnv0<u128>, fnv1_128_step, fnv1_128_advance,
+        fnv1_128_steps: Fnv1<u128>, fnv1_128_step, fnv1_128_advance,
+        fnv1a_128_steps: Fnv1a<u128>, fnv1a_128_step, fnv1a_128_advance,
+    }
+
+    #[test]
+    fn inverses_invert_primes() {
+        assert_eq!(PRIME_32.wrapping_mul(PRIME_INVERSE_32), 1);
+        assert_eq!(PRIME_64.wrapping_mul(PRIME_INVERSE_64), 1);
+        assert_eq!(PRIME_128.wrapping_mul(PRIME_INVERSE_128), 1);
+    }
+
+    #[test]
+    fn public_inverses_invert_primes() {
+        assert_eq!(::u32::PRIME.wrapping_mul(prime_inverse_u32()), 1);
+        assert_eq!(::u64::PRIME.wrapping_mul(prime_inverse_u64()), 1);
+        assert_eq!(::u128::PRIME.wrapping_mul(prime_inverse_u128()), 1);
+        assert_eq!(prime_inverse_u64(), <u64 as FnvWidth>::PRIME_INVERSE);
+    }
+
+    macro_rules! unstep_tests {
+        ($($name: ident: $type: ty, $step: ident, $unstep: ident,)*) => {
+            $(
+                #[test]
+                fn $name() {
+                    // A fixed LCG gives reproducible "random" states
+                    let mut state: $type = 0x0123_4567;
+                    for i in 0..1_000u32 {
+                        state = state.wrapping_mul(0x4c95_7f2d).wrapping_add(1);
+                        let byte = (i * 31) as u8;
+
+                        assert_eq!($unstep($step(state, byte), byte), state);
+                        assert_eq!($step($unstep(state, byte), byte), state);
+                    }
+                }
+            )*
+        };
+    }
+
+    unstep_tests! {
+        fnv1_32_unstep_inverts_step: u32, fnv1_32_step, fnv1_32_unstep,
+        fnv1a_32_unstep_inverts_step: u32, fnv1a_32_step, fnv1a_32_unstep,
+        fnv1_64_unstep_inverts_step: u64, fnv1_64_step, fnv1_64_unstep,
+        fnv1a_64_unstep_inverts_step: u64, fnv1a_64_step, fnv1a_64_unstep,
+        fnv1_128_unstep_inverts_step: u128, fnv1_128_step, fnv1_128_unstep,
+        fnv1a_128_unstep_inverts_step: u128, fnv1a_128_step, fnv1a_128_unstep,
+    }
+
+    #[test]
+    fn aliases_fold_foobar() {
+        let mut hasher = Fnv1a::<u64>::new();
+        hasher.write(b"foobar");
+
+        let mut state = 0xcbf2_9ce4_8422_2325;
+        for &byte in b"foobar" {
+            state = fnv1a_step_u64(state, byte);
+        }
+        assert_eq!(state, hasher.finish());
+
+        let mut hasher = Fnv1::<u64>::new();
+        hasher.write(b"foobar");
+
+        let folded = b"foobar"
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325, |state, &byte| {
+                fnv1_step_u64(state, byte)
+            });
+        assert_eq!(folded, hasher.finish());
+
+        assert_eq!(fnv1a_step_u32(0x811c_9dc5, b'a'), 0xe40c_292c);
+        assert_eq!(fnv1_step_u32(0x811c_9dc5, b'a'), 0x050c_5d7e);
+        assert_eq!(fnv1a_step_u128(1, 0), fnv1a_128_step(1, 0));
+        assert_eq!(fnv1_step_u128(1, 0), fnv1_128_step(1, 0));
+    }
+
+    #[test]
+    fn steps_are_const() {
+        assert_eq!(FOOBAR_64, 0x8594_4171_f739_67e8);
+        assert_eq!(A_32, 0x050c_5d7e);
+    }
+}
@@ -0,0 +1,209 @@
+//! Hashing of asynchronous streams of chunks, such as HTTP bodies.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::future::{self, TryFutureExt};
+use futures::stream::{Stream, TryStreamExt};
+
+use {Fnv1a, FnvHash};
+
+/// Drives a stream of chunks to completion, returning the FNV-1a 64 hash of
+/// their concatenation or the first error.
+///
+/// The chunks may be any `AsRef<[u8]>`, such as the `Bytes` of a `hyper` or
+/// `reqwest` body, and are hashed as they arrive rather than buffered. The
+/// stream is not polled after an error. An empty stream hashes to the offset
+/// basis.
+///
+/// ```
+/// extern crate futures;
+/// extern crate lz_fnv;
+///
+/// use futures::executor::block_on;
+/// use futures::stream;
+/// use lz_fnv::hash_byte_stream;
+///
+/// # fn main() {
+/// let body = stream::iter(vec![Ok::<_, ()>(&b"foo"[..]), Ok(&b"bar"[..])]);
+///
+/// assert_eq!(block_on(hash_byte_stream(body)), Ok(0x8594_4171_f739_67e8));
+/// # }
+/// ```
+pub fn hash_byte_stream<S, B, E>(stream: S) -> impl Future<Output = Result<u64, E>>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    stream
+        .try_fold(Fnv1a::<u64>::new(), |mut hasher, chunk| {
+            hasher.write(chunk.as_ref());
+            future::ready(Ok(hasher))
+        })
+        .map_ok(|hasher| hasher.finish())
+}
+
+/// A stream which passes the chunks of another through unchanged while
+/// hashing them with FNV-1a 64.
+///
+/// This records the fingerprint of a body while forwarding it. Errors are
+/// passed through without affecting the hash. The wrapped stream must be
+/// `Unpin`; a stream which is not can be wrapped after pinning it with
+/// `Box::pin`.
+///
+/// ```
+/// extern crate futures;
+/// extern crate lz_fnv;
+///
+/// use futures::executor::block_on;
+/// use futures::stream::{self, StreamExt};
+/// use lz_fnv::HashedStream;
+///
+/// # fn main() {
+/// let body = stream::iter(vec![Ok::<_, ()>(&b"foo"[..]), Ok(&b"bar"[..])]);
+/// let mut hashed = HashedStream::new(body);
+///
+/// let forwarded: Vec<_> = block_on((&mut hashed).collect());
+///
+/// assert_eq!(forwarded, [Ok(&b"foo"[..]), Ok(&b"bar"[..])]);
+/// assert_eq!(hashed.hash(), 0x8594_4171_f739_67e8);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct HashedStream<S> {
+    stream: S,
+    hasher: Fnv1a<u64>,
+}
+
+impl<S> HashedStream<S> {
+    /// Creates a new `HashedStream` wrapping the specified stream.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            hasher: Fnv1a::new(),
+        }
+    }
+
+    /// Returns the hash of the chunks passed through so far.
+    pub fn hash(&self) -> u64 {
+        self.hasher.finish()
+    }
+
+    /// Consumes this `HashedStream`, returning the wrapped stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S, B, E> Stream for HashedStream<S>
+where
+    S: Stream<Item = Result<B, E>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    type Item = Result<B, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        let poll = Pin::new(&mut this.stream).poll_next(cx);
+        if let Poll::Ready(Some(Ok(ref chunk))) = poll {
+            this.hasher.write(chunk.as_ref());
+        }
+
+        poll
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::executor::block_on;
+    use futures::future;
+    use futures::stream::{self, StreamExt};
+
+    use {hash_byte_stream, FnvExt, HashedStream};
+
+    fn contents() -> Vec<u8> {
+        (0..10_000u32).map(|i| (i * 31) as u8).collect()
+    }
+
+    fn chunks(contents: &[u8]) -> Vec<Result<Bytes, String>> {
+        contents
+            .chunks(777)
+            .map(|chunk| Ok(Bytes::copy_from_slice(chunk)))
+            .collect()
+    }
+
+    #[test]
+    fn matches_one_shot() {
+        let contents = contents();
+
+        let hash = block_on(hash_byte_stream(stream::iter(chunks(&contents))));
+        assert_eq!(hash, Ok(contents.fnv1a_64()));
+
+        let empty = stream::iter(Vec::<Result<Bytes, String>>::new());
+        assert_eq!(block_on(hash_byte_stream(empty)), Ok(0xcbf2_9ce4_8422_2325));
+    }
+
+    #[test]
+    fn errors_propagate_mid_stream() {
+        let mut chunks = chunks(&contents());
+        chunks.insert(3, Err("connection reset".to_string()));
+
+        let polled = stream::iter(chunks).enumerate().map(|(i, chunk)| {
+            assert!(i <= 3, "chunk {} was polled after the error", i);
+            chunk
+        });
+
+        assert_eq!(
+            block_on(hash_byte_stream(polled)),
+            Err("connection reset".to_string())
+        );
+    }
+
+    #[test]
+    fn hashed_stream_passes_chunks_through() {
+        let contents = contents();
+        let mut hashed = HashedStream::new(stream::iter(chunks(&contents)));
+
+        let forwarded: Vec<_> = block_on((&mut hashed).collect());
+
+        assert_eq!(forwarded, chunks(&contents));
+        assert_eq!(hashed.hash(), contents.fnv1a_64());
+    }
+
+    #[test]
+    fn hashed_stream_passes_errors_through() {
+        let items = vec![
+            Ok(Bytes::from_static(b"foo")),
+            Err("timeout".to_string()),
+            Ok(Bytes::from_static(b"bar")),
+        ];
+        let mut hashed = HashedStream::new(stream::iter(items.clone()));
+
+        assert_eq!(hashed.hash(), 0xcbf2_9ce4_8422_2325);
+        let forwarded: Vec<_> = block_on((&mut hashed).collect());
+
+        assert_eq!(forwarded, items);
+        assert_eq!(hashed.hash(), b"foobar".fnv1a_64());
+    }
+
+    #[test]
+    fn hashed_stream_of_pinned_stream() {
+        let contents = contents();
+        let unfold = stream::unfold(chunks(&contents).into_iter(), |mut chunks| {
+            future::ready(chunks.next().map(|chunk| (chunk, chunks)))
+        });
+        let mut hashed = HashedStream::new(Box::pin(unfold));
+
+        let forwarded: Vec<_> = block_on((&mut hashed).collect());
+
+        assert_eq!(forwarded, chunks(&contents));
+        assert_eq!(hashed.hash(), contents.fnv1a_64());
+    }
+}
@@ -0,0 +1,62 @@
+//! Summaries of several hashes.
+
+use {Fnv1a, FnvHash};
+
+/// Summarizes the finished values of several hashers, in slice order, as one
+/// hash.
+///
+/// Each hasher's value is hashed, as 8 little-endian bytes, into a fresh
+/// FNV-1a 64 hasher. This is a stable fingerprint of the values, for example
+/// of chunks hashed in parallel, but it is not the hash of the concatenated
+/// chunks, which cannot be recovered from their hashes.
+///
+/// ```
+/// use lz_fnv::{summarize, Fnv1a, FnvHash};
+///
+/// let mut first = Fnv1a::<u64>::new();
+/// first.write(b"chunk one");
+/// let mut second = Fnv1a::<u64>::new();
+/// second.write(b"chunk two");
+///
+/// let summary = summarize(&[first.clone(), second.clone()]);
+///
+/// assert_ne!(summary, summarize(&[second, first]));
+/// ```
+pub fn summarize(hashers: &[Fnv1a<u64>]) -> u64 {
+    let mut summary = Fnv1a::<u64>::new();
+    for hasher in hashers {
+        summary.write(&hasher.finish().to_le_bytes());
+    }
+
+    summary.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use {summarize, Fnv1a, FnvHash};
+
+    fn hasher(chunk: &[u8]) -> Fnv1a<u64> {
+        let mut fnv1a = Fnv1a::<u64>::new();
+        fnv1a.write(chunk);
+        fnv1a
+    }
+
+    #[test]
+    fn summary_is_order_sensitive() {
+        let hashers = [hasher(b"one"), hasher(b"two"), hasher(b"three")];
+
+        let mut expected = Fnv1a::<u64>::new();
+        for hasher in &hashers {
+            expected.write(&hasher.finish().to_le_bytes());
+        }
+        assert_eq!(summarize(&hashers), expected.finish());
+
+        let swapped = [hasher(b"three"), hasher(b"two"), hasher(b"one")];
+        assert_ne!(summarize(&swapped), summarize(&hashers));
+    }
+
+    #[test]
+    fn empty_summary() {
+        assert_eq!(summarize(&[]), Fnv1a::<u64>::new().finish());
+    }
+}
@@ -0,0 +1,278 @@
+//! Hashing of the members of tar archives without unpacking them.
+
+use std::io;
+use std::iter;
+use std::path::PathBuf;
+
+use tar::{Archive, EntryType};
+
+use file::write_reader;
+use {Fnv1a, FnvHash};
+
+/// Options controlling how [`hash_tar_entries`] treats entries which are not
+/// regular files.
+///
+/// [`hash_tar_entries`]: fn.hash_tar_entries.html
+#[derive(Debug, Clone, Default)]
+pub struct TarHashOptions {
+    report_non_files: bool,
+}
+
+impl TarHashOptions {
+    /// Creates the default options, which skip entries that are not regular
+    /// files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether entries which are not regular files, such as directories
+    /// and links, are reported as errors of kind `InvalidInput` instead of
+    /// being skipped.
+    pub fn report_non_files(mut self, report_non_files: bool) -> Self {
+        self.report_non_files = report_non_files;
+        self
+    }
+}
+
+/// Hashes the contents of each regular file in a tar archive with FNV-1a 64.
+///
+/// The archive is read once, from start to end, and each member's contents
+/// are streamed through the hasher rather than unpacked or read into memory.
+/// Paths are those recorded in the archive, with GNU and PAX long name
+/// extensions resolved. The returned iterator is lazy: each call to `next`
+/// reads the archive only as far as the next result, so memory use does not
+/// grow with the number of entries and an error is yielded as soon as it is
+/// read. An error reading the archive itself ends the iteration.
+///
+/// The archive is borrowed rather than taken by value, because the `tar`
+/// crate's entries borrow their archive. This also allows settings such as
+/// `Archive::set_ignore_zeros` to be made before hashing.
+///
+/// ```no_run
+/// extern crate lz_fnv;
+/// extern crate tar;
+///
+/// use lz_fnv::{hash_tar_entries, TarHashOptions};
+/// use std::fs::File;
+/// use tar::Archive;
+///
+/// # fn main() {
+/// let mut archive = Archive::new(File::open("release.tar").unwrap());
+///
+/// for entry in hash_tar_entries(&mut archive, &TarHashOptions::new()) {
+///     let (path, hash) = entry.unwrap();
+///     println!("{:016x}  {}", hash, path.display());
+/// }
+/// # }
+/// ```
+pub fn hash_tar_entries<'a, R: io::Read + 'a>(
+    archive: &'a mut Archive<R>,
+    options: &TarHashOptions,
+) -> impl Iterator<Item = io::Result<(PathBuf, u64)>> + 'a {
+    let report_non_files = options.report_non_files;
+    let mut entries = Some(archive.entries());
+
+    iter::from_fn(move || loop {
+        let entry = match entries.as_mut()? {
+            Ok(entries) => entries.next(),
+            Err(_) => return entries.take().and_then(Result::err).map(Err),
+        };
+
+        let mut entry = match entry? {
+            Ok(entry) => entry,
+            Err(e) => {
+                entries = None;
+                return Some(Err(e));
+            }
+        };
+
+        let path = match entry.path() {
+            Ok(path) => path.into_owned(),
+            Err(e) => return Some(Err(e)),
+        };
+
+        let entry_type = entry.header().entry_type();
+        if entry_type != EntryType::Regular && entry_type != EntryType::Continuous {
+            if report_non_files {
+                return Some(Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{}: not a regular file", path.display()),
+                )));
+            }
+            continue;
+        }
+
+        let mut hasher = Fnv1a::<u64>::new();
+        return match write_reader(&mut hasher, &mut entry) {
+            Ok(()) => Some(Ok((path, hasher.finish()))),
+            Err(e) => {
+                entries = None;
+                Some(Err(e))
+            }
+        };
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::io::{self, Read};
+    use std::path::{Path, PathBuf};
+    use tar::{Archive, Builder, EntryType, Header};
+
+    use {hash_tar_entries, Fnv1a, FnvHash, TarHashOptions};
+
+    fn fnv1a_64(bytes: &[u8]) -> u64 {
+        let mut hasher = Fnv1a::<u64>::new();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    fn append_file(builder: &mut Builder<Vec<u8>>, path: &str, contents: &[u8]) {
+        let mut header = Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, path, contents).unwrap();
+    }
+
+    fn append_dir(builder: &mut Builder<Vec<u8>>, path: &str) {
+        let mut header = Header::new_gnu();
+        header.set_entry_type(EntryType::Directory);
+        header.set_size(0);
+        header.set_mode(0o755);
+        builder.append_data(&mut header, path, io::empty()).unwrap();
+    }
+
+    fn long_path() -> String {
+        format!("{}/file.txt", ["nested"; 30].join("/"))
+    }
+
+    fn build_archive(large: &[u8]) -> Vec<u8> {
+        let mut builder = Builder::new(Vec::new());
+        append_dir(&mut builder, "a/");
+        append_file(&mut builder, "a/one.txt", b"one");
+        append_file(&mut builder, "a/b/two.txt", b"two");
+        append_file(&mut builder, "empty", b"");
+        append_file(&mut builder, "large.bin", large);
+        append_file(&mut builder, &long_path(), b"long");
+        builder.into_inner().unwrap()
+    }
+
+    fn large() -> Vec<u8> {
+        (0..100_000u32).map(|i| (i * 7) as u8).collect()
+    }
+
+    #[test]
+    fn hashes_match_buffers() {
+        let large = large();
+        let archive = build_archive(&large);
+
+        let entries: Vec<_> =
+            hash_tar_entries(&mut Archive::new(&archive[..]), &TarHashOptions::new())
+                .map(Result::unwrap)
+                .collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                (PathBuf::from("a/one.txt"), fnv1a_64(b"one")),
+                (PathBuf::from("a/b/two.txt"), fnv1a_64(b"two")),
+                (PathBuf::from("empty"), fnv1a_64(b"")),
+                (PathBuf::from("large.bin"), fnv1a_64(&large)),
+                (PathBuf::from(long_path()), fnv1a_64(b"long")),
+            ]
+        );
+    }
+
+    #[test]
+    fn long_names_resolve() {
+        let archive = build_archive(b"");
+        let long_path = long_path();
+        assert!(long_path.len() > 100);
+
+        assert!(
+            hash_tar_entries(&mut Archive::new(&archive[..]), &TarHashOptions::new())
+                .any(|entry| entry.unwrap().0 == Path::new(&long_path))
+        );
+    }
+
+    #[test]
+    fn reports_non_files() {
+        let archive = build_archive(b"");
+
+        let options = TarHashOptions::new().report_non_files(true);
+        let entries: Vec<_> = hash_tar_entries(&mut Archive::new(&archive[..]), &options).collect();
+
+        assert_eq!(entries.len(), 6);
+        let error = entries[0].as_ref().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "a/: not a regular file");
+        assert!(entries[1..].iter().all(Result::is_ok));
+    }
+
+    #[test]
+    fn truncated_archive_fails() {
+        let archive = build_archive(&large());
+
+        let entries: Vec<_> = hash_tar_entries(
+            &mut Archive::new(&archive[..50_000]),
+            &TarHashOptions::new(),
+        )
+        .collect();
+
+        assert!(entries.last().unwrap().is_err());
+    }
+
+    /// A reader recording how many bytes have been read through it.
+    struct Tracked<'a> {
+        inner: &'a [u8],
+        read: &'a Cell<usize>,
+    }
+
+    impl<'a> Read for Tracked<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.read.set(self.read.get() + read);
+            Ok(read)
+        }
+    }
+
+    #[test]
+    fn entries_are_hashed_lazily() {
+        let mut builder = Builder::new(Vec::new());
+        append_file(&mut builder, "first", b"first");
+        for i in 0..100 {
+            append_file(&mut builder, &format!("file{}", i), &large());
+        }
+        let archive = builder.into_inner().unwrap();
+
+        let read = Cell::new(0);
+        let mut tracked = Archive::new(Tracked {
+            inner: &archive,
+            read: &read,
+        });
+        let mut entries = hash_tar_entries(&mut tracked, &TarHashOptions::new());
+
+        let first = entries.next().unwrap().unwrap();
+        assert_eq!(first, (PathBuf::from("first"), fnv1a_64(b"first")));
+        assert!(read.get() < archive.len() / 10);
+
+        assert_eq!(entries.count(), 100);
+        assert!(read.get() > 100 * large().len());
+    }
+
+    #[test]
+    fn errors_are_yielded_in_order() {
+        let archive = build_archive(&large());
+
+        let mut truncated = Archive::new(&archive[..50_000]);
+        let mut entries = hash_tar_entries(&mut truncated, &TarHashOptions::new());
+
+        assert_eq!(
+            entries.next().unwrap().unwrap(),
+            (PathBuf::from("a/one.txt"), fnv1a_64(b"one"))
+        );
+        assert!(entries.by_ref().any(|entry| entry.is_err()));
+        assert!(entries.next().is_none());
+    }
+}
@@ -0,0 +1,465 @@
+//! Utilities for testing code which consumes FNV hashes.
+//!
+//! These are intended for defensive testing only: checking that a service
+//! keyed by FNV-1a 64 degrades acceptably under hash flooding before an
+//! attacker checks it for you. FNV is not a keyed or collision-resistant hash
+//! and must not be exposed to untrusted keys where flooding matters.
+
+use step::PRIME_64;
+use {fnv1a_64_step, Fnv1a, FnvHash};
+
+const BLOCK_LEN: usize = 16;
+
+/// The length of the suffixes produced by [`suffix_for_target`].
+///
+/// [`suffix_for_target`]: fn.suffix_for_target.html
+pub const SUFFIX_LEN: usize = 16;
+
+/// A vector `d` with `sum(d[k] * p^(16 - k)) == 0 (mod 2^64)` for the 64-bit
+/// FNV prime `p`, found by lattice reduction.
+const BLOCK_DIFFERENCE: [i16; BLOCK_LEN] =
+    [2, -2, -5, 8, 7, 7, -3, -6, 0, -2, -9, -1, 4, -8, -1, 3];
+
+/// Finds two distinct blocks which both take FNV-1a 64 from `state` to the
+/// same state, or `None` if this `tweak` does not yield a pair.
+fn colliding_blocks(state: u64, tweak: u64) -> Option<([u8; BLOCK_LEN], [u8; BLOCK_LEN])> {
+    let (mut state_a, mut state_b) = (state, state);
+    let (mut block_a, mut block_b) = ([0; BLOCK_LEN], [0; BLOCK_LEN]);
+    let mut noise = tweak;
+
+    for k in 0..BLOCK_LEN {
+        let (low_a, low_b) = (i32::from(state_a as u8), i32::from(state_b as u8));
+        let shift = i32::from(BLOCK_DIFFERENCE[k]) + low_b - low_a;
+
+        let min = 0.max(-shift);
+        let max = 255.min(255 - shift);
+        if min > max {
+            return None;
+        }
+
+        noise = fnv1a_64_step(noise, k as u8);
+        let low = min + (noise % (max - min + 1) as u64) as i32;
+
+        block_a[k] = (low_a ^ low) as u8;
+        block_b[k] = (low_b ^ (low + shift)) as u8;
+        state_a = fnv1a_64_step(state_a, block_a[k]);
+        state_b = fnv1a_64_step(state_b, block_b[k]);
+    }
+
+    debug_assert_eq!(state_a, state_b);
+    Some((block_a, block_b))
+}
+
+/// Generates `count` distinct byte strings which all have the same FNV-1a 64
+/// hash.
+///
+/// Every string starts with the same `target_prefix_len` bytes of lowercase
+/// ASCII, followed by a number of 16-byte blocks. The construction relies on
+/// the FNV-1a step being invertible: choosing a byte sets the low 8 bits of
+/// the state before it is multiplied by the prime, so a block is equivalent to
+/// adding a chosen small value before each of its multiplications. Two blocks
+/// whose added values differ by a fixed vector in the kernel of the map
+/// `d -> sum(d[k] * p^(16 - k)) mod 2^64` leave the hasher in the same state.
+/// Chaining `n` such pairs, choosing one block of each pair by the bits of
+/// the key's index, gives `2^n` distinct colliding strings, of which the
+/// first `count` are returned.
+///
+/// Requesting zero keys returns an empty `Vec` and requesting one returns just
+/// the prefix.
+///
+/// ```
+/// use lz_fnv::testing::generate_collisions;
+/// use lz_fnv::{Fnv1a, FnvHash};
+///
+/// let keys = generate_collisions(4, 3);
+///
+/// let hash = |key: &[u8]| {
+///     let mut hasher = Fnv1a::<u64>::new();
+///     hasher.write(key);
+///     hasher.finish()
+/// };
+///
+/// assert_eq!(hash(&keys[0]), hash(&keys[1]));
+/// assert_eq!(hash(&keys[1]), hash(&keys[2]));
+/// ```
+pub fn generate_collisions(target_prefix_len: usize, count: usize) -> Vec<Vec<u8>> {
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let prefix: Vec<u8> = (0..target_prefix_len)
+        .map(|i| b'a' + (i % 26) as u8)
+        .collect();
+
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(&prefix);
+
+    let stages = count.next_power_of_two().trailing_zeros();
+    let mut pairs = Vec::with_capacity(stages as usize);
+    for _ in 0..stages {
+        let state = hasher.finish();
+        let pair = (0..)
+            .filter_map(|tweak| colliding_blocks(state, tweak))
+            .next()
+            .unwrap();
+
+        hasher.write(&pair.0);
+        pairs.push(pair);
+    }
+
+    (0..count)
+        .map(|i| {
+            let mut key = prefix.clone();
+            for (stage, (a, b)) in pairs.iter().enumerate() {
+                key.extend_from_slice(if (i >> stage) & 1 == 0 { a } else { b });
+            }
+            key
+        })
+        .collect()
+}
+
+fn prime_power(exponent: usize) -> u64 {
+    (0..exponent).fold(1, |power, _| power.wrapping_mul(PRIME_64))
+}
+
+/// Returns the contribution of the value `low`, the low byte of the state
+/// after the XOR at `position` in a suffix, to the final state.
+fn term(position: usize, low: u8) -> u64 {
+    let stepped = u64::from(low).wrapping_mul(PRIME_64);
+    if position == SUFFIX_LEN - 1 {
+        return stepped;
+    }
+
+    // The low byte of the stepped state is replaced by the next XOR, so only
+    // its high bits carry forward.
+    (stepped & !0xff).wrapping_mul(prime_power(SUFFIX_LEN - 1 - position))
+}
+
+/// Joins the entries of `left` and `right` whose sums are zero in the bits of
+/// `mask`.
+fn merge<A, B, C, F>(
+    left: &mut [(u64, A)],
+    right: &mut [(u64, B)],
+    mask: u64,
+    combine: F,
+) -> Vec<(u64, C)>
+where
+    A: Copy,
+    B: Copy,
+    F: Fn(A, B) -> C,
+{
+    left.sort_unstable_by_key(|&(sum, _)| sum & mask);
+    right.sort_unstable_by_key(|&(sum, _)| sum.wrapping_neg() & mask);
+
+    let mut merged = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < left.len() && j < right.len() {
+        let key = left[i].0 & mask;
+        let other = right[j].0.wrapping_neg() & mask;
+
+        if key < other {
+            i += 1;
+        } else if key > other {
+            j += 1;
+        } else {
+            let end_i = i + left[i..].iter().take_while(|e| e.0 & mask == key).count();
+            let end_j = j + right[j..]
+                .iter()
+                .take_while(|e| e.0.wrapping_neg() & mask == key)
+                .count();
+
+            for &(x, a) in &left[i..end_i] {
+                for &(y, b) in &right[j..end_j] {
+                    merged.push((x.wrapping_add(y), combine(a, b)));
+                }
+            }
+
+            i = end_i;
+            j = end_j;
+        }
+    }
+
+    merged
+}
+
+/// Runs Wagner's 8-list algorithm, zeroing the low `first_bits` bits of the
+/// sums of pairs of lists, then the low 40 bits, then all 64. The low 8 bits
+/// of every entry are already zero.
+fn solve(lists: &[Vec<(u64, u16)>], first_bits: u32) -> Option<[u16; 8]> {
+    let mask = (1 << first_bits) - 1;
+
+    let mut level_1: Vec<Vec<(u64, [u16; 2])>> = lists
+        .chunks(2)
+        .map(|pair| {
+            let (mut left, mut right) = (pair[0].clone(), pair[1].clone());
+            merge(&mut left, &mut right, mask, |a, b| [a, b])
+        })
+        .collect();
+
+    let mut level_2: Vec<Vec<(u64, [u16; 4])>> = level_1
+        .chunks_mut(2)
+        .map(|pair| {
+            let (left, right) = pair.split_at_mut(1);
+            merge(&mut left[0], &mut right[0], (1 << 40) - 1, |a, b| {
+                [a[0], a[1], b[0], b[1]]
+            })
+        })
+        .collect();
+
+    let (left, right) = level_2.split_at_mut(1);
+    merge(&mut left[0], &mut right[0], !0, |a, b| {
+        [a[0], a[1], a[2], a[3], b[0], b[1], b[2], b[3]]
+    })
+    .first()
+    .map(|&(_, indices)| indices)
+}
+
+/// Computes a suffix which, written to an FNV-1a 64 hasher in the state
+/// `current_state`, leaves it in the state `target`.
+///
+/// This demonstrates that FNV has no preimage resistance, and is intended
+/// for building test fixtures with chosen hashes. It must not be used to
+/// justify FNV anywhere an adversary chooses keys.
+///
+/// The construction follows from each step being invertible. XORing in a
+/// byte sets the low 8 bits of the state to any chosen value `u`, and the
+/// low 8 bits of `u * p` are replaced by the next XOR, so the final state is
+/// a constant determined by `current_state` plus an independent term for
+/// each chosen `u`, modulo `2^64`. Only the last term affects the low 8 bits,
+/// which fixes the last `u`. Finding the other 15 values so that their terms
+/// sum to the remainder is a generalized birthday problem over the high 56
+/// bits, which Wagner's 8-list algorithm solves over lists of the sums of
+/// pairs of terms. The chosen values are then converted back into bytes by
+/// hashing forward.
+///
+/// The suffix is 16 bytes rather than the 8 which were first asked for,
+/// because no 8-byte suffix exists for some targets. There are as many 8-byte
+/// suffixes as targets, but two distinct 8-byte inputs, `ca1b3438467703a5`
+/// and `49734fb40c674d95` in hex, have the same FNV-1a 64 hash, so from the
+/// offset basis at least one target has no 8-byte suffix. Even where one
+/// exists, finding it takes around `2^32` steps.
+///
+/// ```
+/// use lz_fnv::testing::suffix_for_target;
+/// use lz_fnv::{Fnv1a, FnvHash};
+///
+/// let mut fnv_hasher = Fnv1a::<u64>::new();
+/// fnv_hasher.write(b"fixture");
+///
+/// let suffix = suffix_for_target(fnv_hasher.finish(), 0);
+/// fnv_hasher.write(&suffix);
+///
+/// assert_eq!(fnv_hasher.finish(), 0);
+/// ```
+pub fn suffix_for_target(current_state: u64, target: u64) -> [u8; SUFFIX_LEN] {
+    let required =
+        target.wrapping_sub((current_state & !0xff).wrapping_mul(prime_power(SUFFIX_LEN)));
+
+    let last = (0..=u8::MAX)
+        .find(|&low| term(SUFFIX_LEN - 1, low) as u8 == required as u8)
+        .unwrap();
+    let required = required.wrapping_sub(term(SUFFIX_LEN - 1, last));
+
+    // Seven lists pair up the positions before the last two, and the eighth
+    // holds the second to last position alone.
+    let mut lists: Vec<Vec<(u64, u16)>> = (0..7)
+        .map(|pair| {
+            (0..=u16::MAX)
+                .map(|index| {
+                    let (a, b) = ((index >> 8) as u8, index as u8);
+                    let sum = term(2 * pair, a).wrapping_add(term(2 * pair + 1, b));
+                    (sum, index)
+                })
+                .collect()
+        })
+        .collect();
+    lists.push(
+        (0..=u8::MAX)
+            .map(|low| (term(SUFFIX_LEN - 2, low), u16::from(low)))
+            .collect(),
+    );
+
+    for entry in &mut lists[0] {
+        entry.0 = entry.0.wrapping_sub(required);
+    }
+
+    // Each attempt expects several solutions, so later attempts are only a
+    // safeguard against an unlucky target.
+    let indices = [22, 21, 23]
+        .iter()
+        .filter_map(|&first_bits| solve(&lists, first_bits))
+        .next()
+        .expect("no suffix found for the target");
+
+    let mut lows = [0; SUFFIX_LEN];
+    for (pair, &index) in indices[..7].iter().enumerate() {
+        lows[2 * pair] = (index >> 8) as u8;
+        lows[2 * pair + 1] = index as u8;
+    }
+    lows[SUFFIX_LEN - 2] = indices[7] as u8;
+    lows[SUFFIX_LEN - 1] = last;
+
+    let mut suffix = [0; SUFFIX_LEN];
+    let mut state = current_state;
+    for (byte, &low) in suffix.iter_mut().zip(&lows) {
+        *byte = state as u8 ^ low;
+        state = fnv1a_64_step(state, *byte);
+    }
+
+    debug_assert_eq!(state, target);
+    suffix
+}
+
+/// Returns `prefix` followed by a suffix which gives the whole key the FNV-1a
+/// 64 hash `target`.
+///
+/// See [`suffix_for_target`] for the construction and its intended use.
+///
+/// [`suffix_for_target`]: fn.suffix_for_target.html
+///
+/// ```
+/// use lz_fnv::testing::key_with_hash;
+/// use lz_fnv::{Fnv1a, FnvHash};
+///
+/// let key = key_with_hash(b"user:", 0xdead_beef_dead_beef);
+///
+/// let mut fnv_hasher = Fnv1a::<u64>::new();
+/// fnv_hasher.write(&key);
+///
+/// assert!(key.starts_with(b"user:"));
+/// assert_eq!(fnv_hasher.finish(), 0xdead_beef_dead_beef);
+/// ```
+pub fn key_with_hash(prefix: &[u8], target: u64) -> Vec<u8> {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(prefix);
+
+    let mut key = prefix.to_vec();
+    key.extend_from_slice(&suffix_for_target(hasher.finish(), target));
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        colliding_blocks, generate_collisions, key_with_hash, suffix_for_target, BLOCK_DIFFERENCE,
+    };
+    use std::collections::HashSet;
+    use {Fnv1a, FnvHash};
+
+    fn fnv1a_64(bytes: &[u8]) -> u64 {
+        let mut hasher = Fnv1a::<u64>::new();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    #[test]
+    fn difference_is_in_kernel() {
+        let prime = 0x100_0000_01B3u64;
+        let sum = BLOCK_DIFFERENCE
+            .iter()
+            .rev()
+            .fold((0u64, prime), |(sum, power), &d| {
+                (
+                    sum.wrapping_add((d as u64).wrapping_mul(power)),
+                    power.wrapping_mul(prime),
+                )
+            })
+            .0;
+
+        assert_eq!(sum, 0);
+    }
+
+    #[test]
+    fn blocks_collide_from_any_state() {
+        for state in (0..100u64).map(|i| i.wrapping_mul(0x9e37_79b9_7f4a_7c15)) {
+            let (a, b) = (0..)
+                .filter_map(|tweak| colliding_blocks(state, tweak))
+                .next()
+                .unwrap();
+
+            assert_ne!(a, b);
+            let mut hasher_a = Fnv1a::with_key(state);
+            let mut hasher_b = Fnv1a::with_key(state);
+            hasher_a.write(&a);
+            hasher_b.write(&b);
+            assert_eq!(hasher_a.finish(), hasher_b.finish());
+        }
+    }
+
+    #[test]
+    fn keys_are_distinct_and_collide() {
+        for &prefix_len in &[0, 1, 7, 32] {
+            let keys = generate_collisions(prefix_len, 1000);
+            assert_eq!(keys.len(), 1000);
+
+            let distinct: HashSet<_> = keys.iter().collect();
+            assert_eq!(distinct.len(), keys.len());
+
+            let expected = fnv1a_64(&keys[0]);
+            for key in &keys {
+                assert_eq!(&key[..prefix_len], &keys[0][..prefix_len]);
+                assert_eq!(fnv1a_64(key), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn zero_and_one_keys() {
+        assert!(generate_collisions(8, 0).is_empty());
+        assert_eq!(generate_collisions(3, 1), vec![b"abc".to_vec()]);
+        assert_eq!(generate_collisions(0, 1), vec![Vec::<u8>::new()]);
+    }
+
+    #[test]
+    fn two_keys() {
+        let keys = generate_collisions(5, 2);
+
+        assert_eq!(keys.len(), 2);
+        assert_ne!(keys[0], keys[1]);
+        assert_eq!(fnv1a_64(&keys[0]), fnv1a_64(&keys[1]));
+    }
+
+    #[test]
+    fn eight_byte_suffixes_cannot_reach_every_target() {
+        // Two distinct 8-byte suffixes of the offset basis leave the same
+        // state, so the 2^64 suffixes of 8 bytes reach fewer than 2^64 states
+        let a = [0xca, 0x1b, 0x34, 0x38, 0x46, 0x77, 0x03, 0xa5];
+        let b = [0x49, 0x73, 0x4f, 0xb4, 0x0c, 0x67, 0x4d, 0x95];
+
+        assert_ne!(a, b);
+        assert_eq!(fnv1a_64(&a), fnv1a_64(&b));
+        assert_eq!(fnv1a_64(&a), 0x98f5_623c_7015_cabd);
+    }
+
+    #[test]
+    fn suffix_reaches_target() {
+        let mut seed = 0x0123_4567_89ab_cdefu64;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            seed
+        };
+
+        for _ in 0..4 {
+            let (state, target) = (next(), next());
+            let suffix = suffix_for_target(state, target);
+
+            let mut hasher = Fnv1a::with_key(state);
+            hasher.write(&suffix);
+            assert_eq!(hasher.finish(), target);
+        }
+    }
+
+    #[test]
+    fn sentinel_targets() {
+        for &target in &[0, u64::MAX, 0xdead_beef_dead_beef] {
+            let key = key_with_hash(b"object-", target);
+
+            assert!(key.starts_with(b"object-"));
+            assert_eq!(fnv1a_64(&key), target);
+        }
+
+        assert_eq!(fnv1a_64(&key_with_hash(b"", 1)), 1);
+    }
+}
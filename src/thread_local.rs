@@ -0,0 +1,73 @@
+//! One-shot hashing through a thread-local hasher.
+
+use std::cell::RefCell;
+
+use {Fnv1a, FnvHash};
+
+thread_local! {
+    static FNV1A_64: RefCell<Fnv1a<u64>> = const { RefCell::new(Fnv1a::new()) };
+}
+
+/// Returns the FNV-1a 64 hash of `bytes`, using a hasher kept by the current
+/// thread.
+///
+/// The hasher is reset before each use, so the hash is always equal to that
+/// of a fresh hasher, as from `FnvExt::fnv1a_64`. Note that creating an
+/// `Fnv1a<u64>` only stores the offset basis and never allocates, so this is
+/// no faster than a fresh hasher, and accessing the thread-local can make it
+/// slightly slower.
+///
+/// ```
+/// use lz_fnv::{fnv1a_64_tl, FnvExt};
+///
+/// assert_eq!(fnv1a_64_tl(b"foobar"), b"foobar".fnv1a_64());
+/// ```
+pub fn fnv1a_64_tl(bytes: &[u8]) -> u64 {
+    FNV1A_64.with(|hasher| {
+        let mut hasher = hasher.borrow_mut();
+        *hasher = Fnv1a::new();
+        hasher.write(bytes);
+        hasher.finish()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use {fnv1a_64_tl, FnvExt};
+
+    const INPUTS: [&[u8]; 5] = [b"", b"a", b"foobar", b"chongo was here!\n", &[0xff; 300]];
+
+    #[test]
+    fn matches_one_shot() {
+        for input in &INPUTS {
+            assert_eq!(fnv1a_64_tl(input), input.fnv1a_64());
+        }
+    }
+
+    #[test]
+    fn resets_between_calls() {
+        assert_eq!(fnv1a_64_tl(b"foo"), b"foo".fnv1a_64());
+        assert_eq!(fnv1a_64_tl(b"foo"), b"foo".fnv1a_64());
+        assert_eq!(fnv1a_64_tl(b""), 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    fn each_thread_has_its_own_hasher() {
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                thread::spawn(move || {
+                    (0..1_000).all(|j| {
+                        let input = format!("{}-{}", i, j);
+                        fnv1a_64_tl(input.as_bytes()) == input.as_bytes().fnv1a_64()
+                    })
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
+}
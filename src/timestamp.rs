@@ -0,0 +1,262 @@
+//! Canonical hashing of instants in time.
+
+#[cfg(feature = "std")]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use {Fnv1a, FnvHash};
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// An instant in time which can be hashed by `FnvHash::write_timestamp`.
+///
+/// This is implemented for `SystemTime`, for `chrono::DateTime` with the
+/// `chrono` feature and for `time::OffsetDateTime` with the `time` feature.
+pub trait FnvTimestamp {
+    /// Returns the whole seconds since the Unix epoch, rounded towards
+    /// negative infinity, and the nanoseconds past them.
+    ///
+    /// Instants before the epoch have negative seconds but still positive
+    /// nanoseconds, so half a second before the epoch is `(-1, 500_000_000)`.
+    fn unix_parts(&self) -> (i64, u32);
+}
+
+/// Returns the 12 bytes which `FnvHash::write_timestamp` writes for an
+/// instant, carrying any nanoseconds of a second or more into the seconds.
+pub(crate) fn canonical_bytes<T: FnvTimestamp + ?Sized>(timestamp: &T) -> [u8; 12] {
+    let (seconds, nanos) = timestamp.unix_parts();
+    let seconds = seconds.wrapping_add(i64::from(nanos / NANOS_PER_SEC));
+    let nanos = nanos % NANOS_PER_SEC;
+
+    let mut bytes = [0u8; 12];
+    bytes[..8].copy_from_slice(&seconds.to_le_bytes());
+    bytes[8..].copy_from_slice(&nanos.to_le_bytes());
+    bytes
+}
+
+/// Returns the FNV-1a 64 hash of an instant, as written by
+/// `FnvHash::write_timestamp`.
+///
+/// Equal instants hash equally whatever their offset from UTC or precision,
+/// and the hash of an instant will not change between versions.
+///
+/// ```
+/// # #[cfg(feature = "std")] {
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use lz_fnv::hash_timestamp;
+///
+/// let instant = UNIX_EPOCH + Duration::new(1_700_000_000, 500_000_000);
+///
+/// assert_eq!(hash_timestamp(&instant), 0x67df_ad92_f5a0_774b);
+/// # }
+/// ```
+pub fn hash_timestamp<T: FnvTimestamp + ?Sized>(timestamp: &T) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write_timestamp(timestamp);
+    hasher.finish()
+}
+
+#[cfg(feature = "std")]
+impl FnvTimestamp for SystemTime {
+    fn unix_parts(&self) -> (i64, u32) {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => (after.as_secs() as i64, after.subsec_nanos()),
+            Err(error) => {
+                let before = error.duration();
+                let seconds = (before.as_secs() as i64).wrapping_neg();
+
+                match before.subsec_nanos() {
+                    0 => (seconds, 0),
+                    nanos => (seconds - 1, NANOS_PER_SEC - nanos),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> FnvTimestamp for chrono::DateTime<Tz> {
+    /// Returns the parts of this instant. A leap second's nanoseconds are a
+    /// second or more, so it hashes as the start of the following second.
+    fn unix_parts(&self) -> (i64, u32) {
+        (self.timestamp(), self.timestamp_subsec_nanos())
+    }
+}
+
+#[cfg(feature = "time")]
+impl FnvTimestamp for time::OffsetDateTime {
+    fn unix_parts(&self) -> (i64, u32) {
+        (self.unix_timestamp(), self.nanosecond())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::canonical_bytes;
+    use {hash_timestamp, FnvTimestamp};
+    #[cfg(feature = "std")]
+    use {Fnv1a, FnvHash};
+
+    const YEAR_9999_END: i64 = 253_402_300_799;
+
+    struct Parts(i64, u32);
+
+    impl FnvTimestamp for Parts {
+        fn unix_parts(&self) -> (i64, u32) {
+            (self.0, self.1)
+        }
+    }
+
+    #[test]
+    fn pinned_vectors() {
+        let vectors = [
+            (Parts(0, 0), 0x5467_b0da_1d10_6495),
+            (Parts(-1, 999_999_999), 0x4e38_7aad_9db0_113a),
+            (Parts(-86_400, 0), 0x9465_69e3_d978_9464),
+            (Parts(YEAR_9999_END, 999_999_999), 0x471e_f28b_d8f8_6a8d),
+            (Parts(1_700_000_000, 500_000_000), 0x67df_ad92_f5a0_774b),
+        ];
+
+        for (timestamp, expected) in &vectors {
+            assert_eq!(hash_timestamp(timestamp), *expected, "{:?}", timestamp.0);
+        }
+    }
+
+    #[test]
+    fn encoding() {
+        assert_eq!(
+            canonical_bytes(&Parts(-2, 7)),
+            [0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 7, 0, 0, 0]
+        );
+        assert_eq!(
+            canonical_bytes(&Parts(59, 1_000_000_001)),
+            canonical_bytes(&Parts(60, 1))
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn system_times() {
+        let cases = [
+            (UNIX_EPOCH, Parts(0, 0)),
+            (UNIX_EPOCH + Duration::new(1, 5), Parts(1, 5)),
+            (UNIX_EPOCH - Duration::new(1, 0), Parts(-1, 0)),
+            (
+                UNIX_EPOCH - Duration::from_millis(500),
+                Parts(-1, 500_000_000),
+            ),
+            (
+                UNIX_EPOCH - Duration::new(86_400, 1),
+                Parts(-86_401, 999_999_999),
+            ),
+            (
+                UNIX_EPOCH + Duration::new(YEAR_9999_END as u64, 999_999_999),
+                Parts(YEAR_9999_END, 999_999_999),
+            ),
+        ];
+
+        for (time, parts) in &cases {
+            assert_eq!(time.unix_parts(), (parts.0, parts.1));
+            assert_eq!(hash_timestamp(time), hash_timestamp(parts));
+        }
+    }
+
+    #[test]
+    fn distinct_instants_hash_differently() {
+        let distinct = [
+            Parts(0, 0),
+            Parts(0, 1),
+            Parts(1, 0),
+            Parts(-1, 0),
+            Parts(-1, 999_999_999),
+            Parts(i64::MIN, 0),
+            Parts(i64::MAX, 999_999_999),
+        ];
+
+        for (i, a) in distinct.iter().enumerate() {
+            for b in &distinct[i + 1..] {
+                assert_ne!(hash_timestamp(a), hash_timestamp(b));
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn writes_compose() {
+        let mut timestamps = Fnv1a::<u64>::new();
+        timestamps.write_timestamp(&UNIX_EPOCH);
+        timestamps.write_timestamp(&SystemTime::now());
+
+        assert_ne!(timestamps.finish(), hash_timestamp(&UNIX_EPOCH));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_offsets_and_precisions() {
+        use chrono::{DateTime, FixedOffset, TimeZone, Timelike, Utc};
+
+        let utc = Utc.with_ymd_and_hms(2023, 11, 14, 22, 13, 20).unwrap();
+        let offset = FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap();
+        let shifted = utc.with_timezone(&offset);
+        let negative = utc.with_timezone(&FixedOffset::west_opt(8 * 3600).unwrap());
+
+        assert_eq!(utc.unix_parts(), (1_700_000_000, 0));
+        assert_eq!(hash_timestamp(&shifted), hash_timestamp(&utc));
+        assert_eq!(hash_timestamp(&negative), hash_timestamp(&utc));
+
+        let millis = utc.with_nanosecond(500_000_000).unwrap();
+        let nanos = DateTime::from_timestamp(1_700_000_000, 500_000_000).unwrap();
+        assert_eq!(
+            hash_timestamp(&millis.with_timezone(&offset)),
+            hash_timestamp(&nanos)
+        );
+        assert_eq!(
+            hash_timestamp(&nanos),
+            hash_timestamp(&Parts(1_700_000_000, 500_000_000))
+        );
+        assert_ne!(hash_timestamp(&millis), hash_timestamp(&utc));
+
+        let before = Utc.with_ymd_and_hms(1969, 12, 31, 0, 0, 0).unwrap();
+        assert_eq!(before.unix_parts(), (-86_400, 0));
+        let last = Utc.with_ymd_and_hms(9999, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(last.unix_parts(), (YEAR_9999_END, 0));
+
+        let leap = utc.with_nanosecond(1_500_000_000).unwrap();
+        assert_eq!(
+            hash_timestamp(&leap),
+            hash_timestamp(&Parts(1_700_000_001, 500_000_000))
+        );
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_offsets_and_precisions() {
+        use time::{OffsetDateTime, UtcOffset};
+
+        let utc = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let shifted = utc.to_offset(UtcOffset::from_hms(5, 30, 0).unwrap());
+        let negative = utc.to_offset(UtcOffset::from_hms(-8, 0, 0).unwrap());
+
+        assert_eq!(utc.unix_parts(), (1_700_000_000, 0));
+        assert_eq!(hash_timestamp(&shifted), hash_timestamp(&utc));
+        assert_eq!(hash_timestamp(&negative), hash_timestamp(&utc));
+
+        let millis = utc.replace_millisecond(500).unwrap();
+        let nanos = OffsetDateTime::from_unix_timestamp_nanos(1_700_000_000_500_000_000).unwrap();
+        assert_eq!(
+            hash_timestamp(&millis.to_offset(shifted.offset())),
+            hash_timestamp(&nanos)
+        );
+        assert_eq!(
+            hash_timestamp(&nanos),
+            hash_timestamp(&Parts(1_700_000_000, 500_000_000))
+        );
+
+        let before = OffsetDateTime::from_unix_timestamp_nanos(-500_000_000).unwrap();
+        assert_eq!(before.unix_parts(), (-1, 500_000_000));
+        let last = OffsetDateTime::from_unix_timestamp(YEAR_9999_END).unwrap();
+        assert_eq!(last.unix_parts(), (YEAR_9999_END, 0));
+    }
+}
@@ -0,0 +1,352 @@
+//! Fingerprinting of `tracing` events.
+//!
+//! Each part of an event is written into an FNV-1a 64 hasher as a tag byte
+//! followed by its framed contents, where a string is framed as its length in
+//! bytes as 8 little-endian bytes followed by its UTF-8 bytes:
+//!
+//! * a `str` field is `b's'`, the field name and the value,
+//! * an `i64` field is `b'i'`, the field name and the value as 8
+//!   little-endian bytes,
+//! * a `u64` field is `b'u'`, the field name and the value as 8
+//!   little-endian bytes,
+//! * a `bool` field is `b'b'`, the field name and the value as one byte, 0
+//!   or 1, and
+//! * any other field is `b'd'`, the field name and the value's `Debug`
+//!   output, framed as a string.
+//!
+//! Fields are hashed in the order `tracing` visits them, which is the order
+//! of the callsite's field set rather than the order the values were given.
+//! Other values, such as `f64` and `i128`, are visited as `Debug` by
+//! `tracing`.
+
+use std::fmt::{self, Write};
+
+use tracing::field::{Field, Visit};
+use tracing::{Event, Metadata};
+
+use {Fnv1a, FnvHash};
+
+const STR_TAG: u8 = b's';
+const I64_TAG: u8 = b'i';
+const U64_TAG: u8 = b'u';
+const BOOL_TAG: u8 = b'b';
+const DEBUG_TAG: u8 = b'd';
+
+/// Counts the bytes written, to frame `Debug` output without buffering it.
+struct CountingWriter(u64);
+
+impl Write for CountingWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len() as u64;
+        Ok(())
+    }
+}
+
+struct HashingWriter<'a>(&'a mut Fnv1a<u64>);
+
+impl<'a> Write for HashingWriter<'a> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// A `tracing` field visitor which hashes the fields it visits with FNV-1a
+/// 64.
+///
+/// The framing of each field is described in the [module documentation].
+/// The `Debug` output of a value is formatted twice, once to measure it and
+/// once to hash it, so that nothing is allocated.
+///
+/// [module documentation]: index.html
+#[derive(Debug, Clone, Default)]
+pub struct FnvEventHasher {
+    hasher: Fnv1a<u64>,
+}
+
+impl FnvEventHasher {
+    /// Creates a new `FnvEventHasher` which has visited no fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the hash of the fields visited so far.
+    pub fn finish(&self) -> u64 {
+        self.hasher.finish()
+    }
+
+    fn write_tag(&mut self, tag: u8, field: &Field) {
+        self.hasher.write(&[tag]);
+        self.write_str(field.name());
+    }
+
+    fn write_str(&mut self, value: &str) {
+        self.hasher.write(&(value.len() as u64).to_le_bytes());
+        self.hasher.write(value.as_bytes());
+    }
+
+    fn write_option_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.hasher.write(&[1]);
+                self.write_str(value);
+            }
+            None => self.hasher.write(&[0]),
+        }
+    }
+
+    fn write_metadata(&mut self, metadata: &Metadata) {
+        self.write_str(metadata.name());
+        self.write_str(metadata.target());
+        self.write_str(metadata.level().as_str());
+        self.write_option_str(metadata.module_path());
+        self.write_option_str(metadata.file());
+        match metadata.line() {
+            Some(line) => {
+                self.hasher.write(&[1]);
+                self.hasher.write(&line.to_le_bytes());
+            }
+            None => self.hasher.write(&[0]),
+        }
+    }
+}
+
+impl Visit for FnvEventHasher {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.write_tag(STR_TAG, field);
+        self.write_str(value);
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.write_tag(I64_TAG, field);
+        self.hasher.write(&value.to_le_bytes());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.write_tag(U64_TAG, field);
+        self.hasher.write(&value.to_le_bytes());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.write_tag(BOOL_TAG, field);
+        self.hasher.write(&[value as u8]);
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.write_tag(DEBUG_TAG, field);
+
+        let mut counter = CountingWriter(0);
+        let _ = write!(counter, "{:?}", value);
+        self.hasher.write(&counter.0.to_le_bytes());
+
+        let _ = write!(HashingWriter(&mut self.hasher), "{:?}", value);
+    }
+}
+
+/// Returns a fingerprint of an event's callsite and field values.
+///
+/// The callsite is hashed first, as the metadata's name, target and level,
+/// each framed as a string, followed by its module path, file and line, each
+/// as the byte 0 if absent or the byte 1 and then the value if present. The
+/// line is 4 little-endian bytes. The fields are then hashed by an
+/// [`FnvEventHasher`].
+///
+/// Events from one callsite with equal field values have equal fingerprints,
+/// so they can be deduplicated or sampled consistently. The fingerprint
+/// changes when the callsite moves.
+///
+/// ```
+/// extern crate lz_fnv;
+/// extern crate tracing;
+///
+/// use lz_fnv::event_fingerprint;
+/// use tracing::{Event, Subscriber};
+///
+/// struct Fingerprints;
+///
+/// impl Subscriber for Fingerprints {
+///     fn event(&self, event: &Event) {
+///         println!("{:016x}", event_fingerprint(event));
+///     }
+///     # fn enabled(&self, _: &tracing::Metadata) -> bool { true }
+///     # fn new_span(&self, _: &tracing::span::Attributes) -> tracing::span::Id {
+///     #     tracing::span::Id::from_u64(1)
+///     # }
+///     # fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record) {}
+///     # fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+///     # fn enter(&self, _: &tracing::span::Id) {}
+///     # fn exit(&self, _: &tracing::span::Id) {}
+/// }
+///
+/// # fn main() {
+/// tracing::subscriber::with_default(Fingerprints, || {
+///     tracing::warn!(retries = 3, "connection lost");
+/// });
+/// # }
+/// ```
+///
+/// [`FnvEventHasher`]: struct.FnvEventHasher.html
+pub fn event_fingerprint(event: &Event) -> u64 {
+    let mut visitor = FnvEventHasher::new();
+    visitor.write_metadata(event.metadata());
+    event.record(&mut visitor);
+    visitor.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use {event_fingerprint, Fnv1a, FnvEventHasher, FnvHash};
+
+    #[derive(Default)]
+    struct Recorded {
+        fingerprints: Vec<u64>,
+        metadata: Vec<&'static Metadata<'static>>,
+        fields: Vec<u64>,
+    }
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Recorded>>);
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &Attributes) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event) {
+            let mut fields = FnvEventHasher::new();
+            event.record(&mut fields);
+
+            let mut recorded = self.0.lock().unwrap();
+            recorded.fingerprints.push(event_fingerprint(event));
+            recorded.metadata.push(event.metadata());
+            recorded.fields.push(fields.finish());
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn record<F: FnOnce()>(f: F) -> Recorded {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), f);
+
+        let mut recorded = recorder.0.lock().unwrap();
+        ::std::mem::take(&mut *recorded)
+    }
+
+    fn write_str(hasher: &mut Fnv1a<u64>, value: &str) {
+        hasher.write(&(value.len() as u64).to_le_bytes());
+        hasher.write(value.as_bytes());
+    }
+
+    #[test]
+    fn identical_events_are_equal() {
+        let recorded = record(|| {
+            for _ in 0..2 {
+                tracing::info!(user = "alice", attempts = 3u64, "login failed");
+            }
+        });
+
+        assert_eq!(recorded.fingerprints.len(), 2);
+        assert_eq!(recorded.fingerprints[0], recorded.fingerprints[1]);
+    }
+
+    #[test]
+    fn field_values_change_the_fingerprint() {
+        let recorded = record(|| {
+            for user in &["alice", "bob"] {
+                tracing::info!(user = *user, attempts = 3u64, "login failed");
+            }
+        });
+
+        assert_ne!(recorded.fingerprints[0], recorded.fingerprints[1]);
+    }
+
+    #[test]
+    fn callsites_change_the_fingerprint() {
+        let recorded = record(|| {
+            tracing::info!(user = "alice");
+            tracing::info!(user = "alice");
+        });
+
+        assert_eq!(recorded.fields[0], recorded.fields[1]);
+        assert_ne!(recorded.fingerprints[0], recorded.fingerprints[1]);
+    }
+
+    #[test]
+    fn framing_is_pinned() {
+        let recorded = record(|| {
+            tracing::warn!(
+                target: "lz_fnv::pinned",
+                name = "x",
+                delta = -2i64,
+                count = 7u64,
+                ok = true,
+                ratio = 0.5f64
+            );
+        });
+
+        let mut fields = Fnv1a::<u64>::new();
+        fields.write(b"s");
+        write_str(&mut fields, "name");
+        write_str(&mut fields, "x");
+        fields.write(b"i");
+        write_str(&mut fields, "delta");
+        fields.write(&(-2i64).to_le_bytes());
+        fields.write(b"u");
+        write_str(&mut fields, "count");
+        fields.write(&7u64.to_le_bytes());
+        fields.write(b"b");
+        write_str(&mut fields, "ok");
+        fields.write(&[1]);
+        fields.write(b"d");
+        write_str(&mut fields, "ratio");
+        write_str(&mut fields, "0.5");
+
+        assert_eq!(recorded.fields[0], fields.finish());
+        assert_eq!(recorded.fields[0], 0xbc36_e7cd_66c6_507a);
+
+        let metadata = recorded.metadata[0];
+        let mut fingerprint = Fnv1a::<u64>::new();
+        write_str(&mut fingerprint, metadata.name());
+        write_str(&mut fingerprint, "lz_fnv::pinned");
+        write_str(&mut fingerprint, "WARN");
+        fingerprint.write(&[1]);
+        write_str(&mut fingerprint, "lz_fnv::tracing_hash::tests");
+        fingerprint.write(&[1]);
+        write_str(&mut fingerprint, file!());
+        fingerprint.write(&[1]);
+        fingerprint.write(&metadata.line().unwrap().to_le_bytes());
+        fingerprint.write(b"s");
+        write_str(&mut fingerprint, "name");
+        write_str(&mut fingerprint, "x");
+        fingerprint.write(b"i");
+        write_str(&mut fingerprint, "delta");
+        fingerprint.write(&(-2i64).to_le_bytes());
+        fingerprint.write(b"u");
+        write_str(&mut fingerprint, "count");
+        fingerprint.write(&7u64.to_le_bytes());
+        fingerprint.write(b"b");
+        write_str(&mut fingerprint, "ok");
+        fingerprint.write(&[1]);
+        fingerprint.write(b"d");
+        write_str(&mut fingerprint, "ratio");
+        write_str(&mut fingerprint, "0.5");
+
+        assert_eq!(recorded.fingerprints[0], fingerprint.finish());
+    }
+}
@@ -0,0 +1,265 @@
+//! Order-independent hashing of multisets.
+//!
+//! The construction is frozen, as the digests are intended to be persisted:
+//!
+//! 1. Each element `e` is hashed with FNV-1a 64 over the tag byte `0x01`
+//!    followed by the bytes of `e`.
+//! 2. Each element hash is post-mixed with the MurmurHash3 64-bit finalizer,
+//!    so that the sum below does not simply add FNV states together.
+//! 3. The mixed element hashes are summed with wrapping addition and the
+//!    elements are counted, both modulo `2^64`.
+//! 4. The digest is FNV-1a 64 over the tag byte `0x02`, the sum as 8
+//!    little-endian bytes and the count as 8 little-endian bytes.
+//!
+//! Addition is commutative, so the order of the elements does not matter,
+//! but it is not idempotent, so every occurrence of a duplicate element
+//! contributes to the digest.
+
+use {Fnv1a, FnvHash};
+
+const ELEMENT_TAG: u8 = 0x01;
+const DIGEST_TAG: u8 = 0x02;
+
+pub(crate) const fn fmix64(mut k: u64) -> u64 {
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    k ^= k >> 33;
+    k
+}
+
+/// Returns the mixed hash of a single element, as summed into a digest.
+pub(crate) fn element_hash(element: &[u8]) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(&[ELEMENT_TAG]);
+    hasher.write(element);
+
+    fmix64(hasher.finish())
+}
+
+/// Returns the digest of a multiset from the sum and count of its elements.
+pub(crate) fn digest(sum: u64, count: u64) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    hasher.write(&[DIGEST_TAG]);
+    hasher.write(&sum.to_le_bytes());
+    hasher.write(&count.to_le_bytes());
+
+    hasher.finish()
+}
+
+/// Hashes a multiset of byte strings independently of their order.
+///
+/// Reordering the items does not change the result, while adding, removing
+/// or changing any item, including a duplicate, does. The construction is
+/// described in the [module documentation](index.html) and will not change.
+///
+/// ```
+/// use lz_fnv::hash_unordered;
+///
+/// let a = hash_unordered(&["red", "green", "blue"]);
+/// let b = hash_unordered(&["blue", "red", "green"]);
+///
+/// assert_eq!(a, b);
+/// assert_ne!(a, hash_unordered(&["red", "green", "blue", "blue"]));
+/// ```
+pub fn hash_unordered<I>(items: I) -> u64
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut sum = 0u64;
+    let mut count = 0u64;
+
+    for item in items {
+        sum = sum.wrapping_add(element_hash(item.as_ref()));
+        count = count.wrapping_add(1);
+    }
+
+    digest(sum, count)
+}
+
+/// A live, order-independent digest of a changing multiset.
+///
+/// Elements are summed into the digest exactly as by [`hash_unordered`], so
+/// `current` always equals `hash_unordered` of the current membership.
+///
+/// Removal subtracts an element's hash and decrements the count, so the
+/// digest cannot tell whether a removed element was ever inserted. Removing a
+/// non-member silently corrupts the digest, leaving it as if the membership
+/// held a "negative" copy of that element, until that element is inserted
+/// again.
+///
+/// [`hash_unordered`]: fn.hash_unordered.html
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IncrementalSetHash {
+    sum: u64,
+    count: u64,
+}
+
+impl IncrementalSetHash {
+    /// Creates a new, empty `IncrementalSetHash`.
+    ///
+    /// ```
+    /// use lz_fnv::{hash_unordered, IncrementalSetHash};
+    ///
+    /// let mut set_hash = IncrementalSetHash::new();
+    /// set_hash.insert(b"a");
+    /// set_hash.insert(b"b");
+    /// set_hash.remove(b"a");
+    ///
+    /// assert_eq!(set_hash.current(), hash_unordered(&["b"]));
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an element to the digest.
+    pub fn insert(&mut self, element: &[u8]) {
+        self.sum = self.sum.wrapping_add(element_hash(element));
+        self.count = self.count.wrapping_add(1);
+    }
+
+    /// Removes an element from the digest.
+    ///
+    /// The element must previously have been inserted, otherwise the digest
+    /// is silently corrupted.
+    pub fn remove(&mut self, element: &[u8]) {
+        self.sum = self.sum.wrapping_sub(element_hash(element));
+        self.count = self.count.wrapping_sub(1);
+    }
+
+    /// Returns the digest of the current membership.
+    pub fn current(&self) -> u64 {
+        digest(self.sum, self.count)
+    }
+
+    /// Returns whether this is the digest of the empty multiset.
+    pub fn is_empty_digest(&self) -> bool {
+        self.sum == 0 && self.count == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {hash_unordered, IncrementalSetHash};
+
+    const EMPTY: [&[u8]; 0] = [];
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn permutation_invariance() {
+        let expected = hash_unordered(&["a", "b", "c", "d"]);
+
+        assert_eq!(hash_unordered(&["d", "c", "b", "a"]), expected);
+        assert_eq!(hash_unordered(&["b", "d", "a", "c"]), expected);
+        assert_eq!(hash_unordered(vec!["c", "a", "d", "b"]), expected);
+    }
+
+    #[test]
+    fn duplicates_affect_result() {
+        let single = hash_unordered(&["a", "b"]);
+        let duplicate = hash_unordered(&["a", "a", "b"]);
+        let triplicate = hash_unordered(&["a", "a", "a", "b"]);
+
+        assert_ne!(single, duplicate);
+        assert_ne!(duplicate, triplicate);
+        assert_ne!(single, triplicate);
+    }
+
+    #[test]
+    fn element_change_affects_result() {
+        let expected = hash_unordered(&["alpha", "beta", "gamma"]);
+
+        assert_ne!(hash_unordered(&["alpha", "beta", "gammb"]), expected);
+        assert_ne!(hash_unordered(&["alpha", "beta"]), expected);
+        assert_ne!(hash_unordered(&["alpha", "beta", "gamma", ""]), expected);
+    }
+
+    #[test]
+    fn element_boundaries_matter() {
+        assert_ne!(hash_unordered(&["ab", "c"]), hash_unordered(&["a", "bc"]));
+        assert_ne!(hash_unordered(&["abc"]), hash_unordered(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn incremental_starts_empty() {
+        let set_hash = IncrementalSetHash::new();
+
+        assert!(set_hash.is_empty_digest());
+        assert_eq!(set_hash.current(), hash_unordered(&EMPTY));
+    }
+
+    #[test]
+    fn incremental_round_trips_to_empty() {
+        let mut set_hash = IncrementalSetHash::new();
+
+        set_hash.insert(b"a");
+        set_hash.insert(b"b");
+        set_hash.insert(b"a");
+        assert!(!set_hash.is_empty_digest());
+
+        set_hash.remove(b"a");
+        set_hash.remove(b"b");
+        set_hash.remove(b"a");
+
+        assert!(set_hash.is_empty_digest());
+        assert_eq!(set_hash, IncrementalSetHash::new());
+    }
+
+    #[test]
+    fn incremental_order_independence() {
+        let mut forward = IncrementalSetHash::new();
+        let mut backward = IncrementalSetHash::new();
+
+        for element in &["x", "y", "z"] {
+            forward.insert(element.as_bytes());
+        }
+        for element in &["z", "y", "x"] {
+            backward.insert(element.as_bytes());
+        }
+
+        assert_eq!(forward.current(), backward.current());
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let mut set_hash = IncrementalSetHash::new();
+
+        set_hash.insert(b"web-1");
+        set_hash.insert(b"web-2");
+        set_hash.insert(b"db-1");
+        set_hash.remove(b"web-2");
+        set_hash.insert(b"web-3");
+        set_hash.insert(b"web-3");
+
+        assert_eq!(
+            set_hash.current(),
+            hash_unordered(&["db-1", "web-3", "web-1", "web-3"])
+        );
+    }
+
+    #[test]
+    fn incremental_removing_non_member() {
+        let mut set_hash = IncrementalSetHash::new();
+        set_hash.insert(b"a");
+
+        set_hash.remove(b"b");
+        assert_ne!(set_hash.current(), hash_unordered(&["a"]));
+        assert_ne!(set_hash.current(), hash_unordered(&EMPTY));
+
+        set_hash.insert(b"b");
+        assert_eq!(set_hash.current(), hash_unordered(&["a"]));
+    }
+
+    #[test]
+    fn pinned_vectors() {
+        assert_eq!(hash_unordered(&EMPTY), 0xeba8_d4f0_aba8_0485);
+        assert_eq!(hash_unordered(&[""]), 0xa241_c576_0d4a_0837);
+        assert_eq!(hash_unordered(&["foobar"]), 0x5936_a78e_0f39_d1b6);
+        assert_eq!(
+            hash_unordered(&["env:prod", "team:core"]),
+            0xfea5_ae5e_a924_84c4
+        );
+    }
+}
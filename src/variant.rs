@@ -0,0 +1,156 @@
+//! The FNV variants, as marker types for `Fnv<V, T>`.
+
+use FnvWidth;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A variant of the FNV hash, distinguishing FNV-0, FNV-1 and FNV-1a.
+///
+/// The variants differ only in whether hashing starts from zero or the offset
+/// basis, and in whether each byte is XORed in before or after multiplying by
+/// the prime. This is implemented for `V0`, `V1` and `V1a`, and sealed
+/// against other implementations, so code generic over `Fnv<V, T>` works with
+/// every variant.
+///
+/// ```
+/// use lz_fnv::{Fnv, FnvHash, FnvWidth, Variant, V1, V1a};
+///
+/// fn hash<V: Variant, T: FnvWidth>(bytes: &[u8]) -> T {
+///     let mut fnv_hasher = Fnv::<V, T>::new();
+///     fnv_hasher.write(bytes);
+///     fnv_hasher.finish()
+/// }
+///
+/// assert_eq!(hash::<V1, u64>(b"foobar"), 0x340d_8765_a4dd_a9c2);
+/// assert_eq!(hash::<V1a, u32>(b"foobar"), 0xbf9c_f968);
+/// ```
+pub trait Variant: private::Sealed {
+    /// The name of the hasher type alias for this variant, such as `"Fnv1a"`.
+    const NAME: &'static str;
+
+    /// Whether hashing starts from the offset basis rather than zero.
+    const USES_OFFSET_BASIS: bool;
+
+    /// Whether each byte is XORed in before multiplying by the prime, rather
+    /// than after.
+    const XOR_FIRST: bool;
+
+    /// Performs one step of this variant, hashing `byte` into `state`.
+    #[inline]
+    fn step<T: FnvWidth>(state: T, byte: u8) -> T {
+        if Self::XOR_FIRST {
+            T::fnv1a_step(state, byte)
+        } else {
+            T::fnv1_step(state, byte)
+        }
+    }
+
+    /// Performs a step of this variant for each of `bytes`, returning the new
+    /// state.
+    #[inline]
+    fn advance<T: FnvWidth>(state: T, bytes: &[u8]) -> T {
+        if Self::XOR_FIRST {
+            T::fnv1a_advance(state, bytes)
+        } else {
+            T::fnv1_advance(state, bytes)
+        }
+    }
+
+    /// Undoes one step of this variant which hashed `byte` into `state`.
+    #[inline]
+    fn unstep<T: FnvWidth>(state: T, byte: u8) -> T {
+        if Self::XOR_FIRST {
+            T::fnv1a_unstep(state, byte)
+        } else {
+            T::fnv1_unstep(state, byte)
+        }
+    }
+}
+
+/// The FNV-0 variant, which starts from zero and XORs after multiplying.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct V0;
+
+/// The FNV-1 variant, which starts from the offset basis and XORs after
+/// multiplying.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct V1;
+
+/// The FNV-1a variant, which starts from the offset basis and XORs before
+/// multiplying.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct V1a;
+
+impl private::Sealed for V0 {}
+impl private::Sealed for V1 {}
+impl private::Sealed for V1a {}
+
+impl Variant for V0 {
+    const NAME: &'static str = "Fnv0";
+    const USES_OFFSET_BASIS: bool = false;
+    const XOR_FIRST: bool = false;
+}
+
+impl Variant for V1 {
+    const NAME: &'static str = "Fnv1";
+    const USES_OFFSET_BASIS: bool = true;
+    const XOR_FIRST: bool = false;
+}
+
+impl Variant for V1a {
+    const NAME: &'static str = "Fnv1a";
+    const USES_OFFSET_BASIS: bool = true;
+    const XOR_FIRST: bool = true;
+}
+
+#[cfg(test)]
+mod tests {
+    use spec_vectors::{FNV1A_128, FNV1A_32, FNV1A_64, FNV1_128, FNV1_32, FNV1_64};
+    use std::fmt::Debug;
+    use {Fnv, FnvHash, FnvWidth, V1a, Variant, V0, V1};
+    #[cfg(feature = "std")]
+    use {Fnv0, Fnv1a};
+
+    fn hash_generic<V: Variant, T: FnvWidth>(input: &[u8]) -> T {
+        let mut fnv_hasher = Fnv::<V, T>::new();
+        fnv_hasher.write(input);
+        fnv_hasher.finish()
+    }
+
+    fn check<V: Variant, T: FnvWidth + PartialEq + Debug>(vectors: &[(&[u8], T)]) {
+        for &(input, expected) in vectors {
+            assert_eq!(hash_generic::<V, T>(input), expected, "input {:?}", input);
+        }
+    }
+
+    #[test]
+    fn generic_code_matches_spec() {
+        // FNV-0 vectors from test_fnv.c
+        check::<V0, u32>(&[(b"", 0), (b"a", 0x0000_0061), (b"foobar", 0xb74b_b5ef)]);
+        check::<V0, u64>(&[
+            (b"", 0),
+            (b"a", 0x0000_0000_0000_0061),
+            (b"foobar", 0x0b91_ae3f_7ccd_c5ef),
+        ]);
+        check::<V0, u128>(&[(b"", 0)]);
+
+        check::<V1, u32>(FNV1_32);
+        check::<V1a, u32>(FNV1A_32);
+        check::<V1, u64>(FNV1_64);
+        check::<V1a, u64>(FNV1A_64);
+        check::<V1, u128>(FNV1_128);
+        check::<V1a, u128>(FNV1A_128);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn debug_names_variant() {
+        let mut fnv1a = Fnv1a::<u32>::new();
+        fnv1a.write(b"a");
+
+        assert_eq!(format!("{:?}", fnv1a), "Fnv1a { hash: 3826002220 }");
+        assert_eq!(format!("{:?}", Fnv0::<u64>::new()), "Fnv0 { hash: 0 }");
+    }
+}
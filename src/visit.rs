@@ -0,0 +1,436 @@
+//! Structure-aware hashing of nested values.
+//!
+//! Values describe themselves to a hasher through [`FnvVisit`], framing each
+//! part with [`write_leaf`] and [`write_node`]:
+//!
+//! * a leaf is the tag byte `0x00`, its length as 8 little-endian bytes and
+//!   then its bytes, and
+//! * a node is the tag byte `0x01`, its number of children as 8 little-endian
+//!   bytes and then each child in order.
+//!
+//! This framing is a prefix-free pre-order encoding, so trees of different
+//! shapes hash differently even when their leaves hold the same bytes.
+//!
+//! Byte slices, byte arrays and strings are leaves of their bytes, a `char`
+//! is a leaf of its UTF-8 bytes and a `bool` a leaf of one byte, 0 or 1.
+//! Integers are leaves of their little-endian bytes, with `usize` and
+//! `isize` widened to 64 bits so that they hash equally on every platform.
+//!
+//! [`FnvVisit`]: trait.FnvVisit.html
+//! [`write_leaf`]: fn.write_leaf.html
+//! [`write_node`]: fn.write_node.html
+
+use {DynFnvHasher, Fnv1a, FnvHash};
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+
+/// A value which can describe its structure to an FNV hasher.
+pub trait FnvVisit {
+    /// Writes this value into the hasher.
+    fn visit(&self, h: &mut dyn DynFnvHasher);
+}
+
+impl FnvVisit for [u8] {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        write_leaf(h, self);
+    }
+}
+
+impl FnvVisit for str {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        write_leaf(h, self.as_bytes());
+    }
+}
+
+impl<const N: usize> FnvVisit for [u8; N] {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        write_leaf(h, self);
+    }
+}
+
+#[cfg(feature = "std")]
+impl FnvVisit for Vec<u8> {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        write_leaf(h, self);
+    }
+}
+
+#[cfg(feature = "std")]
+impl FnvVisit for String {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        write_leaf(h, self.as_bytes());
+    }
+}
+
+macro_rules! fnv_visit_int_impl {
+    ($($type: ty),*) => {
+        $(
+            impl FnvVisit for $type {
+                fn visit(&self, h: &mut dyn DynFnvHasher) {
+                    write_leaf(h, &self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+fnv_visit_int_impl!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl FnvVisit for usize {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        (*self as u64).visit(h);
+    }
+}
+
+impl FnvVisit for isize {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        (*self as i64).visit(h);
+    }
+}
+
+impl FnvVisit for bool {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        write_leaf(h, &[*self as u8]);
+    }
+}
+
+impl FnvVisit for char {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        write_leaf(h, self.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+}
+
+impl<T: FnvVisit + ?Sized> FnvVisit for &T {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        (**self).visit(h);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: FnvVisit + ?Sized> FnvVisit for Box<T> {
+    fn visit(&self, h: &mut dyn DynFnvHasher) {
+        (**self).visit(h);
+    }
+}
+
+/// Writes a framed leaf into the hasher.
+pub fn write_leaf(h: &mut dyn DynFnvHasher, bytes: &[u8]) {
+    h.write_bytes(&[LEAF_TAG]);
+    h.write_bytes(&(bytes.len() as u64).to_le_bytes());
+    h.write_bytes(bytes);
+}
+
+/// Writes the header of a framed node with `len` children into the hasher.
+///
+/// The caller must then visit exactly `len` children. This frames nodes
+/// whose children are of different types, which `write_node` cannot.
+pub fn write_node_header(h: &mut dyn DynFnvHasher, len: usize) {
+    h.write_bytes(&[NODE_TAG]);
+    h.write_bytes(&(len as u64).to_le_bytes());
+}
+
+/// Writes a framed node into the hasher, visiting each of its children.
+pub fn write_node<T: FnvVisit>(h: &mut dyn DynFnvHasher, children: &[T]) {
+    write_node_header(h, children.len());
+
+    for child in children {
+        child.visit(h);
+    }
+}
+
+/// Hashes a value by visiting it with the specified hasher.
+pub fn visit_with<H, V>(hasher: &mut H, value: &V)
+where
+    H: DynFnvHasher,
+    V: FnvVisit + ?Sized,
+{
+    value.visit(hasher);
+}
+
+/// Hashes a value by visiting it with FNV-1a 64.
+///
+/// ```
+/// use lz_fnv::{hash_visit, write_leaf, write_node, DynFnvHasher, FnvVisit};
+///
+/// enum Tree {
+///     Leaf(&'static str),
+///     Node(Vec<Tree>),
+/// }
+///
+/// impl FnvVisit for Tree {
+///     fn visit(&self, h: &mut dyn DynFnvHasher) {
+///         match *self {
+///             Tree::Leaf(leaf) => write_leaf(h, leaf.as_bytes()),
+///             Tree::Node(ref children) => write_node(h, children),
+///         }
+///     }
+/// }
+///
+/// let flat = Tree::Node(vec![Tree::Leaf("a"), Tree::Leaf("b")]);
+/// let nested = Tree::Node(vec![Tree::Node(vec![Tree::Leaf("a")]), Tree::Leaf("b")]);
+///
+/// assert_ne!(hash_visit(&flat), hash_visit(&nested));
+/// ```
+pub fn hash_visit<V: FnvVisit + ?Sized>(value: &V) -> u64 {
+    let mut hasher = Fnv1a::<u64>::new();
+    visit_with(&mut hasher, value);
+    hasher.finish()
+}
+
+/// Evaluates to the hash of several values of possibly different types, each
+/// implementing [`FnvVisit`].
+///
+/// The values are hashed as the children of one node, framed as by
+/// [`write_node_header`] followed by visiting each value in turn, so the
+/// boundaries between values, and their number, change the hash. With no
+/// selector the hash is FNV-1a 64; otherwise the first argument, followed by
+/// a semicolon, selects the variant and width as one of `fnv0_32`, `fnv0_64`,
+/// `fnv0_128`, `fnv1_32`, `fnv1_64`, `fnv1_128`, `fnv1a_32`, `fnv1a_64` or
+/// `fnv1a_128`, matching the names of the step functions. Each may also be
+/// spelled without its underscore, as in `fnv1a32`. A trailing comma is
+/// allowed after the last value. Nothing is allocated, and the macro is
+/// available without the `std` feature.
+///
+/// ```
+/// #[macro_use]
+/// extern crate lz_fnv;
+///
+/// # fn main() {
+/// let (user, attempt, admin) = ("alice", 3u32, false);
+///
+/// let key: u64 = fnv_hash!(user, attempt, admin);
+/// let key_32: u32 = fnv_hash!(fnv1a_32; user, attempt, admin);
+///
+/// assert_ne!(fnv_hash!("ab", "c"), fnv_hash!("a", "bc"));
+/// # }
+/// ```
+///
+/// [`FnvVisit`]: trait.FnvVisit.html
+/// [`write_node_header`]: fn.write_node_header.html
+#[macro_export]
+macro_rules! fnv_hash {
+    (fnv0_32; $($value: expr),+ $(,)?) => {
+        $crate::fnv_hash!(@hash $crate::Fnv0::<u32>; $($value),+)
+    };
+    (fnv0_64; $($value: expr),+ $(,)?) => {
+        $crate::fnv_hash!(@hash $crate::Fnv0::<u64>; $($value),+)
+    };
+    (fnv0_128; $($value: expr),+ $(,)?) => {
+        $crate::fnv_hash!(@hash $crate::Fnv0::<u128>; $($value),+)
+    };
+    (fnv1_32; $($value: expr),+ $(,)?) => {
+        $crate::fnv_hash!(@hash $crate::Fnv1::<u32>; $($value),+)
+    };
+    (fnv1_64; $($value: expr),+ $(,)?) => {
+        $crate::fnv_hash!(@hash $crate::Fnv1::<u64>; $($value),+)
+    };
+    (fnv1_128; $($value: expr),+ $(,)?) => {
+        $crate::fnv_hash!(@hash $crate::Fnv1::<u128>; $($value),+)
+    };
+    (fnv1a_32; $($value: expr),+ $(,)?) => {
+        $crate::fnv_hash!(@hash $crate::Fnv1a::<u32>; $($value),+)
+    };
+    (fnv1a_64; $($value: expr),+ $(,)?) => {
+        $crate::fnv_hash!(@hash $crate::Fnv1a::<u64>; $($value),+)
+    };
+    (fnv1a_128; $($value: expr),+ $(,)?) => {
+        $crate::fnv_hash!(@hash $crate::Fnv1a::<u128>; $($value),+)
+    };
+    (fnv032; $($rest: tt)*) => {
+        $crate::fnv_hash!(fnv0_32; $($rest)*)
+    };
+    (fnv064; $($rest: tt)*) => {
+        $crate::fnv_hash!(fnv0_64; $($rest)*)
+    };
+    (fnv0128; $($rest: tt)*) => {
+        $crate::fnv_hash!(fnv0_128; $($rest)*)
+    };
+    (fnv132; $($rest: tt)*) => {
+        $crate::fnv_hash!(fnv1_32; $($rest)*)
+    };
+    (fnv164; $($rest: tt)*) => {
+        $crate::fnv_hash!(fnv1_64; $($rest)*)
+    };
+    (fnv1128; $($rest: tt)*) => {
+        $crate::fnv_hash!(fnv1_128; $($rest)*)
+    };
+    (fnv1a32; $($rest: tt)*) => {
+        $crate::fnv_hash!(fnv1a_32; $($rest)*)
+    };
+    (fnv1a64; $($rest: tt)*) => {
+        $crate::fnv_hash!(fnv1a_64; $($rest)*)
+    };
+    (fnv1a128; $($rest: tt)*) => {
+        $crate::fnv_hash!(fnv1a_128; $($rest)*)
+    };
+    (@hash $hasher: ty; $($value: expr),+) => {{
+        let mut hasher = <$hasher>::new();
+        $crate::write_node_header(&mut hasher, 0 $(+ $crate::fnv_hash!(@one $value))+);
+        $($crate::FnvVisit::visit(&$value, &mut hasher);)+
+        $crate::FnvHash::finish(&hasher)
+    }};
+    (@one $value: expr) => {
+        1
+    };
+    ($($value: expr),+ $(,)?) => {
+        $crate::fnv_hash!(fnv1a_64; $($value),+)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "std")]
+    use {hash_visit, write_node, write_node_header, DynFnvHasher, Fnv0, FnvVisit};
+    use {visit_with, write_leaf, Fnv1a, FnvHash};
+
+    #[cfg(feature = "std")]
+    enum Tree {
+        Leaf(&'static [u8]),
+        Node(Vec<Tree>),
+    }
+
+    #[cfg(feature = "std")]
+    use self::Tree::{Leaf, Node};
+
+    #[cfg(feature = "std")]
+    impl FnvVisit for Tree {
+        fn visit(&self, h: &mut dyn DynFnvHasher) {
+            match *self {
+                Leaf(bytes) => write_leaf(h, bytes),
+                Node(ref children) => write_node(h, children),
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn shape_changes_hash() {
+        let left = Node(vec![Node(vec![Leaf(b"a"), Leaf(b"b")]), Leaf(b"c")]);
+        let right = Node(vec![Leaf(b"a"), Node(vec![Leaf(b"b"), Leaf(b"c")])]);
+        let flat = Node(vec![Leaf(b"a"), Leaf(b"b"), Leaf(b"c")]);
+
+        assert_ne!(hash_visit(&left), hash_visit(&right));
+        assert_ne!(hash_visit(&left), hash_visit(&flat));
+        assert_ne!(hash_visit(&right), hash_visit(&flat));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn leaf_boundaries_change_hash() {
+        let split = Node(vec![Leaf(b"ab"), Leaf(b"c")]);
+        let other = Node(vec![Leaf(b"a"), Leaf(b"bc")]);
+
+        assert_ne!(hash_visit(&split), hash_visit(&other));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn equal_trees_hash_equal() {
+        let a = Node(vec![Leaf(b"x"), Node(vec![])]);
+        let b = Node(vec![Leaf(b"x"), Node(vec![])]);
+
+        assert_eq!(hash_visit(&a), hash_visit(&b));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn framing_is_documented_encoding() {
+        let tree = Node(vec![Leaf(b"ab")]);
+
+        let mut expected = Fnv1a::<u64>::new();
+        expected.write(&[0x01, 1, 0, 0, 0, 0, 0, 0, 0]);
+        expected.write(&[0x00, 2, 0, 0, 0, 0, 0, 0, 0]);
+        expected.write(b"ab");
+
+        assert_eq!(hash_visit(&tree), expected.finish());
+    }
+
+    #[test]
+    fn strings_are_leaves() {
+        let mut hasher = Fnv1a::<u32>::new();
+        visit_with(&mut hasher, "abc");
+
+        let mut expected = Fnv1a::<u32>::new();
+        write_leaf(&mut expected, b"abc");
+
+        assert_eq!(hasher.finish(), expected.finish());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn scalars_are_leaves() {
+        let leaf = |bytes: &[u8]| {
+            let mut hasher = Fnv1a::<u64>::new();
+            write_leaf(&mut hasher, bytes);
+            hasher.finish()
+        };
+
+        assert_eq!(hash_visit(&0x0102u16), leaf(&[0x02, 0x01]));
+        assert_eq!(hash_visit(&-1i8), leaf(&[0xff]));
+        assert_eq!(hash_visit(&7usize), hash_visit(&7u64));
+        assert_eq!(hash_visit(&-7isize), hash_visit(&-7i64));
+        assert_eq!(hash_visit(&true), leaf(&[1]));
+        assert_eq!(hash_visit(&'é'), hash_visit("é"));
+        assert_eq!(hash_visit(b"ab"), hash_visit(&b"ab"[..]));
+        assert_eq!(hash_visit(&String::from("ab")), hash_visit("ab"));
+        assert_eq!(hash_visit(&b"ab".to_vec()), hash_visit("ab"));
+    }
+
+    #[test]
+    fn fnv_hash_separates_arguments() {
+        assert_ne!(fnv_hash!("ab", "c"), fnv_hash!("a", "bc"));
+        assert_ne!(fnv_hash!("abc"), fnv_hash!("abc", ""));
+        assert_ne!(fnv_hash!(1u8, 2u8), fnv_hash!(2u8, 1u8));
+        assert_ne!(fnv_hash!(1u8), fnv_hash!(1u16));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn fnv_hash_matches_driving_the_hasher() {
+        let name = String::from("alice");
+
+        let mut expected = Fnv1a::<u64>::new();
+        write_node_header(&mut expected, 3);
+        "alice".visit(&mut expected);
+        42u32.visit(&mut expected);
+        true.visit(&mut expected);
+
+        assert_eq!(fnv_hash!(name, 42u32, true), expected.finish());
+        assert_eq!(fnv_hash!(fnv1a_64; &name, 42u32, true,), expected.finish());
+
+        let mut expected = Fnv0::<u128>::new();
+        write_node_header(&mut expected, 1);
+        'x'.visit(&mut expected);
+
+        assert_eq!(fnv_hash!(fnv0_128; 'x'), expected.finish());
+    }
+
+    #[test]
+    fn fnv_hash_framing_is_documented_encoding() {
+        let mut expected = Fnv1a::<u32>::new();
+        expected.write(&[0x01, 2, 0, 0, 0, 0, 0, 0, 0]);
+        expected.write(&[0x00, 2, 0, 0, 0, 0, 0, 0, 0]);
+        expected.write(b"ab");
+        expected.write(&[0x00, 4, 0, 0, 0, 0, 0, 0, 0]);
+        expected.write(&7u32.to_le_bytes());
+
+        assert_eq!(fnv_hash!(fnv1a_32; "ab", 7u32), expected.finish());
+    }
+
+    #[test]
+    fn fnv_hash_selectors_without_underscores() {
+        assert_eq!(
+            fnv_hash!(fnv0_32; "ab", 7u32),
+            fnv_hash!(fnv032; "ab", 7u32)
+        );
+        assert_eq!(fnv_hash!(fnv0_64; "ab"), fnv_hash!(fnv064; "ab"));
+        assert_eq!(fnv_hash!(fnv0_128; "ab"), fnv_hash!(fnv0128; "ab"));
+        assert_eq!(fnv_hash!(fnv1_32; "ab"), fnv_hash!(fnv132; "ab"));
+        assert_eq!(fnv_hash!(fnv1_64; "ab"), fnv_hash!(fnv164; "ab"));
+        assert_eq!(fnv_hash!(fnv1_128; "ab"), fnv_hash!(fnv1128; "ab"));
+        assert_eq!(fnv_hash!(fnv1a_32; "ab"), fnv_hash!(fnv1a32; "ab",));
+        assert_eq!(fnv_hash!(fnv1a_64; "ab"), fnv_hash!(fnv1a64; "ab"));
+        assert_eq!(fnv_hash!(fnv1a_128; "ab"), fnv_hash!(fnv1a128; "ab"));
+    }
+}
@@ -0,0 +1,173 @@
+//! Walking of a directory tree, shared by the directory hashers.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Options controlling which entries [`hash_dir_files`] visits.
+///
+/// [`hash_dir_files`]: fn.hash_dir_files.html
+#[derive(Debug, Clone)]
+pub struct DirHashOptions {
+    follow_symlinks: bool,
+    include_hidden: bool,
+}
+
+impl Default for DirHashOptions {
+    fn default() -> Self {
+        Self {
+            follow_symlinks: false,
+            include_hidden: true,
+        }
+    }
+}
+
+// `hash_dir` walks with the defaults, so only `hash_dir_files` sets options
+#[cfg_attr(not(feature = "rayon"), allow(dead_code))]
+impl DirHashOptions {
+    /// Creates the default options, which skip symbolic links and include
+    /// hidden entries.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether symbolic links are followed.
+    ///
+    /// When followed, a link to a file is hashed under the link's path and a
+    /// link to a directory is walked, visiting each directory at most once.
+    /// Otherwise links are skipped.
+    pub fn follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Sets whether hidden entries, whose names begin with `.`, are visited.
+    pub fn include_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+}
+
+/// Returns the paths, relative to `root` and sorted, of the regular files
+/// under `root` which `options` selects.
+pub(crate) fn walk(root: &Path, options: &DirHashOptions) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    walk_dir(root, root, options, &mut HashSet::new(), &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn walk_dir(
+    root: &Path,
+    dir: &Path,
+    options: &DirHashOptions,
+    visited: &mut HashSet<PathBuf>,
+    files: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    if options.follow_symlinks && !visited.insert(dir.canonicalize()?) {
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        if !options.include_hidden && entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let file_type = entry.file_type()?;
+        let file_type = if file_type.is_symlink() {
+            if !options.follow_symlinks {
+                continue;
+            }
+            fs::metadata(&path)?.file_type()
+        } else {
+            file_type
+        };
+
+        if file_type.is_dir() {
+            walk_dir(root, &path, options, visited, files)?;
+        } else if file_type.is_file() {
+            files.push(path.strip_prefix(root).unwrap().to_path_buf());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use tempfile::tempdir;
+
+    use walk::{walk, DirHashOptions};
+
+    pub(crate) fn create_tree(root: &Path) {
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::create_dir_all(root.join(".hidden_dir")).unwrap();
+        fs::write(root.join("top.txt"), b"top").unwrap();
+        fs::write(root.join("a/one.txt"), b"one").unwrap();
+        fs::write(root.join("a/b/two.txt"), b"two").unwrap();
+        fs::write(root.join("a/b/empty"), b"").unwrap();
+        fs::write(root.join(".hidden"), b"hidden").unwrap();
+        fs::write(root.join(".hidden_dir/inner"), b"inner").unwrap();
+    }
+
+    fn paths(paths: &[&str]) -> Vec<PathBuf> {
+        paths.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn walks_sorted_relative_paths() {
+        let dir = tempdir().unwrap();
+        create_tree(dir.path());
+        fs::create_dir_all(dir.path().join("a/empty_dir")).unwrap();
+
+        assert_eq!(
+            walk(dir.path(), &DirHashOptions::new()).unwrap(),
+            paths(&[
+                ".hidden",
+                ".hidden_dir/inner",
+                "a/b/empty",
+                "a/b/two.txt",
+                "a/one.txt",
+                "top.txt",
+            ])
+        );
+        assert_eq!(
+            walk(dir.path(), &DirHashOptions::new().include_hidden(false)).unwrap(),
+            paths(&["a/b/empty", "a/b/two.txt", "a/one.txt", "top.txt"])
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_symlinks_only_when_asked() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("a")).unwrap();
+        fs::write(dir.path().join("a/file"), b"file").unwrap();
+        symlink(dir.path().join("a/file"), dir.path().join("link")).unwrap();
+        symlink(dir.path().join("a"), dir.path().join("a/loop")).unwrap();
+
+        assert_eq!(
+            walk(dir.path(), &DirHashOptions::new()).unwrap(),
+            paths(&["a/file"])
+        );
+        assert_eq!(
+            walk(dir.path(), &DirHashOptions::new().follow_symlinks(true)).unwrap(),
+            paths(&["a/file", "link"])
+        );
+    }
+
+    #[test]
+    fn missing_root_fails() {
+        let dir = tempdir().unwrap();
+
+        assert!(walk(&dir.path().join("missing"), &DirHashOptions::new()).is_err());
+    }
+}
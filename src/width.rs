@@ -0,0 +1,180 @@
+//! The integer types which the FNV hashers are defined over.
+
+use step::{
+    fnv1_128_advance, fnv1_128_step, fnv1_128_unstep, fnv1_32_advance, fnv1_32_step,
+    fnv1_32_unstep, fnv1_64_advance, fnv1_64_step, fnv1_64_unstep, fnv1a_128_advance,
+    fnv1a_128_step, fnv1a_128_unstep, fnv1a_32_advance, fnv1a_32_step, fnv1a_32_unstep,
+    fnv1a_64_advance, fnv1a_64_step, fnv1a_64_unstep, PRIME_128, PRIME_32, PRIME_64,
+    PRIME_INVERSE_128, PRIME_INVERSE_32, PRIME_INVERSE_64,
+};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// An integer type which the FNV hashers are defined over.
+///
+/// This is implemented for `u32`, `u64` and `u128`, and sealed against other
+/// implementations, so a hasher of any other width does not compile:
+///
+/// ```compile_fail
+/// use lz_fnv::Fnv1a;
+///
+/// let _ = Fnv1a::<String>::with_key(String::new());
+/// ```
+pub trait FnvWidth: Copy + private::Sealed {
+    /// The width of the type in bits.
+    const BITS: u32;
+
+    /// Zero.
+    const ZERO: Self;
+
+    /// The FNV prime for this width.
+    const PRIME: Self;
+
+    /// The inverse of the FNV prime modulo `2^BITS`.
+    const PRIME_INVERSE: Self;
+
+    /// The FNV offset basis for this width.
+    const OFFSET_BASIS: Self;
+
+    /// Multiplies two values, wrapping on overflow.
+    fn wrapping_mul(self, rhs: Self) -> Self;
+
+    /// XORs a byte into the low 8 bits of the value.
+    fn xor_byte(self, byte: u8) -> Self;
+
+    /// Performs one FNV-1 step at this width, as `fnv1_64_step` does for
+    /// `u64`.
+    fn fnv1_step(state: Self, byte: u8) -> Self;
+
+    /// Performs one FNV-1a step at this width, as `fnv1a_64_step` does for
+    /// `u64`.
+    fn fnv1a_step(state: Self, byte: u8) -> Self;
+
+    /// Performs an FNV-1 step for each of `bytes` at this width, as
+    /// `fnv1_64_advance` does for `u64`.
+    fn fnv1_advance(state: Self, bytes: &[u8]) -> Self;
+
+    /// Performs an FNV-1a step for each of `bytes` at this width, as
+    /// `fnv1a_64_advance` does for `u64`.
+    fn fnv1a_advance(state: Self, bytes: &[u8]) -> Self;
+
+    /// Undoes one FNV-1 step at this width, as `fnv1_64_unstep` does for
+    /// `u64`.
+    fn fnv1_unstep(state: Self, byte: u8) -> Self;
+
+    /// Undoes one FNV-1a step at this width, as `fnv1a_64_unstep` does for
+    /// `u64`.
+    fn fnv1a_unstep(state: Self, byte: u8) -> Self;
+}
+
+macro_rules! fnv_width_impl {
+    (
+        $type: ty,
+        $bits: expr,
+        $prime: expr,
+        $inverse: expr,
+        $offset: expr,
+        $fnv1_step: ident,
+        $fnv1a_step: ident,
+        $fnv1_advance: ident,
+        $fnv1a_advance: ident,
+        $fnv1_unstep: ident,
+        $fnv1a_unstep: ident
+    ) => {
+        impl private::Sealed for $type {}
+
+        impl FnvWidth for $type {
+            const BITS: u32 = $bits;
+
+            const ZERO: Self = 0;
+
+            const PRIME: Self = $prime;
+
+            const PRIME_INVERSE: Self = $inverse;
+
+            const OFFSET_BASIS: Self = $offset;
+
+            #[inline]
+            fn wrapping_mul(self, rhs: Self) -> Self {
+                <$type>::wrapping_mul(self, rhs)
+            }
+
+            #[inline]
+            fn xor_byte(self, byte: u8) -> Self {
+                self ^ Self::from(byte)
+            }
+
+            #[inline]
+            fn fnv1_step(state: Self, byte: u8) -> Self {
+                $fnv1_step(state, byte)
+            }
+
+            #[inline]
+            fn fnv1a_step(state: Self, byte: u8) -> Self {
+                $fnv1a_step(state, byte)
+            }
+
+            #[inline]
+            fn fnv1_advance(state: Self, bytes: &[u8]) -> Self {
+                $fnv1_advance(state, bytes)
+            }
+
+            #[inline]
+            fn fnv1a_advance(state: Self, bytes: &[u8]) -> Self {
+                $fnv1a_advance(state, bytes)
+            }
+
+            #[inline]
+            fn fnv1_unstep(state: Self, byte: u8) -> Self {
+                $fnv1_unstep(state, byte)
+            }
+
+            #[inline]
+            fn fnv1a_unstep(state: Self, byte: u8) -> Self {
+                $fnv1a_unstep(state, byte)
+            }
+        }
+    };
+}
+
+fnv_width_impl!(
+    u32,
+    32,
+    PRIME_32,
+    PRIME_INVERSE_32,
+    0x811c_9dc5,
+    fnv1_32_step,
+    fnv1a_32_step,
+    fnv1_32_advance,
+    fnv1a_32_advance,
+    fnv1_32_unstep,
+    fnv1a_32_unstep
+);
+fnv_width_impl!(
+    u64,
+    64,
+    PRIME_64,
+    PRIME_INVERSE_64,
+    0xcbf2_9ce4_8422_2325,
+    fnv1_64_step,
+    fnv1a_64_step,
+    fnv1_64_advance,
+    fnv1a_64_advance,
+    fnv1_64_unstep,
+    fnv1a_64_unstep
+);
+fnv_width_impl!(
+    u128,
+    128,
+    PRIME_128,
+    PRIME_INVERSE_128,
+    0x6C62_272E_07BB_0142_62B8_2175_6295_C58D,
+    fnv1_128_step,
+    fnv1a_128_step,
+    fnv1_128_advance,
+    fnv1a_128_advance,
+    fnv1_128_unstep,
+    fnv1a_128_unstep
+);
@@ -0,0 +1,65 @@
+//! The per-width modules of hashers and constants.
+
+macro_rules! width_module {
+    ($type: ident, $bits: expr, $prime: expr, $offset: expr) => {
+        #[doc = concat!("The FNV hashers and constants for ", $bits, "-bit hashes.")]
+        ///
+        /// ```
+        /// use lz_fnv::FnvHash;
+        #[doc = concat!("use lz_fnv::", stringify!($type), "::{Fnv1a, OFFSET_BASIS};")]
+        ///
+        /// let fnv_hasher = Fnv1a::new();
+        ///
+        /// assert_eq!(fnv_hasher.finish(), OFFSET_BASIS);
+        /// ```
+        pub mod $type {
+            use FnvWidth;
+
+            #[doc = concat!("The FNV prime for ", $bits, "-bit hashes.")]
+            pub const PRIME: $type = <$type as FnvWidth>::PRIME;
+
+            #[doc = concat!("The FNV offset basis for ", $bits, "-bit hashes.")]
+            pub const OFFSET_BASIS: $type = <$type as FnvWidth>::OFFSET_BASIS;
+
+            #[doc = concat!("The ", $bits, "-bit FNV-0 hasher.")]
+            pub type Fnv0 = ::Fnv0<$type>;
+
+            #[doc = concat!("The ", $bits, "-bit FNV-1 hasher.")]
+            pub type Fnv1 = ::Fnv1<$type>;
+
+            #[doc = concat!("The ", $bits, "-bit FNV-1a hasher.")]
+            pub type Fnv1a = ::Fnv1a<$type>;
+
+            #[cfg(test)]
+            mod tests {
+                use super::{Fnv0, Fnv1, Fnv1a, OFFSET_BASIS, PRIME};
+                use FnvHash;
+
+                #[test]
+                fn constants_match_the_specification() {
+                    assert_eq!(PRIME, $prime);
+                    assert_eq!(OFFSET_BASIS, $offset);
+                }
+
+                #[test]
+                fn types_match_top_level_types() {
+                    assert_eq!(Fnv0::new().finish(), ::Fnv0::<$type>::new().finish());
+                    assert_eq!(Fnv1::new().finish(), OFFSET_BASIS);
+                    assert_eq!(
+                        Fnv1a::with_key(7).finish(),
+                        ::Fnv1a::<$type>::with_key(7).finish()
+                    );
+                }
+            }
+        }
+    };
+}
+
+width_module!(
+    u128,
+    128,
+    0x0000_0000_0100_0000_0000_0000_0000_013b,
+    0x6c62_272e_07bb_0142_62b8_2175_6295_c58d
+);
+width_module!(u32, 32, 0x0100_0193, 0x811c_9dc5);
+width_module!(u64, 64, 0x0000_0100_0000_01b3, 0xcbf2_9ce4_8422_2325);
@@ -0,0 +1,185 @@
+//! A word-at-a-time variant of FNV-1a for speed over large buffers.
+
+use step::PRIME_64;
+use FnvHash;
+
+const WORD: usize = 8;
+
+/// A hash in the style of FNV-1a which consumes its input 8 bytes at a time.
+///
+/// This is **not** the FNV hash and its output differs from `Fnv1a<u64>` on
+/// every input of 8 or more bytes. Each complete 8-byte word of the input is
+/// read as a big-endian `u64`, XORed into the state and multiplied by the
+/// 64-bit FNV prime. Words are taken from the input as a whole, so the result
+/// does not depend on how the input is split between calls to `write`.
+///
+/// The trailing 1 to 7 bytes which do not fill a word are zero-padded on the
+/// right into a final word, which is folded in the same way, and the number
+/// of trailing bytes is then folded in as a final word so that the padding
+/// cannot collide with real zero bytes.
+///
+/// ```
+/// use lz_fnv::{Fnv1aWords, FnvHash};
+///
+/// let mut fnv_hasher = Fnv1aWords::<u64>::new();
+/// fnv_hasher.write(b"foobar");
+///
+/// assert_ne!(fnv_hasher.finish(), 0x8594_4171_f739_67e8);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fnv1aWords<T> {
+    hash: T,
+    tail: [u8; WORD],
+    tail_len: usize,
+}
+
+fn fold(hash: u64, word: u64) -> u64 {
+    (hash ^ word).wrapping_mul(PRIME_64)
+}
+
+impl Fnv1aWords<u64> {
+    /// Creates a new `Fnv1aWords<u64>`, starting from the FNV-1a 64 offset
+    /// basis.
+    pub fn new() -> Self {
+        Self::with_key(0xcbf2_9ce4_8422_2325)
+    }
+
+    /// Creates a new `Fnv1aWords<u64>` with the specified key.
+    pub fn with_key(key: u64) -> Self {
+        Self {
+            hash: key,
+            tail: [0; WORD],
+            tail_len: 0,
+        }
+    }
+}
+
+impl Default for Fnv1aWords<u64> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FnvHash for Fnv1aWords<u64> {
+    type Hash = u64;
+
+    fn finish(&self) -> u64 {
+        if self.tail_len == 0 {
+            return self.hash;
+        }
+
+        let mut padded = [0; WORD];
+        padded[..self.tail_len].copy_from_slice(&self.tail[..self.tail_len]);
+
+        let hash = fold(self.hash, u64::from_be_bytes(padded));
+        fold(hash, self.tail_len as u64)
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) {
+        if self.tail_len > 0 {
+            let take = (WORD - self.tail_len).min(bytes.len());
+            self.tail[self.tail_len..self.tail_len + take].copy_from_slice(&bytes[..take]);
+            self.tail_len += take;
+            bytes = &bytes[take..];
+
+            if self.tail_len < WORD {
+                return;
+            }
+
+            self.hash = fold(self.hash, u64::from_be_bytes(self.tail));
+            self.tail_len = 0;
+        }
+
+        let mut words = bytes.chunks_exact(WORD);
+        for word in &mut words {
+            let mut buf = [0; WORD];
+            buf.copy_from_slice(word);
+            self.hash = fold(self.hash, u64::from_be_bytes(buf));
+        }
+
+        let rest = words.remainder();
+        self.tail[..rest.len()].copy_from_slice(rest);
+        self.tail_len = rest.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use step::PRIME_64;
+    use {Fnv1aWords, FnvHash};
+
+    fn hash(bytes: &[u8]) -> u64 {
+        let mut hasher = Fnv1aWords::<u64>::new();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    #[test]
+    fn empty_is_offset_basis() {
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+    }
+
+    #[test]
+    fn whole_words() {
+        let expected = ((0xcbf2_9ce4_8422_2325u64 ^ 0x0102_0304_0506_0708).wrapping_mul(PRIME_64)
+            ^ 0x1112_1314_1516_1718)
+            .wrapping_mul(PRIME_64);
+
+        assert_eq!(
+            hash(&[1, 2, 3, 4, 5, 6, 7, 8, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18]),
+            expected
+        );
+    }
+
+    #[test]
+    fn tail_is_padded_and_counted() {
+        let padded = (0xcbf2_9ce4_8422_2325u64 ^ 0x6162_6300_0000_0000).wrapping_mul(PRIME_64);
+        let expected = (padded ^ 3).wrapping_mul(PRIME_64);
+
+        assert_eq!(hash(b"abc"), expected);
+        assert_ne!(hash(b"abc"), hash(b"abc\0\0\0\0\0"));
+        assert_ne!(hash(b"\0"), hash(b"\0\0\0\0\0\0\0\0"));
+        assert_ne!(hash(b"\0"), hash(b"\0\0"));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn deterministic_across_splits() {
+        let input: Vec<u8> = (0..100u8).map(|i| i.wrapping_mul(37)).collect();
+
+        for len in 0..input.len() {
+            let expected = hash(&input[..len]);
+            assert_eq!(hash(&input[..len]), expected);
+
+            for split in 0..=len {
+                let mut hasher = Fnv1aWords::<u64>::new();
+                hasher.write(&input[..split]);
+                hasher.write(&input[split..len]);
+
+                assert_eq!(hasher.finish(), expected, "len {} split {}", len, split);
+            }
+        }
+    }
+
+    #[test]
+    fn byte_at_a_time() {
+        let input = b"chongo was here!\n";
+
+        let mut hasher = Fnv1aWords::<u64>::new();
+        for byte in input.iter() {
+            hasher.write(&[*byte]);
+        }
+
+        assert_eq!(hasher.finish(), hash(input));
+    }
+
+    #[test]
+    fn finish_does_not_consume_tail() {
+        let mut hasher = Fnv1aWords::<u64>::new();
+        hasher.write(b"abcd");
+        let _ = hasher.finish();
+        hasher.write(b"efgh");
+
+        assert_eq!(hasher.finish(), hash(b"abcdefgh"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Storing file hashes in extended attributes.
+
+use std::io;
+use std::path::Path;
+
+use xattr;
+
+use hash_file;
+
+/// The extended attribute under which file hashes are stored.
+///
+/// The value is the FNV-1a 64 hash of the file's contents as 8 big-endian
+/// bytes.
+pub const XATTR_NAME: &str = "user.lz_fnv.fnv1a64";
+
+/// The outcome of verifying a file against its stored hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    /// The file's contents match the stored hash.
+    Match,
+    /// The file's contents do not match the stored hash.
+    Mismatch {
+        /// The hash stored in the extended attribute.
+        stored: u64,
+        /// The hash of the file's current contents.
+        actual: u64,
+    },
+    /// The file has no stored hash.
+    Missing,
+}
+
+/// Hashes a file with FNV-1a 64 and stores the hash in its [`XATTR_NAME`]
+/// extended attribute, returning the hash.
+///
+/// Errors from filesystems which do not support extended attributes are
+/// returned unchanged.
+///
+/// [`XATTR_NAME`]: constant.XATTR_NAME.html
+pub fn store_xattr_hash<P: AsRef<Path>>(path: P) -> io::Result<u64> {
+    let path = path.as_ref();
+    let hash = hash_file(path)?;
+
+    xattr::set(path, XATTR_NAME, &hash.to_be_bytes())?;
+    Ok(hash)
+}
+
+/// Reads the hash stored in a file's [`XATTR_NAME`] extended attribute, if
+/// any.
+///
+/// Returns an error of kind `InvalidData` if the attribute is not 8 bytes.
+///
+/// [`XATTR_NAME`]: constant.XATTR_NAME.html
+pub fn read_xattr_hash<P: AsRef<Path>>(path: P) -> io::Result<Option<u64>> {
+    match xattr::get(path, XATTR_NAME)? {
+        Some(value) => {
+            if value.len() != 8 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stored hash is not 8 bytes",
+                ));
+            }
+
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&value);
+            Ok(Some(u64::from_be_bytes(bytes)))
+        }
+        None => Ok(None),
+    }
+}
+
+/// Verifies a file's contents against the hash stored in its [`XATTR_NAME`]
+/// extended attribute.
+///
+/// [`XATTR_NAME`]: constant.XATTR_NAME.html
+pub fn verify_xattr_hash<P: AsRef<Path>>(path: P) -> io::Result<VerifyOutcome> {
+    let path = path.as_ref();
+
+    let stored = match read_xattr_hash(path)? {
+        Some(stored) => stored,
+        None => return Ok(VerifyOutcome::Missing),
+    };
+
+    let actual = hash_file(path)?;
+    if actual == stored {
+        Ok(VerifyOutcome::Match)
+    } else {
+        Ok(VerifyOutcome::Mismatch { stored, actual })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+    use tempfile::tempdir;
+    use xattr;
+
+    use super::XATTR_NAME;
+    use {hash_file, read_xattr_hash, store_xattr_hash, verify_xattr_hash, VerifyOutcome};
+
+    /// Stores the hash of `path`, or returns `None` if the filesystem does not
+    /// support extended attributes.
+    fn store(path: &Path) -> Option<u64> {
+        match store_xattr_hash(path) {
+            Ok(hash) => Some(hash),
+            Err(ref e) if e.kind() == io::ErrorKind::Unsupported => None,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data");
+        fs::write(&path, b"foobar").unwrap();
+
+        let hash = match store(&path) {
+            Some(hash) => hash,
+            None => return,
+        };
+
+        assert_eq!(hash, 0x8594_4171_f739_67e8);
+        assert_eq!(
+            xattr::get(&path, XATTR_NAME).unwrap(),
+            Some(hash.to_be_bytes().to_vec())
+        );
+        assert_eq!(read_xattr_hash(&path).unwrap(), Some(hash));
+        assert_eq!(verify_xattr_hash(&path).unwrap(), VerifyOutcome::Match);
+    }
+
+    #[test]
+    fn detects_modification() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data");
+        fs::write(&path, b"foobar").unwrap();
+
+        let stored = match store(&path) {
+            Some(hash) => hash,
+            None => return,
+        };
+        fs::write(&path, b"foobaz").unwrap();
+
+        assert_eq!(
+            verify_xattr_hash(&path).unwrap(),
+            VerifyOutcome::Mismatch {
+                stored,
+                actual: hash_file(&path).unwrap(),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_attribute() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data");
+        fs::write(&path, b"foobar").unwrap();
+
+        match read_xattr_hash(&path) {
+            Ok(stored) => assert_eq!(stored, None),
+            Err(ref e) if e.kind() == io::ErrorKind::Unsupported => return,
+            Err(e) => panic!("{}", e),
+        }
+        assert_eq!(verify_xattr_hash(&path).unwrap(), VerifyOutcome::Missing);
+    }
+
+    #[test]
+    fn malformed_attribute() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("data");
+        fs::write(&path, b"foobar").unwrap();
+
+        if store(&path).is_none() {
+            return;
+        }
+        xattr::set(&path, XATTR_NAME, b"short").unwrap();
+
+        assert_eq!(
+            read_xattr_hash(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn missing_file() {
+        let dir = tempdir().unwrap();
+
+        assert!(store_xattr_hash(dir.path().join("missing")).is_err());
+    }
+}